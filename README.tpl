@@ -8,6 +8,9 @@
 
 ## Benchmarks
 
+The benchmarks are run with `cargo bench --features bench`. The same
+feature adds `punkt::bench`, which measures throughput on your own documents.
+
 Specs of my machine:
 
   * i5-4460 @ 3.20 x 4
@@ -0,0 +1,19 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A seed Arabic model of the `punkt` crate. NLTK has no Arabic model, so it
+//! only contains common abbreviations, like the ones for doctor, page, and BC,
+//! which the orthographic heuristic can't find in a caseless script. Training
+//! on your own documents, on top of it, gives better results. Enable the
+//! `arabic` feature of `punkt` to load it with `TrainingData::arabic()`,
+//! instead of depending on this crate.
+
+#![no_std]
+
+/// The model, in the JSON format read by `punkt`.
+pub const MODEL: &str = include_str!("../arabic.json");
@@ -0,0 +1,16 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The pretrained Dutch model of the `punkt` crate, which was converted from
+//! NLTK. Enable the `dutch` feature of `punkt` to load it with
+//! `TrainingData::dutch()`, instead of depending on this crate.
+
+#![no_std]
+
+/// The model, in the JSON format read by `punkt`.
+pub const MODEL: &str = include_str!("../dutch.json");
@@ -10,6 +10,7 @@ use phf::phf_set;
 struct MyParams;
 
 impl DefinesInternalPunctuation for MyParams {}
+impl DefinesMultiChars for MyParams {}
 impl DefinesNonPrefixCharacters for MyParams {}
 impl DefinesNonWordCharacters for MyParams {
   const NONWORD_CHARS: &'static Set<char> = &phf_set![
@@ -18,6 +19,8 @@ impl DefinesNonWordCharacters for MyParams {
 }
 impl DefinesPunctuation for MyParams {}
 impl DefinesSentenceEndings for MyParams {}
+impl DefinesTypeNormalization for MyParams {}
+impl TokenizerParameters for MyParams {}
 
 impl TrainerParameters for MyParams {
   const ABBREV_LOWER_BOUND: f64 = 0.3;
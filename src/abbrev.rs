@@ -0,0 +1,135 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::marker::PhantomData;
+
+use model::Model;
+use prelude::TokenizerParameters;
+use token::Token;
+use trainer::TrainingData;
+
+/// Decides if periods in a document belong to abbreviations, without
+/// segmenting the document into sentences. Only the word around each
+/// period is looked at, using the same rules as the first pass of the
+/// sentence tokenizer.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{AbbrevDetector, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "I met Mr. Smith at 5 p.m. today.";
+/// let data = TrainingData::english();
+/// let detector = AbbrevDetector::<Standard>::new(&data);
+///
+/// assert!(detector.is_abbrev_at(doc, 8));
+/// assert!(!detector.is_abbrev_at(doc, doc.len() - 1));
+/// assert_eq!(detector.abbrev_offsets(doc).collect::<Vec<_>>(), vec![8, 24]);
+/// # }
+/// ```
+pub struct AbbrevDetector<'a, P, D: 'a = TrainingData> {
+  data: &'a D,
+  params: PhantomData<P>,
+}
+
+impl<'a, P, D> AbbrevDetector<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Creates a new `AbbrevDetector`.
+  #[inline(always)]
+  pub fn new(data: &'a D) -> AbbrevDetector<'a, P, D> {
+    AbbrevDetector {
+      data,
+      params: PhantomData,
+    }
+  }
+
+  /// Checks if the period at a byte offset in a document ends an
+  /// abbreviation. Returns false if there is no period at the offset, if
+  /// the period is inside of a word, or if it is part of an ellipsis.
+  pub fn is_abbrev_at(&self, doc: &str, idx: usize) -> bool {
+    let bytes = doc.as_bytes();
+
+    if bytes.get(idx) != Some(&b'.') || (idx > 0 && bytes[idx - 1] == b'.') {
+      return false;
+    }
+
+    // Only the final period of a word can end an abbreviation.
+    match doc[idx + 1..].chars().next() {
+      Some(c) if c == '.' || c.is_alphanumeric() => return false,
+      _ => (),
+    }
+
+    // Find the start of the word that ends with the period. Words are split
+    // on whitespace, and characters that can't be inside of a word.
+    let start = doc[..idx]
+      .char_indices()
+      .rev()
+      .find(|&(_, c)| c.is_whitespace() || P::is_nonword_char(&c))
+      .map(|(i, c)| i + c.len_utf8())
+      .unwrap_or(0);
+    let word = doc[start..idx + 1].trim_start_matches(|c| P::is_nonprefix_char(&c));
+
+    if word.len() < 2 {
+      return false;
+    }
+
+    let mut tok = Token::new::<P>(word, false, false, false);
+
+    ::util::annotate_first_pass::<P, _>(&mut tok, self.data);
+    tok.is_abbrev()
+  }
+
+  /// Checks each of the byte offsets in a document, in order. See
+  /// `is_abbrev_at`.
+  #[inline]
+  pub fn are_abbrevs_at(&self, doc: &str, idxs: &[usize]) -> Vec<bool> {
+    idxs.iter().map(|&i| self.is_abbrev_at(doc, i)).collect()
+  }
+
+  /// Returns an iterator over the byte offsets of the periods in a document
+  /// that end an abbreviation.
+  #[inline]
+  pub fn abbrev_offsets<'b>(&'b self, doc: &'b str) -> impl Iterator<Item = usize> + 'b {
+    doc
+      .match_indices('.')
+      .map(|(i, _)| i)
+      .filter(move |&i| self.is_abbrev_at(doc, i))
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn abbrev_detector_test() {
+  use prelude::Standard;
+
+  let data = TrainingData::english();
+  let detector = AbbrevDetector::<Standard>::new(&data);
+  let doc = "(Dr. Who) and co. Go... The U.S. is big. Visit e.g.";
+
+  assert!(detector.is_abbrev_at(doc, doc.find("Dr.").unwrap() + 2));
+  assert!(!detector.is_abbrev_at(doc, doc.find("...").unwrap() + 1));
+  assert!(!detector.is_abbrev_at(doc, doc.find("big.").unwrap() + 3));
+  assert!(!detector.is_abbrev_at(doc, 0));
+  assert!(!detector.is_abbrev_at(doc, doc.find("U.S.").unwrap() + 1));
+  assert!(!detector.is_abbrev_at(doc, doc.len()));
+  assert_eq!(
+    detector
+      .abbrev_offsets(doc)
+      .map(|i| &doc[..i + 1])
+      .map(|s| s.rsplit(' ').next().unwrap())
+      .collect::<Vec<_>>(),
+    vec!["(Dr.", "co.", "U.S."]
+  );
+  assert_eq!(detector.are_abbrevs_at(doc, &[3, 4]), vec![true, false]);
+}
@@ -0,0 +1,373 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Measures the throughput of the tokenizers, and the trainer, on any
+//! documents. Requires the `bench` feature.
+//!
+//! Throughput depends on the text, so the numbers of this crate's own
+//! benchmarks don't say much about other corpora. Running these on a sample
+//! of your own documents, before, and after upgrading, or changing the
+//! parameters, shows if segmentation got slower for you.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "english")]
+//! # {
+//! # use punkt::TrainingData;
+//! # use punkt::bench;
+//! # use punkt::params::Standard;
+//! #
+//! let docs = ["Mr. Smith went to Washington. He arrived at 5 p.m. sharp."];
+//! let report = bench::sentence_tokenizer::<Standard, _>(&docs, &TrainingData::english(), 10);
+//!
+//! assert_eq!(report.sentences, 20);
+//! println!("{:.0} sentences/sec", report.sentences_per_sec());
+//! # }
+//! ```
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use model::Model;
+use prelude::{
+  DefinesMultiChars, DefinesNonPrefixCharacters, DefinesNonWordCharacters,
+  DefinesTypeNormalization, TokenizerParameters, TrainerParameters,
+};
+use tokenizer::{SentenceByteOffsetTokenizer, WordTokenizer};
+use trainer::{Trainer, TrainingData};
+
+/// The work done by a benchmark, and how long it took. Counts are totals
+/// over every iteration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+  /// The number of documents.
+  pub documents: usize,
+  /// The number of times each document was processed.
+  pub iterations: usize,
+  /// The number of bytes processed.
+  pub bytes: usize,
+  /// The number of word tokens processed.
+  pub tokens: usize,
+  /// The number of sentences found. Always 0 for benchmarks that don't
+  /// segment sentences.
+  pub sentences: usize,
+  /// The time spent processing the documents.
+  pub elapsed: Duration,
+}
+
+impl Report {
+  /// The number of bytes processed in a second.
+  #[inline]
+  pub fn bytes_per_sec(&self) -> f64 {
+    per_sec(self.bytes, self.elapsed)
+  }
+
+  /// The number of word tokens processed in a second.
+  #[inline]
+  pub fn tokens_per_sec(&self) -> f64 {
+    per_sec(self.tokens, self.elapsed)
+  }
+
+  /// The number of sentences found in a second.
+  #[inline]
+  pub fn sentences_per_sec(&self) -> f64 {
+    per_sec(self.sentences, self.elapsed)
+  }
+}
+
+#[inline]
+fn per_sec(n: usize, elapsed: Duration) -> f64 {
+  let secs = elapsed.as_secs_f64();
+
+  if secs > 0f64 {
+    n as f64 / secs
+  } else {
+    0f64
+  }
+}
+
+/// Times a pass over every document, `iterations` times. Tokens are
+/// counted before timing, so counting doesn't add to the time.
+fn run<P, F>(docs: &[&str], iterations: usize, mut pass: F) -> Report
+where
+  P: DefinesMultiChars
+    + DefinesNonPrefixCharacters
+    + DefinesNonWordCharacters
+    + DefinesTypeNormalization,
+  F: FnMut(&str) -> usize,
+{
+  let tokens: usize = docs.iter().map(|doc| word_pass::<P>(doc)).sum();
+  let bytes: usize = docs.iter().map(|doc| doc.len()).sum();
+  let mut sentences = 0;
+  let start = Instant::now();
+
+  for _ in 0..iterations {
+    for doc in docs.iter() {
+      sentences += black_box(pass(doc));
+    }
+  }
+
+  Report {
+    documents: docs.len(),
+    iterations: iterations,
+    bytes: bytes * iterations,
+    tokens: tokens * iterations,
+    sentences: sentences,
+    elapsed: start.elapsed(),
+  }
+}
+
+/// Measures `WordTokenizer`.
+pub fn word_tokenizer<P>(docs: &[&str], iterations: usize) -> Report
+where
+  P: DefinesMultiChars
+    + DefinesNonPrefixCharacters
+    + DefinesNonWordCharacters
+    + DefinesTypeNormalization,
+{
+  run::<P, _>(docs, iterations, |doc| {
+    black_box(word_pass::<P>(doc));
+    0
+  })
+}
+
+/// Measures `SentenceByteOffsetTokenizer`, with a model.
+pub fn sentence_tokenizer<P, D>(docs: &[&str], data: &D, iterations: usize) -> Report
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  run::<P, _>(docs, iterations, |doc| sentence_pass::<P, D>(doc, data))
+}
+
+/// Measures training a new model on each document.
+pub fn trainer<P>(docs: &[&str], iterations: usize) -> Report
+where
+  P: TokenizerParameters + TrainerParameters,
+{
+  run::<P, _>(docs, iterations, |doc| {
+    black_box(train_pass::<P>(doc));
+    0
+  })
+}
+
+/// Tokenizes the words of a document, and returns how many there are.
+#[inline]
+fn word_pass<P>(doc: &str) -> usize
+where
+  P: DefinesMultiChars
+    + DefinesNonPrefixCharacters
+    + DefinesNonWordCharacters
+    + DefinesTypeNormalization,
+{
+  WordTokenizer::<P>::new(doc).count()
+}
+
+/// Segments a document, and returns how many sentences there are.
+#[inline]
+fn sentence_pass<P, D>(doc: &str, data: &D) -> usize
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  SentenceByteOffsetTokenizer::<P, D>::new(doc, data).count()
+}
+
+/// Trains a new model on a document.
+#[inline]
+fn train_pass<P>(doc: &str) -> TrainingData
+where
+  P: TokenizerParameters + TrainerParameters,
+{
+  let mut data = TrainingData::new();
+
+  Trainer::<P>::new().train(doc, &mut data);
+  data
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn bench_report_test() {
+  use prelude::Standard;
+
+  let docs = ["One. Two. Three.", "Dr. Smith left."];
+  let data = TrainingData::english();
+  let report = sentence_tokenizer::<Standard, _>(&docs, &data, 3);
+
+  assert_eq!(report.documents, 2);
+  assert_eq!(report.iterations, 3);
+  assert_eq!(report.bytes, 31 * 3);
+  assert_eq!(report.tokens, 6 * 3);
+  assert_eq!(report.sentences, 4 * 3);
+
+  let report = word_tokenizer::<Standard>(&docs, 2);
+
+  assert_eq!(report.tokens, 6 * 2);
+  assert_eq!(report.sentences, 0);
+  assert_eq!(trainer::<Standard>(&docs, 1).tokens, 6);
+  assert_eq!(Report::default().tokens_per_sec(), 0f64);
+  assert_eq!(
+    Report {
+      tokens: 10,
+      elapsed: Duration::from_millis(500),
+      ..Report::default()
+    }
+    .tokens_per_sec(),
+    20f64
+  );
+}
+
+#[cfg(test)]
+macro_rules! bench_word_tokenizer(
+  ($name:ident, $doc:expr) => (
+    #[bench] fn $name(b: &mut ::test::Bencher) {
+      b.iter(|| word_pass::<::prelude::Standard>($doc))
+    }
+  )
+);
+
+#[cfg(test)]
+bench_word_tokenizer!(
+  word_tokenizer_bench_short,
+  include_str!("../test/raw/sigma-wiki.txt")
+);
+
+#[cfg(test)]
+bench_word_tokenizer!(
+  word_tokenizer_bench_medium,
+  include_str!("../test/raw/npr-article-01.txt")
+);
+
+#[cfg(test)]
+bench_word_tokenizer!(
+  word_tokenizer_bench_long,
+  include_str!("../test/raw/the-sayings-of-confucius.txt")
+);
+
+#[cfg(test)]
+bench_word_tokenizer!(
+  word_tokenizer_bench_very_long,
+  include_str!("../test/raw/pride-and-prejudice.txt")
+);
+
+#[cfg(test)]
+macro_rules! bench_sentence_tokenizer(
+  ($name:ident, $doc:expr) => (
+    #[bench] fn $name(b: &mut ::test::Bencher) {
+      let doc = $doc;
+
+      b.iter(|| {
+        let data = train_pass::<::prelude::Standard>(doc);
+
+        sentence_pass::<::prelude::Standard, _>(doc, &data)
+      })
+    }
+  )
+);
+
+#[cfg(test)]
+bench_sentence_tokenizer!(
+  bench_sentence_tokenizer_train_on_document_short,
+  include_str!("../test/raw/sigma-wiki.txt")
+);
+
+#[cfg(test)]
+bench_sentence_tokenizer!(
+  bench_sentence_tokenizer_train_on_document_medium,
+  include_str!("../test/raw/npr-article-01.txt")
+);
+
+#[cfg(test)]
+bench_sentence_tokenizer!(
+  bench_sentence_tokenizer_train_on_document_long,
+  include_str!("../test/raw/pride-and-prejudice.txt")
+);
+
+#[cfg(all(test, feature = "english"))]
+macro_rules! bench_sentence_tokenizer_english(
+  ($name:ident, $doc:expr, $filter:expr) => (
+    #[bench] fn $name(b: &mut ::test::Bencher) {
+      let doc = $doc;
+      let mut data = TrainingData::english();
+
+      if $filter {
+        data.enable_lookup_filter();
+      }
+
+      b.iter(|| sentence_pass::<::prelude::Standard, _>(doc, &data))
+    }
+  )
+);
+
+#[cfg(all(test, feature = "english"))]
+bench_sentence_tokenizer_english!(
+  bench_sentence_tokenizer_english_long,
+  include_str!("../test/raw/pride-and-prejudice.txt"),
+  false
+);
+
+#[cfg(all(test, feature = "english"))]
+bench_sentence_tokenizer_english!(
+  bench_sentence_tokenizer_english_long_lookup_filter,
+  include_str!("../test/raw/pride-and-prejudice.txt"),
+  true
+);
+
+#[cfg(test)]
+macro_rules! bench_trainer(
+  ($name:ident, $doc:expr) => (
+    #[bench] fn $name(b: &mut ::test::Bencher) {
+      b.iter(|| train_pass::<::prelude::Standard>($doc))
+    }
+  )
+);
+
+#[cfg(test)]
+bench_trainer!(
+  bench_trainer_short,
+  include_str!("../test/raw/sigma-wiki.txt")
+);
+
+#[cfg(test)]
+bench_trainer!(
+  bench_trainer_medium,
+  include_str!("../test/raw/npr-article-01.txt")
+);
+
+#[cfg(test)]
+bench_trainer!(
+  bench_trainer_long,
+  include_str!("../test/raw/the-sayings-of-confucius.txt")
+);
+
+#[cfg(test)]
+bench_trainer!(
+  bench_trainer_very_long,
+  include_str!("../test/raw/pride-and-prejudice.txt")
+);
+
+#[cfg(all(test, feature = "english"))]
+#[bench]
+fn bench_sentence_count_english_long(b: &mut ::test::Bencher) {
+  let doc = include_str!("../test/raw/pride-and-prejudice.txt");
+  let data = TrainingData::english();
+
+  b.iter(|| ::estimate::sentence_count(doc, &data))
+}
+
+// The sentences that `bench_sentence_count_english_long` estimates, counted
+// exactly.
+#[cfg(all(test, feature = "english"))]
+#[bench]
+fn bench_sentence_count_byte_offset_english_long(b: &mut ::test::Bencher) {
+  let doc = include_str!("../test/raw/pride-and-prejudice.txt");
+  let data = TrainingData::english();
+
+  b.iter(|| SentenceByteOffsetTokenizer::<::prelude::Standard>::new(doc, &data).count())
+}
@@ -0,0 +1,269 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Command line tools for punkt. Requires the `cli` feature.
+//!
+//! ```text
+//! punkt eval --gold <dir> --raw <dir> [--model <model>] [--worst <n>]
+//! ```
+//!
+//! `eval` segments each file in the raw directory, and compares the sentence
+//! boundaries to the file with the same name in the gold directory. Gold
+//! files contain one sentence per line, in the format of the files in
+//! `test/sentence`: wrapped in brackets, with newlines and quotes escaped.
+//! The model can be a JSON, or binary file, or a punkt_tab directory. If no
+//! model is given, a model is trained on each raw file before it is
+//! segmented.
+
+extern crate punkt;
+
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use punkt::params::Standard;
+use punkt::{SentenceByteOffsetTokenizer, Trainer, TrainingData};
+
+const USAGE: &'static str =
+  "usage: punkt eval --gold <dir> --raw <dir> [--model <model>] [--worst <n>]";
+
+struct EvalArgs {
+  gold: PathBuf,
+  raw: PathBuf,
+  model: Option<PathBuf>,
+  worst: usize,
+}
+
+/// Boundary counts for a single file, or an entire corpus.
+#[derive(Default)]
+struct Score {
+  true_positives: usize,
+  predicted: usize,
+  expected: usize,
+}
+
+impl Score {
+  fn precision(&self) -> f64 {
+    ratio(self.true_positives, self.predicted)
+  }
+
+  fn recall(&self) -> f64 {
+    ratio(self.true_positives, self.expected)
+  }
+
+  fn f1(&self) -> f64 {
+    let (p, r) = (self.precision(), self.recall());
+
+    if p + r == 0.0 {
+      0.0
+    } else {
+      2.0 * p * r / (p + r)
+    }
+  }
+
+  fn add(&mut self, other: &Score) {
+    self.true_positives += other.true_positives;
+    self.predicted += other.predicted;
+    self.expected += other.expected;
+  }
+}
+
+#[inline]
+fn ratio(n: usize, d: usize) -> f64 {
+  if d == 0 {
+    1.0
+  } else {
+    n as f64 / d as f64
+  }
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+
+  let result = match args.first().map(|s| &s[..]) {
+    Some("eval") => parse_eval_args(&args[1..]).and_then(|args| eval(&args)),
+    _ => Err(USAGE.to_string()),
+  };
+
+  if let Err(e) = result {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+}
+
+fn parse_eval_args(args: &[String]) -> Result<EvalArgs, String> {
+  let mut gold = None;
+  let mut raw = None;
+  let mut model = None;
+  let mut worst = 5;
+  let mut iter = args.iter();
+
+  while let Some(flag) = iter.next() {
+    let value = match iter.next() {
+      Some(v) => v,
+      None => return Err(format!("missing value for {}\n{}", flag, USAGE)),
+    };
+
+    match &flag[..] {
+      "--gold" => gold = Some(PathBuf::from(value)),
+      "--raw" => raw = Some(PathBuf::from(value)),
+      "--model" => model = Some(PathBuf::from(value)),
+      "--worst" => {
+        worst = value
+          .parse()
+          .map_err(|_| format!("invalid value for --worst: {}", value))?
+      }
+      _ => return Err(format!("unknown argument: {}\n{}", flag, USAGE)),
+    }
+  }
+
+  match (gold, raw) {
+    (Some(gold), Some(raw)) => Ok(EvalArgs {
+      gold,
+      raw,
+      model,
+      worst,
+    }),
+    _ => Err(USAGE.to_string()),
+  }
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+  let mut s = String::new();
+
+  fs::File::open(path)
+    .and_then(|mut f| f.read_to_string(&mut s))
+    .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+  Ok(s)
+}
+
+fn eval(args: &EvalArgs) -> Result<(), String> {
+  let model = match args.model {
+    Some(ref path) => Some(TrainingData::try_from(path.as_path()).map_err(|e| e.to_string())?),
+    None => None,
+  };
+
+  // Models record the parameters they were trained with. Boundaries are
+  // unreliable if the tokenizer splits words, or sentences differently.
+  if let Some(params) = model.as_ref().and_then(|m| m.metadata().params()) {
+    let mismatches = params.tokenizer_mismatches::<Standard>();
+
+    if !mismatches.is_empty() {
+      eprintln!(
+        "warning: model was trained with different parameters: {}",
+        mismatches.join(", ")
+      );
+    }
+  }
+
+  let mut paths: Vec<PathBuf> = fs::read_dir(&args.gold)
+    .map_err(|e| format!("{}: {}", args.gold.display(), e))?
+    .filter_map(|e| e.ok().map(|e| e.path()))
+    .filter(|p| p.is_file())
+    .collect();
+
+  paths.sort();
+
+  let mut total = Score::default();
+  let mut scores = Vec::with_capacity(paths.len());
+
+  for path in paths {
+    let name = path.file_name().unwrap().to_string_lossy().into_owned();
+    let doc = read_file(&args.raw.join(&name))?;
+    let gold = read_file(&path)?;
+    let trained;
+
+    let data = match model {
+      Some(ref data) => data,
+      None => {
+        let mut data = TrainingData::new();
+
+        Trainer::<Standard>::new().train(&doc, &mut data);
+        trained = data;
+        &trained
+      }
+    };
+
+    let score = score_file(&doc, &gold, data)?;
+
+    total.add(&score);
+    scores.push((name, score));
+  }
+
+  println!("files:     {}", scores.len());
+  println!("precision: {:.4}", total.precision());
+  println!("recall:    {:.4}", total.recall());
+  println!("f1:        {:.4}", total.f1());
+
+  scores.sort_by(|a, b| a.1.f1().partial_cmp(&b.1.f1()).unwrap());
+
+  if args.worst > 0 && !scores.is_empty() {
+    println!("\nworst segmented:");
+
+    for &(ref name, ref score) in scores.iter().take(args.worst) {
+      println!(
+        "  {:.4}  {} (p: {:.4}, r: {:.4})",
+        score.f1(),
+        name,
+        score.precision(),
+        score.recall()
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Scores the sentence boundaries found in a document against the expected
+/// sentences. A boundary is the end of a sentence, ignoring trailing
+/// whitespace. The end of the document is not counted.
+fn score_file(doc: &str, gold: &str, data: &TrainingData) -> Result<Score, String> {
+  let mut expected = Vec::new();
+  let mut pos = 0;
+
+  for line in gold.lines().map(str::trim).filter(|l| !l.is_empty()) {
+    let sent = unescape(line);
+
+    match doc[pos..].find(&sent[..]) {
+      Some(i) => {
+        pos += i + sent.len();
+        expected.push(pos - (sent.len() - sent.trim_end().len()));
+      }
+      None => return Err(format!("gold sentence not found in raw file: {}", line)),
+    }
+  }
+
+  let predicted: Vec<usize> = SentenceByteOffsetTokenizer::<Standard>::new(doc, data)
+    .map(|(start, end)| start + doc[start..end].trim_end().len())
+    .collect();
+
+  let is_boundary = |&i: &usize| i < doc.trim_end().len();
+  let expected: Vec<usize> = expected.into_iter().filter(is_boundary).collect();
+  let predicted: Vec<usize> = predicted.into_iter().filter(is_boundary).collect();
+
+  Ok(Score {
+    true_positives: predicted
+      .iter()
+      .filter(|i| expected.binary_search(i).is_ok())
+      .count(),
+    predicted: predicted.len(),
+    expected: expected.len(),
+  })
+}
+
+/// Removes the brackets around a gold sentence, and unescapes newlines and
+/// quotes.
+fn unescape(line: &str) -> String {
+  let line = line.trim_start_matches('[').trim_end_matches(']');
+
+  line.replace("\\n", "\n").replace("\\\"", "\"")
+}
@@ -0,0 +1,237 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use ortho::OrthoContext;
+use prelude::{ORT_LC, ORT_UC};
+use trainer::{Provenance, TrainingData};
+
+/// Builds a model from abbreviations, collocations, sentence starters, and
+/// orthographic contexts that were obtained some other way than training,
+/// like from a database. Words are lowercased, like the words learned by a
+/// trainer, and are checked when the model is built.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::{OrthoContext, TrainingDataBuilder};
+/// #
+/// let data = TrainingDataBuilder::new()
+///   .abbrev("Dr")
+///   .extend_abbrevs(vec!["mr", "mrs"])
+///   .collocation("##number##", "may")
+///   .sentence_starter("however")
+///   .ortho("the", OrthoContext::BEG_UC)
+///   .build()
+///   .unwrap();
+///
+/// assert!(data.contains_abbrev("dr"));
+/// assert!(data.contains_abbrev("mrs"));
+/// assert!(data.contains_collocation("##number##", "may"));
+///
+/// assert!(TrainingDataBuilder::new().abbrev("").build().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrainingDataBuilder {
+  abbrevs: Vec<String>,
+  collocations: Vec<(String, String)>,
+  sentence_starters: Vec<String>,
+  orthographic_contexts: Vec<(String, OrthoContext)>,
+}
+
+impl TrainingDataBuilder {
+  /// Creates a builder for an empty model.
+  #[inline(always)]
+  pub fn new() -> TrainingDataBuilder {
+    Default::default()
+  }
+
+  /// Adds an abbreviation, without its final period.
+  #[inline]
+  pub fn abbrev(mut self, abbrev: &str) -> TrainingDataBuilder {
+    self.abbrevs.push(abbrev.to_lowercase());
+    self
+  }
+
+  /// Adds a pair of words that appear together, where a period after the
+  /// left word doesn't end a sentence.
+  #[inline]
+  pub fn collocation(mut self, left: &str, right: &str) -> TrainingDataBuilder {
+    self
+      .collocations
+      .push((left.to_lowercase(), right.to_lowercase()));
+    self
+  }
+
+  /// Adds a word that commonly starts a sentence.
+  #[inline]
+  pub fn sentence_starter(mut self, tok: &str) -> TrainingDataBuilder {
+    self.sentence_starters.push(tok.to_lowercase());
+    self
+  }
+
+  /// Adds an orthographic context that a word was seen in. The contexts of
+  /// a word are combined.
+  #[inline]
+  pub fn ortho(mut self, tok: &str, ctxt: OrthoContext) -> TrainingDataBuilder {
+    self.orthographic_contexts.push((tok.to_lowercase(), ctxt));
+    self
+  }
+
+  /// Adds several abbreviations. See `abbrev`.
+  pub fn extend_abbrevs<I, S>(self, abbrevs: I) -> TrainingDataBuilder
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    abbrevs.into_iter().fold(self, |b, a| b.abbrev(a.as_ref()))
+  }
+
+  /// Adds several collocations. See `collocation`.
+  pub fn extend_collocations<I, L, R>(self, collocations: I) -> TrainingDataBuilder
+  where
+    I: IntoIterator<Item = (L, R)>,
+    L: AsRef<str>,
+    R: AsRef<str>,
+  {
+    collocations
+      .into_iter()
+      .fold(self, |b, (l, r)| b.collocation(l.as_ref(), r.as_ref()))
+  }
+
+  /// Adds several sentence starters. See `sentence_starter`.
+  pub fn extend_sentence_starters<I, S>(self, toks: I) -> TrainingDataBuilder
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    toks
+      .into_iter()
+      .fold(self, |b, t| b.sentence_starter(t.as_ref()))
+  }
+
+  /// Adds several orthographic contexts. See `ortho`.
+  pub fn extend_ortho<I, S>(self, contexts: I) -> TrainingDataBuilder
+  where
+    I: IntoIterator<Item = (S, OrthoContext)>,
+    S: AsRef<str>,
+  {
+    contexts
+      .into_iter()
+      .fold(self, |b, (t, c)| b.ortho(t.as_ref(), c))
+  }
+
+  /// Checks the entries, and builds the model. Fails if a word is empty, or
+  /// contains whitespace, if an abbreviation ends with a period, or if an
+  /// orthographic context has an unknown flag set.
+  pub fn build(self) -> Result<TrainingData, &'static str> {
+    let mut data = TrainingData::new();
+
+    for a in self.abbrevs.iter() {
+      check_word(a)?;
+
+      if a.ends_with('.') {
+        return Err("abbreviation ends with a period");
+      }
+
+      data.insert_abbrev_with_provenance(a, Provenance::Inserted);
+    }
+
+    for &(ref l, ref r) in self.collocations.iter() {
+      check_word(l)?;
+      check_word(r)?;
+      data.insert_collocation(l, r);
+    }
+
+    for s in self.sentence_starters.iter() {
+      check_word(s)?;
+      data.insert_sentence_starter(s);
+    }
+
+    for &(ref t, c) in self.orthographic_contexts.iter() {
+      check_word(t)?;
+
+      if c.bits() & !(ORT_UC | ORT_LC) != 0 {
+        return Err("unknown orthographic context flag");
+      }
+
+      data.insert_orthographic_context(t, c.bits());
+    }
+
+    Ok(data)
+  }
+}
+
+/// Checks that a word can be matched by a tokenizer.
+#[inline]
+fn check_word(w: &str) -> Result<(), &'static str> {
+  if w.is_empty() {
+    Err("empty word")
+  } else if w.chars().any(char::is_whitespace) {
+    Err("word contains whitespace")
+  } else {
+    Ok(())
+  }
+}
+
+#[test]
+fn training_data_builder_test() {
+  use prelude::Standard;
+  use tokenizer::SentenceTokenizer;
+
+  let data = TrainingDataBuilder::new()
+    .extend_abbrevs(vec!["Approx".to_string(), "etc".to_string()])
+    .extend_collocations(vec![("##number##", "Dec")])
+    .extend_sentence_starters(&["but"])
+    .extend_ortho(vec![
+      ("then", OrthoContext::BEG_LC),
+      ("then", OrthoContext::MID_UC),
+    ])
+    .build()
+    .unwrap();
+
+  assert_eq!(data.abbrevs().count(), 2);
+  assert_eq!(data.abbrev_provenance("approx"), Some(Provenance::Inserted));
+  assert!(data.contains_collocation("##number##", "dec"));
+  assert!(data.contains_sentence_starter("but"));
+  assert_eq!(
+    data.get_orthographic_context("then"),
+    OrthoContext::BEG_LC | OrthoContext::MID_UC
+  );
+
+  let sents: Vec<&str> =
+    SentenceTokenizer::<Standard>::new("It cost approx. ten dollars. Then it broke.", &data)
+      .collect();
+
+  assert_eq!(
+    sents,
+    vec!["It cost approx. ten dollars.", "Then it broke."]
+  );
+
+  for &(ref builder, err) in [
+    (
+      TrainingDataBuilder::new().abbrev("u.s."),
+      "abbreviation ends with a period",
+    ),
+    (
+      TrainingDataBuilder::new().collocation("a b", "c"),
+      "word contains whitespace",
+    ),
+    (
+      TrainingDataBuilder::new().sentence_starter(""),
+      "empty word",
+    ),
+    (
+      TrainingDataBuilder::new().ortho("x", OrthoContext::from_bits(0b1)),
+      "unknown orthographic context flag",
+    ),
+  ]
+  .iter()
+  {
+    assert_eq!(builder.clone().build().unwrap_err(), err);
+  }
+}
@@ -0,0 +1,332 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::any::type_name;
+use std::collections::{BTreeMap, HashMap};
+
+use model::Model;
+use prelude::TokenizerParameters;
+use tokenizer::SentenceByteOffsetTokenizer;
+
+/// The 128 bit FNV-1a hash of a document.
+fn fnv128(bytes: &[u8]) -> u128 {
+  let mut h: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+
+  for &b in bytes {
+    h ^= u128::from(b);
+    h = h.wrapping_mul(0x0000_0000_0100_0000_0000_0000_0000_013b);
+  }
+
+  h
+}
+
+/// Identifies the sentence spans of a document, segmented with a model, and
+/// a set of parameters. The hash of the document is stable across versions,
+/// and platforms, so keys can be stored outside of the process. Documents
+/// with the same hash, and length share a key, so the spans of a key are
+/// checked against the document before they are used.
+///
+/// The parameters are identified by the name of their type, so a cache can
+/// be shared by tokenizers with different parameters. The name can change
+/// when the type is moved, or renamed, or the crate is built with another
+/// compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+  doc: u128,
+  len: u64,
+  model: u64,
+  params: u64,
+}
+
+impl CacheKey {
+  /// Creates a key for a document, the id of a model, like its
+  /// `TrainingData::content_hash`, and the parameters it is segmented with.
+  #[inline]
+  pub fn new<P>(doc: &str, model: u64) -> CacheKey
+  where
+    P: TokenizerParameters,
+  {
+    CacheKey {
+      doc: fnv128(doc.as_bytes()),
+      len: doc.len() as u64,
+      model: model,
+      params: fnv128(type_name::<P>().as_bytes()) as u64,
+    }
+  }
+
+  /// The hash of the document.
+  #[inline(always)]
+  pub fn doc_hash(&self) -> u128 {
+    self.doc
+  }
+
+  /// The length of the document in bytes.
+  #[inline(always)]
+  pub fn doc_len(&self) -> u64 {
+    self.len
+  }
+
+  /// The id of the model.
+  #[inline(always)]
+  pub fn model_id(&self) -> u64 {
+    self.model
+  }
+
+  /// The hash of the name of the parameters.
+  #[inline(always)]
+  pub fn params_hash(&self) -> u64 {
+    self.params
+  }
+}
+
+/// Storage for the sentence spans of documents. Spans are returned by value,
+/// so they can be kept outside of the process.
+pub trait SegmentCache {
+  /// Returns the spans stored for a key.
+  fn get(&mut self, key: &CacheKey) -> Option<Vec<(usize, usize)>>;
+
+  /// Stores the spans for a key.
+  fn insert(&mut self, key: CacheKey, spans: Vec<(usize, usize)>);
+}
+
+/// An in-memory cache, that evicts the least recently used spans once it
+/// holds `capacity` documents.
+pub struct LruSegmentCache {
+  capacity: usize,
+  tick: u64,
+  entries: HashMap<CacheKey, (u64, Vec<(usize, usize)>)>,
+  order: BTreeMap<u64, CacheKey>,
+}
+
+impl LruSegmentCache {
+  /// Creates a cache that holds at most `capacity` documents.
+  #[inline]
+  pub fn new(capacity: usize) -> LruSegmentCache {
+    LruSegmentCache {
+      capacity: capacity,
+      tick: 0,
+      entries: HashMap::new(),
+      order: BTreeMap::new(),
+    }
+  }
+
+  /// The number of documents in the cache.
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Checks if the cache holds no documents.
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Marks an entry as the most recently used.
+  #[inline]
+  fn touch(&mut self, key: &CacheKey) {
+    self.tick += 1;
+
+    if let Some(entry) = self.entries.get_mut(key) {
+      self.order.remove(&entry.0);
+      self.order.insert(self.tick, *key);
+      entry.0 = self.tick;
+    }
+  }
+}
+
+impl SegmentCache for LruSegmentCache {
+  fn get(&mut self, key: &CacheKey) -> Option<Vec<(usize, usize)>> {
+    self.touch(key);
+    self.entries.get(key).map(|&(_, ref spans)| spans.clone())
+  }
+
+  fn insert(&mut self, key: CacheKey, spans: Vec<(usize, usize)>) {
+    if self.capacity == 0 {
+      return;
+    }
+
+    if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+      let oldest = self.order.keys().next().cloned();
+
+      if let Some(tick) = oldest {
+        if let Some(k) = self.order.remove(&tick) {
+          self.entries.remove(&k);
+        }
+      }
+    }
+
+    self.entries.insert(key, (0, spans));
+    self.touch(&key);
+  }
+}
+
+/// Checks if spans can be the sentences of a document: they are in order,
+/// don't overlap, start, and end at character boundaries, and only leave
+/// whitespace between them.
+fn spans_fit(doc: &str, spans: &[(usize, usize)]) -> bool {
+  let mut last = 0;
+
+  for &(start, end) in spans {
+    if start < last
+      || end < start
+      || end > doc.len()
+      || !doc.is_char_boundary(start)
+      || !doc.is_char_boundary(end)
+      || !doc[last..start].trim().is_empty()
+    {
+      return false;
+    }
+
+    last = end;
+  }
+
+  doc[last..].trim().is_empty()
+}
+
+/// Returns the sentence spans of a document, from a cache if they are stored
+/// for the document, model, and parameters, and fit the document. Otherwise,
+/// the document is segmented, and the spans are stored. The id of the model
+/// should change whenever the model does.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{cached_sentence_byte_offsets, LruSegmentCache, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let data = TrainingData::english();
+/// let model_id = data.content_hash();
+/// let mut cache = LruSegmentCache::new(100);
+/// let doc = "This is a sentence. This is another.";
+///
+/// let spans = cached_sentence_byte_offsets::<Standard, _, _>(doc, &data, model_id, &mut cache);
+///
+/// assert_eq!(spans, vec![(0, 19), (20, 36)]);
+/// assert_eq!(cache.len(), 1);
+/// # }
+/// ```
+pub fn cached_sentence_byte_offsets<P, D, C>(
+  doc: &str,
+  data: &D,
+  model_id: u64,
+  cache: &mut C,
+) -> Vec<(usize, usize)>
+where
+  P: TokenizerParameters,
+  D: Model,
+  C: SegmentCache,
+{
+  let key = CacheKey::new::<P>(doc, model_id);
+
+  if let Some(spans) = cache.get(&key) {
+    if spans_fit(doc, &spans) {
+      return spans;
+    }
+  }
+
+  let spans: Vec<(usize, usize)> = SentenceByteOffsetTokenizer::<P, D>::new(doc, data).collect();
+
+  cache.insert(key, spans.clone());
+  spans
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn lru_segment_cache_test() {
+  use prelude::Standard;
+  use trainer::TrainingData;
+
+  let data = TrainingData::english();
+  let id = data.content_hash();
+  let docs = ["One. Two.", "Three. Four.", "Five. Six."];
+  let mut cache = LruSegmentCache::new(2);
+
+  for doc in docs.iter() {
+    cached_sentence_byte_offsets::<Standard, _, _>(doc, &data, id, &mut cache);
+  }
+
+  assert_eq!(cache.len(), 2);
+  assert!(cache.get(&CacheKey::new::<Standard>(docs[0], id)).is_none());
+
+  // Using the second document makes the third the least recently used.
+  assert!(cache.get(&CacheKey::new::<Standard>(docs[1], id)).is_some());
+  cache.insert(CacheKey::new::<Standard>(docs[0], id), vec![(0, 1)]);
+
+  assert!(cache.get(&CacheKey::new::<Standard>(docs[2], id)).is_none());
+  assert!(cache.get(&CacheKey::new::<Standard>(docs[1], id)).is_some());
+  assert!(cache
+    .get(&CacheKey::new::<Standard>(docs[1], id + 1))
+    .is_none());
+
+  // Spans that don't fit the document are replaced.
+  assert_eq!(
+    cached_sentence_byte_offsets::<Standard, _, _>(docs[0], &data, id, &mut cache),
+    vec![(0, 4), (5, 9)]
+  );
+
+  // Stored spans are returned without segmenting the document.
+  cache.insert(CacheKey::new::<Standard>(docs[0], id), vec![(0, 9)]);
+
+  assert_eq!(
+    cached_sentence_byte_offsets::<Standard, _, _>(docs[0], &data, id, &mut cache),
+    vec![(0, 9)]
+  );
+  assert_eq!(
+    CacheKey::new::<Standard>(docs[0], id),
+    CacheKey::new::<Standard>(docs[0], id)
+  );
+  assert!(
+    CacheKey::new::<Standard>(docs[0], id).doc_hash()
+      != CacheKey::new::<Standard>(docs[1], id).doc_hash()
+  );
+  assert_eq!(CacheKey::new::<Standard>(docs[1], id).doc_len(), 12);
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn segment_cache_params_test() {
+  use prelude::{Precise, Standard};
+  use trainer::TrainingData;
+
+  let data = TrainingData::english();
+  let id = data.content_hash();
+  let doc = "One. Two.";
+  let mut cache = LruSegmentCache::new(2);
+
+  assert!(CacheKey::new::<Standard>(doc, id) != CacheKey::new::<Precise>(doc, id));
+
+  // Spans stored for one set of parameters aren't returned for another,
+  // even if they fit the document.
+  cache.insert(CacheKey::new::<Standard>(doc, id), vec![(0, 9)]);
+
+  assert_eq!(
+    cached_sentence_byte_offsets::<Precise, _, _>(doc, &data, id, &mut cache),
+    vec![(0, 4), (5, 9)]
+  );
+  assert_eq!(
+    cached_sentence_byte_offsets::<Standard, _, _>(doc, &data, id, &mut cache),
+    vec![(0, 9)]
+  );
+  assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn spans_fit_test() {
+  let doc = " One. Two.\u{e9} ";
+
+  assert!(spans_fit(doc, &[(1, 5), (6, 12)]));
+  assert!(spans_fit(doc, &[(0, doc.len())]));
+  assert!(spans_fit("  ", &[]));
+  assert!(!spans_fit(doc, &[(1, 5)]));
+  assert!(!spans_fit(doc, &[(1, 6), (5, 12)]));
+  assert!(!spans_fit(doc, &[(1, 5), (6, 11)]));
+  assert!(!spans_fit(doc, &[(1, 5), (6, 100)]));
+}
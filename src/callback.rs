@@ -0,0 +1,91 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use std::ops::Range;
+
+use model::Model;
+use prelude::{Standard, TokenizerParameters};
+use tokenizer::SentenceByteOffsetTokenizer;
+
+/// Calls a function with the byte range of each sentence in a document, with
+/// the standard parameters. See `for_each_sentence_with_params`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{for_each_sentence, TrainingData};
+/// #
+/// let data = TrainingData::english();
+/// let doc = "Mr. Smith left. He was tired.";
+/// let mut sentences = Vec::new();
+///
+/// for_each_sentence(doc, &data, |range| sentences.push(&doc[range]));
+///
+/// assert_eq!(sentences, vec!["Mr. Smith left.", "He was tired."]);
+/// # }
+/// ```
+#[inline]
+pub fn for_each_sentence<D, F>(doc: &str, data: &D, f: F)
+where
+  D: Model,
+  F: FnMut(Range<usize>),
+{
+  for_each_sentence_with_params::<Standard, D, F>(doc, data, f)
+}
+
+/// Calls a function with the byte range of each sentence in a document. The
+/// ranges are the same as the ones of `SentenceByteOffsetTokenizer`, but no
+/// iterator has to be held by the caller, which is simpler to expose through
+/// a callback based interface, like the one in `ffi`.
+pub fn for_each_sentence_with_params<P, D, F>(doc: &str, data: &D, mut f: F)
+where
+  P: TokenizerParameters,
+  D: Model,
+  F: FnMut(Range<usize>),
+{
+  for (start, end) in SentenceByteOffsetTokenizer::<P, D>::new(doc, data) {
+    f(start..end);
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn for_each_sentence_test() {
+  use trainer::TrainingData;
+
+  let data = TrainingData::english();
+  let doc = "The U.S. economy grew. Dr. Jones disagreed. ";
+  let mut ranges = Vec::new();
+
+  for_each_sentence(doc, &data, |r| ranges.push(r));
+
+  assert_eq!(
+    ranges,
+    SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+      .map(|(s, e)| s..e)
+      .collect::<Vec<_>>()
+  );
+
+  for_each_sentence("", &data, |_| panic!("no sentences expected"));
+}
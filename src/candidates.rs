@@ -0,0 +1,423 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use model::Model;
+use ortho::OrthoContext;
+use prelude::{Standard, TokenizerParameters};
+use token::{LetterCase, Token};
+use tokenizer::SentenceByteOffsetTokenizer;
+use trainer::TrainingData;
+
+/// The features of a token that could end a sentence, and the token after
+/// it. See `boundary_candidates`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundaryCandidate {
+  /// The byte offset of the start of the token in the document.
+  pub start: usize,
+  /// The byte offset of the end of the token in the document.
+  pub end: usize,
+  /// The type of the token, without the final period.
+  pub left_type: String,
+  /// The case of the first letter of the next token.
+  pub right_case: LetterCase,
+  /// The token is a known abbreviation.
+  pub is_known_abbrev: bool,
+  /// The token, and the next token are a known collocation.
+  pub is_collocation: bool,
+  /// The orthographic context of the next token.
+  pub right_ortho: OrthoContext,
+  /// The decision of the second pass of the sentence tokenizer, which can be
+  /// used as a label. The sentence tokenizer can still continue the sentence
+  /// after a break, if its parameters keep breaks inside of quotations, or
+  /// brackets.
+  pub is_sentence_break: bool,
+}
+
+/// Checks if a token could end a sentence, after the first pass. Only these
+/// tokens can be made sentence breaks by the second pass.
+#[inline]
+pub(crate) fn is_candidate(tok: &Token) -> bool {
+  tok.has_final_period() || tok.is_sentence_break() || tok.is_abbrev()
+}
+
+/// Creates the features for a token that went through the second pass, and
+/// the token after it.
+pub(crate) fn boundary_candidate<D>(
+  tok: &Token,
+  nxt: &Token,
+  start: usize,
+  end: usize,
+  data: &D,
+) -> BoundaryCandidate
+where
+  D: Model,
+{
+  let typ = nxt.typ_without_break_or_period();
+
+  BoundaryCandidate {
+    start: start,
+    end: end,
+    left_type: tok.typ_without_period().to_string(),
+    right_case: nxt.first_case(),
+    is_known_abbrev: data.contains_abbrev(tok.typ_without_period()),
+    is_collocation: data.contains_collocation(tok.typ_without_period(), typ),
+    right_ortho: data.get_orthographic_context(typ),
+    is_sentence_break: tok.is_sentence_break(),
+  }
+}
+
+/// Iterator over the tokens of a document that could end a sentence. See
+/// `boundary_candidates`.
+pub struct BoundaryCandidates<'a, P, D: 'a = TrainingData> {
+  iter: SentenceByteOffsetTokenizer<'a, P, D, Recorder>,
+  candidates: Rc<RefCell<VecDeque<BoundaryCandidate>>>,
+}
+
+/// Keeps the candidates the sentence tokenizer decides, and takes the
+/// decisions of the second pass.
+struct Recorder {
+  candidates: Rc<RefCell<VecDeque<BoundaryCandidate>>>,
+}
+
+impl BoundaryDecider for Recorder {
+  #[inline(always)]
+  fn is_sentence_break(&self, candidate: &BoundaryCandidate) -> bool {
+    candidate.is_sentence_break
+  }
+
+  #[inline]
+  fn decide<F>(&self, second_pass: bool, candidate: F) -> bool
+  where
+    F: FnOnce() -> BoundaryCandidate,
+  {
+    self.candidates.borrow_mut().push_back(candidate());
+    second_pass
+  }
+}
+
+impl<'a, P, D> Iterator for BoundaryCandidates<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  type Item = BoundaryCandidate;
+
+  fn next(&mut self) -> Option<BoundaryCandidate> {
+    loop {
+      if let Some(candidate) = self.candidates.borrow_mut().pop_front() {
+        return Some(candidate);
+      }
+
+      if self.iter.next().is_none() {
+        return self.candidates.borrow_mut().pop_front();
+      }
+    }
+  }
+}
+
+/// Returns an iterator over the tokens of a document that could end a
+/// sentence, with the features that the sentence tokenizer uses to decide
+/// them, using the standard parameters. The features can be used to train
+/// another classifier, that reuses the tokenization of this crate.
+///
+/// The candidates are the ones the sentence tokenizer decides, in order. A
+/// token at the end of the document isn't one, because the rest of the
+/// document is always a sentence.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{boundary_candidates, TrainingData};
+/// #
+/// let doc = "Dr. Smith arrived. Was he late? No.";
+/// let data = TrainingData::english();
+/// let candidates: Vec<_> = boundary_candidates(doc, &data).collect();
+///
+/// assert_eq!(candidates.len(), 3);
+/// assert_eq!(candidates[0].left_type, "dr");
+/// assert!(candidates[0].is_known_abbrev);
+/// assert!(!candidates[0].is_sentence_break);
+/// assert!(candidates[1].is_sentence_break);
+/// assert_eq!(&doc[candidates[2].start..candidates[2].end], "?");
+/// # }
+/// ```
+#[inline]
+pub fn boundary_candidates<'a, D>(doc: &'a str, data: &'a D) -> BoundaryCandidates<'a, Standard, D>
+where
+  D: Model,
+{
+  boundary_candidates_with_params::<Standard, D>(doc, data)
+}
+
+/// Returns an iterator over the tokens of a document that could end a
+/// sentence, with their features.
+#[inline]
+pub fn boundary_candidates_with_params<'a, P, D>(
+  doc: &'a str,
+  data: &'a D,
+) -> BoundaryCandidates<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  let candidates = Rc::new(RefCell::new(VecDeque::new()));
+  let recorder = Recorder {
+    candidates: candidates.clone(),
+  };
+
+  BoundaryCandidates {
+    iter: SentenceByteOffsetTokenizer::with_decider(doc, data, recorder),
+    candidates,
+  }
+}
+
+/// Decides if a boundary candidate ends a sentence. Replaces the second
+/// pass of the sentence tokenizer in `DecidedSentences`, so a classifier
+/// trained on the features of `boundary_candidates` can be used instead of
+/// the heuristics of Punkt. Implemented by closures.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{BoundaryCandidate, BoundaryDecider, DecidedSentences, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// // Only breaks before uppercase words, unless the period is an abbreviation.
+/// struct CaseDecider;
+///
+/// impl BoundaryDecider for CaseDecider {
+///   fn is_sentence_break(&self, c: &BoundaryCandidate) -> bool {
+///     !c.is_known_abbrev && c.right_case != punkt::token::LetterCase::Lower
+///   }
+/// }
+///
+/// let doc = "I saw Dr. Smith. then I left.";
+/// let data = TrainingData::english();
+/// let sents: Vec<&str> = DecidedSentences::<Standard, _, _>::new(doc, &data, CaseDecider)
+///   .map(|(start, end)| &doc[start..end])
+///   .collect();
+///
+/// assert_eq!(sents, vec!["I saw Dr. Smith. then I left."]);
+/// # }
+/// ```
+pub trait BoundaryDecider {
+  /// Checks if a candidate ends a sentence.
+  fn is_sentence_break(&self, candidate: &BoundaryCandidate) -> bool;
+
+  /// Decides a candidate, given the decision of the second pass, and a
+  /// function that creates the features of the candidate. Only needs to be
+  /// implemented by deciders that don't look at the features, so they
+  /// aren't created.
+  #[inline]
+  fn decide<F>(&self, _second_pass: bool, candidate: F) -> bool
+  where
+    F: FnOnce() -> BoundaryCandidate,
+  {
+    self.is_sentence_break(&candidate())
+  }
+}
+
+impl<F> BoundaryDecider for F
+where
+  F: Fn(&BoundaryCandidate) -> bool,
+{
+  #[inline(always)]
+  fn is_sentence_break(&self, candidate: &BoundaryCandidate) -> bool {
+    self(candidate)
+  }
+}
+
+/// The decisions of the sentence tokenizer, which are made by the heuristics
+/// of Punkt. The decider of `SentenceByteOffsetTokenizer`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PunktDecider;
+
+impl BoundaryDecider for PunktDecider {
+  #[inline(always)]
+  fn is_sentence_break(&self, candidate: &BoundaryCandidate) -> bool {
+    candidate.is_sentence_break
+  }
+
+  #[inline(always)]
+  fn decide<F>(&self, second_pass: bool, _candidate: F) -> bool
+  where
+    F: FnOnce() -> BoundaryCandidate,
+  {
+    second_pass
+  }
+}
+
+/// Iterator over the byte offsets of the sentences in a document, that are
+/// ended by the candidates a `BoundaryDecider` decides are sentence breaks.
+/// Only the second pass is replaced. Everything else the parameters of the
+/// sentence tokenizer do is kept, like keeping breaks inside of quotations,
+/// or brackets, splitting at unspaced sentence endings, or realigning
+/// boundaries, so `PunktDecider` gives the sentences of the sentence
+/// tokenizer.
+pub struct DecidedSentences<'a, P, D: 'a, B> {
+  iter: SentenceByteOffsetTokenizer<'a, P, D, B>,
+}
+
+impl<'a, P, D, B> DecidedSentences<'a, P, D, B>
+where
+  P: TokenizerParameters,
+  D: Model,
+  B: BoundaryDecider,
+{
+  /// Creates a new `DecidedSentences`.
+  #[inline(always)]
+  pub fn new(doc: &'a str, data: &'a D, decider: B) -> DecidedSentences<'a, P, D, B> {
+    DecidedSentences {
+      iter: SentenceByteOffsetTokenizer::with_decider(doc, data, decider),
+    }
+  }
+}
+
+impl<'a, P, D, B> Iterator for DecidedSentences<'a, P, D, B>
+where
+  P: TokenizerParameters,
+  D: Model,
+  B: BoundaryDecider,
+{
+  type Item = (usize, usize);
+
+  #[inline(always)]
+  fn next(&mut self) -> Option<(usize, usize)> {
+    self.iter.next()
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn boundary_candidates_test() {
+  let mut data = TrainingData::english();
+
+  data.insert_collocation("##number##", "may");
+  data.insert_orthographic_context("then", ::prelude::BEG_LC);
+
+  let doc = "It was 5. May came.\n\nWhat?! No... then 2. 3 more. End.";
+  let candidates: Vec<BoundaryCandidate> = boundary_candidates(doc, &data).collect();
+  let spans: Vec<&str> = candidates.iter().map(|c| &doc[c.start..c.end]).collect();
+
+  // The period at the end of the document isn't decided.
+  assert_eq!(spans, vec!["5.", "came.", "!", "...", "2.", "more."]);
+  assert!(candidates[0].is_collocation);
+  assert!(!candidates[0].is_sentence_break);
+  assert_eq!(candidates[0].right_case, LetterCase::Upper);
+  assert!(candidates[1].is_sentence_break);
+  assert!(candidates[3].right_ortho.seen_lower());
+  assert_eq!(candidates[3].right_case, LetterCase::Lower);
+  assert_eq!(candidates[4].left_type, "##number##");
+  assert_eq!(candidates[4].right_case, LetterCase::Unknown);
+  assert!(candidates[4].right_ortho.is_empty());
+  assert!(boundary_candidates("No periods", &data).next().is_none());
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn decided_sentences_test() {
+  use prelude::Standard;
+
+  let data = TrainingData::english();
+  let doc = "Mr. Smith arrived. He was late! Was he? Yes.  ";
+  let every: Vec<&str> =
+    DecidedSentences::<Standard, _, _>::new(doc, &data, |_: &BoundaryCandidate| true)
+      .map(|(s, e)| &doc[s..e])
+      .collect();
+  let never: Vec<&str> =
+    DecidedSentences::<Standard, _, _>::new(doc, &data, |_: &BoundaryCandidate| false)
+      .map(|(s, e)| &doc[s..e])
+      .collect();
+
+  assert_eq!(
+    every,
+    vec!["Mr.", "Smith arrived.", "He was late!", "Was he?", "Yes.  "]
+  );
+  assert_eq!(never, vec![doc]);
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn decided_sentences_presets_test() {
+  use prelude::*;
+
+  // No preset limits the length of a sentence.
+  struct Short;
+
+  impl DefinesInternalPunctuation for Short {}
+  impl DefinesMultiChars for Short {}
+  impl DefinesNonPrefixCharacters for Short {}
+  impl DefinesNonWordCharacters for Short {}
+  impl DefinesPunctuation for Short {}
+  impl DefinesSentenceEndings for Short {}
+  impl DefinesTypeNormalization for Short {}
+  impl TokenizerParameters for Short {
+    const MAX_SENTENCE_LEN: usize = 200;
+  }
+
+  fn check<P>(docs: &[&str], data: &TrainingData)
+  where
+    P: TokenizerParameters,
+  {
+    for doc in docs {
+      let expected: Vec<(usize, usize)> =
+        SentenceByteOffsetTokenizer::<P>::new(doc, data).collect();
+      let punkt: Vec<(usize, usize)> =
+        DecidedSentences::<P, _, _>::new(doc, data, PunktDecider).collect();
+      let labels: Vec<(usize, usize)> =
+        DecidedSentences::<P, _, _>::new(doc, data, |c: &BoundaryCandidate| c.is_sentence_break)
+          .collect();
+
+      assert_eq!(punkt, expected);
+      assert_eq!(labels, expected);
+    }
+  }
+
+  let data = TrainingData::english();
+  let long = "word ".repeat(100) + "end. Then more.";
+  let docs = [
+    "Mr. Smith arrived. He was late! Was he? Yes.  ",
+    "The door slammed. \"I am leaving!\" she said. He laughed.",
+    "It grew in broth.(1) Then it died. It was (shown. He left. He came (back) later.",
+    "Wait\u{2026} what? Really\u{203d}Yes. @bob said hi.",
+    "\u{5e9}\u{5dc}\u{5d5}\u{5dd}.\u{200f} \u{5e2}\u{5d5}\u{5d3}.",
+    &long,
+    include_str!("../test/raw/npr-article-01.txt"),
+  ];
+
+  check::<Standard>(&docs, &data);
+  check::<Precise>(&docs, &data);
+  check::<Legal>(&docs, &data);
+  check::<Web>(&docs, &data);
+  check::<Rtl>(&docs, &data);
+  check::<Biomedical>(&docs, &data);
+  check::<Aggressive>(&docs, &data);
+  check::<Short>(&docs, &data);
+}
@@ -0,0 +1,225 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::VecDeque;
+
+use model::Model;
+use prelude::TokenizerParameters;
+use tokenizer::SentenceByteOffsetTokenizer;
+use trainer::TrainingData;
+
+/// Iterator over the byte offsets of chunks of consecutive sentences in a
+/// document, that fit in a budget.
+///
+/// Each chunk holds as many sentences as fit in the budget, measured in
+/// bytes by default, or with the function passed to `with_measure`, like a
+/// token counter. A sentence that is larger than the budget on its own is
+/// returned as a chunk by itself. Consecutive chunks can share sentences, set
+/// with `with_overlap`, so context isn't lost at the edges of a chunk.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{SentenceChunker, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "One sentence. Two sentences. Three sentences. Four.";
+/// let data = TrainingData::english();
+/// let chunks: Vec<&str> = SentenceChunker::<Standard>::new(doc, &data, 32)
+///   .with_overlap(1)
+///   .map(|(start, end)| &doc[start..end])
+///   .collect();
+///
+/// assert_eq!(
+///   chunks,
+///   vec!["One sentence. Two sentences.", "Two sentences. Three sentences.", "Three sentences. Four."]
+/// );
+/// # }
+/// ```
+pub struct SentenceChunker<'a, P, D: 'a = TrainingData> {
+  doc: &'a str,
+  budget: usize,
+  overlap: usize,
+  measure: fn(&str) -> usize,
+  inner: SentenceByteOffsetTokenizer<'a, P, D>,
+  pending: VecDeque<(usize, usize)>,
+  repeated: usize,
+  done: bool,
+}
+
+impl<'a, P, D> SentenceChunker<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Creates a new `SentenceChunker`, with chunks of at most `budget` bytes,
+  /// and no overlap.
+  #[inline]
+  pub fn new(doc: &'a str, data: &'a D, budget: usize) -> SentenceChunker<'a, P, D> {
+    SentenceChunker {
+      doc: doc,
+      budget: budget,
+      overlap: 0,
+      measure: str::len,
+      inner: SentenceByteOffsetTokenizer::new(doc, data),
+      pending: VecDeque::new(),
+      repeated: 0,
+      done: false,
+    }
+  }
+
+  /// Sets the number of sentences at the end of a chunk, that are repeated at
+  /// the start of the next chunk. At least one new sentence is always added
+  /// to each chunk, so fewer sentences are repeated if the next sentence
+  /// doesn't fit in the budget with them.
+  #[inline(always)]
+  pub fn with_overlap(mut self, sentences: usize) -> SentenceChunker<'a, P, D> {
+    self.overlap = sentences;
+    self
+  }
+
+  /// Sets the function that measures the size of a chunk against the
+  /// budget. It is passed the text of the chunk.
+  #[inline(always)]
+  pub fn with_measure(mut self, measure: fn(&str) -> usize) -> SentenceChunker<'a, P, D> {
+    self.measure = measure;
+    self
+  }
+
+  /// Makes sure there are at least `n` pending sentences, if the document
+  /// has enough left.
+  #[inline]
+  fn fill(&mut self, n: usize) -> bool {
+    while self.pending.len() < n {
+      match self.inner.next() {
+        Some(span) => self.pending.push_back(span),
+        None => return false,
+      }
+    }
+
+    true
+  }
+}
+
+impl<'a, P, D> Iterator for SentenceChunker<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  type Item = (usize, usize);
+
+  fn next(&mut self) -> Option<(usize, usize)> {
+    if self.done || !self.fill(self.repeated + 1) {
+      return None;
+    }
+
+    // Drop the sentences repeated from the last chunk, until the first new
+    // sentence fits with the ones that are left.
+    while self.repeated > 0
+      && (self.measure)(&self.doc[self.pending[0].0..self.pending[self.repeated].1]) > self.budget
+    {
+      self.pending.pop_front();
+      self.repeated -= 1;
+    }
+
+    let start = self.pending[0].0;
+    let mut n = self.repeated + 1;
+
+    while self.fill(n + 1) {
+      let end = self.pending[n].1;
+
+      if (self.measure)(&self.doc[start..end]) > self.budget {
+        break;
+      }
+
+      n += 1;
+    }
+
+    let end = self.pending[n - 1].1;
+
+    // Every sentence was added, so any overlap would only repeat this chunk.
+    if n == self.pending.len() && !self.fill(n + 1) {
+      self.done = true;
+    }
+
+    let keep = ::std::cmp::min(self.overlap, n - 1);
+
+    self.pending.drain(..n - keep);
+    self.repeated = keep;
+
+    Some((start, end))
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn sentence_chunker_test() {
+  use prelude::Standard;
+
+  let data = TrainingData::english();
+  let doc = "Dr. Smith is here. The U.S. team left. A sentence that is too long to fit. End.";
+  let chunks = |budget: usize, overlap: usize| -> Vec<&str> {
+    SentenceChunker::<Standard>::new(doc, &data, budget)
+      .with_overlap(overlap)
+      .map(|(start, end)| &doc[start..end])
+      .collect()
+  };
+
+  assert_eq!(
+    chunks(40, 0),
+    vec![
+      "Dr. Smith is here. The U.S. team left.",
+      "A sentence that is too long to fit. End."
+    ]
+  );
+  assert_eq!(
+    chunks(20, 0),
+    vec![
+      "Dr. Smith is here.",
+      "The U.S. team left.",
+      "A sentence that is too long to fit.",
+      "End."
+    ]
+  );
+  assert_eq!(
+    chunks(40, 1),
+    vec![
+      "Dr. Smith is here. The U.S. team left.",
+      "A sentence that is too long to fit. End."
+    ]
+  );
+  assert_eq!(
+    chunks(56, 1),
+    vec![
+      "Dr. Smith is here. The U.S. team left.",
+      "The U.S. team left. A sentence that is too long to fit.",
+      "A sentence that is too long to fit. End."
+    ]
+  );
+  assert_eq!(
+    chunks(20, 1),
+    vec![
+      "Dr. Smith is here.",
+      "The U.S. team left.",
+      "A sentence that is too long to fit.",
+      "End."
+    ]
+  );
+  assert_eq!(chunks(1000, 2), vec![doc]);
+  assert!(SentenceChunker::<Standard>::new("", &data, 10)
+    .next()
+    .is_none());
+
+  let words: Vec<(usize, usize)> = SentenceChunker::<Standard>::new(doc, &data, 8)
+    .with_measure(|s| s.split_whitespace().count())
+    .collect();
+
+  assert_eq!(words, vec![(0, 38), (39, 74), (75, 79)]);
+}
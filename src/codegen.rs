@@ -0,0 +1,262 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Compiles `TrainingData` into Rust source code, so a finalized model can
+//! be embedded into a program as a `CompiledData`. Requires the `codegen`
+//! feature.
+//!
+//! # Examples
+//!
+//! From a build script:
+//!
+//! ```no_run
+//! # #[cfg(feature = "english")]
+//! # {
+//! # use std::env;
+//! # use std::fs::File;
+//! # use std::io::Write;
+//! # use std::path::Path;
+//! # use punkt::TrainingData;
+//! #
+//! let data = TrainingData::english();
+//! let path = Path::new(&env::var("OUT_DIR").unwrap()).join("english.rs");
+//!
+//! File::create(path)
+//!   .unwrap()
+//!   .write_all(punkt::codegen::emit_rust(&data).as_bytes())
+//!   .unwrap();
+//! # }
+//! ```
+//!
+//! Then include the generated file, which defines a
+//! `COMPILED_DATA: CompiledData` static:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/english.rs"));
+//! ```
+//!
+//! Models stored as JSON in a crate can be embedded with less setup. The
+//! build script compiles each model with `embed_model`:
+//!
+//! ```no_run
+//! punkt::codegen::embed_model("models/legal.json").unwrap();
+//! ```
+//!
+//! Then `embed_model!` expands to the compiled model, with no parsing at
+//! runtime:
+//!
+//! ```ignore
+//! static LEGAL: punkt::CompiledData = punkt::embed_model!("models/legal.json");
+//! ```
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use format::LoadError;
+use phf_generator;
+use trainer::TrainingData;
+
+#[doc(hidden)]
+pub use phf::{Map, Set, Slice};
+
+/// Generates the source of a Rust file that defines a static named
+/// `COMPILED_DATA`, holding the data compiled into perfect hash tables.
+/// The output is deterministic.
+pub fn emit_rust(data: &TrainingData) -> String {
+  format!(
+    "// Generated by `punkt::codegen::emit_rust`. Do not edit.\n\n\
+     pub static COMPILED_DATA: ::punkt::CompiledData = {};\n",
+    emit_expr(data)
+  )
+}
+
+/// Generates a Rust expression that evaluates to a `CompiledData`, holding
+/// the data compiled into perfect hash tables. The output is deterministic.
+pub fn emit_expr(data: &TrainingData) -> String {
+  let mut out = String::new();
+
+  let mut collocations: Vec<(&str, Vec<&str>)> = Vec::new();
+  let mut pairs: Vec<(&str, &str)> = data.collocations().collect();
+
+  pairs.sort();
+
+  for (l, r) in pairs {
+    match collocations.last_mut() {
+      Some(&mut (last, ref mut rs)) if last == l => rs.push(r),
+      _ => collocations.push((l, vec![r])),
+    }
+  }
+
+  let mut orthographic_context: Vec<(&str, u8)> = data
+    .orthographic_contexts()
+    .map(|(s, c)| (s, c.bits()))
+    .collect();
+
+  orthographic_context.sort();
+
+  out.push_str("::punkt::CompiledData {\n");
+
+  out.push_str("  abbrevs: ");
+  emit_set(&mut out, sorted(data.abbrevs()));
+  out.push_str(",\n  collocations: ");
+  emit_map(&mut out, &collocations, |out, rs| emit_set(out, rs.clone()));
+  out.push_str(",\n  sentence_starters: ");
+  emit_set(&mut out, sorted(data.sentence_starters()));
+  out.push_str(",\n  orthographic_context: ");
+  emit_map(&mut out, &orthographic_context, |out, c| {
+    write!(out, "{}", c).unwrap()
+  });
+  out.push_str(",\n}");
+
+  out
+}
+
+/// Compiles a JSON model into the output directory of a build script, for
+/// `embed_model!`. A relative path is resolved against the directory of the
+/// crate being built, and Cargo is told to rerun the build script when the
+/// model changes. Must be called from a build script.
+pub fn embed_model<T: AsRef<Path>>(path: T) -> Result<(), LoadError> {
+  let path = path.as_ref();
+  let var = |name: &str| {
+    env::var_os(name).map(PathBuf::from).ok_or_else(|| {
+      LoadError::Io(
+        path.to_path_buf(),
+        io::Error::new(io::ErrorKind::NotFound, "not run from a build script"),
+      )
+    })
+  };
+  let manifest_dir = var("CARGO_MANIFEST_DIR")?;
+  let out_dir = var("OUT_DIR")?;
+  let src = write_embedded_model(&manifest_dir, &out_dir, path)?;
+
+  println!("cargo:rerun-if-changed={}", src.display());
+
+  Ok(())
+}
+
+/// Compiles a model to `<out_dir>/punkt/<path>.rs`, where `embed_model!`
+/// looks for it. Returns the path of the model that was read.
+fn write_embedded_model(
+  manifest_dir: &Path,
+  out_dir: &Path,
+  path: &Path,
+) -> Result<PathBuf, LoadError> {
+  let src = manifest_dir.join(path);
+  let data = TrainingData::from_json_file(&src)?;
+  let mut dst = out_dir.join("punkt").join(path).into_os_string();
+
+  dst.push(".rs");
+
+  let dst = PathBuf::from(dst);
+  let write = || -> io::Result<()> {
+    if let Some(dir) = dst.parent() {
+      fs::create_dir_all(dir)?;
+    }
+
+    fs::write(&dst, emit_expr(&data))
+  };
+
+  write().map_err(|e| LoadError::Io(dst.clone(), e))?;
+
+  Ok(src)
+}
+
+#[inline]
+fn sorted<'a, I>(iter: I) -> Vec<&'a str>
+where
+  I: Iterator<Item = &'a str>,
+{
+  let mut keys: Vec<&str> = iter.collect();
+
+  keys.sort();
+  keys
+}
+
+/// Writes a `phf::Set` literal.
+fn emit_set(out: &mut String, keys: Vec<&str>) {
+  let entries: Vec<(&str, ())> = keys.into_iter().map(|k| (k, ())).collect();
+
+  out.push_str("::punkt::codegen::Set { map: ");
+  emit_map(out, &entries, |out, _| out.push_str("()"));
+  out.push_str(" }");
+}
+
+/// Writes a `phf::Map` literal, with each value written by `emit_value`.
+fn emit_map<V, F>(out: &mut String, entries: &[(&str, V)], emit_value: F)
+where
+  F: Fn(&mut String, &V),
+{
+  let keys: Vec<&str> = entries.iter().map(|&(k, _)| k).collect();
+  let state = phf_generator::generate_hash(&keys);
+
+  write!(
+    out,
+    "::punkt::codegen::Map {{ key: {}, disps: ::punkt::codegen::Slice::Static(&[",
+    state.key
+  )
+  .unwrap();
+
+  for &(d1, d2) in state.disps.iter() {
+    write!(out, "({}, {}), ", d1, d2).unwrap();
+  }
+
+  out.push_str("]), entries: ::punkt::codegen::Slice::Static(&[");
+
+  for &i in state.map.iter() {
+    let (k, ref v) = entries[i];
+
+    write!(out, "({:?}, ", k).unwrap();
+    emit_value(out, v);
+    out.push_str("), ");
+  }
+
+  out.push_str("]) }");
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn emit_rust_test() {
+  let data = TrainingData::english();
+  let src = emit_rust(&data);
+
+  assert!(src.contains("pub static COMPILED_DATA: ::punkt::CompiledData"));
+  assert!(src.contains("\"w.va\""));
+  assert!(src.contains("\"##number##\""));
+  assert_eq!(src, emit_rust(&data));
+  assert!(src.contains(&emit_expr(&data)));
+  assert_eq!(
+    emit_rust(&TrainingData::new())
+      .matches("entries: ::punkt::codegen::Slice::Static(&[])")
+      .count(),
+    4
+  );
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn write_embedded_model_test() {
+  let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+  let out_dir = env::temp_dir().join(format!("punkt-embed-{}", ::std::process::id()));
+  let path = Path::new("data/english/english.json");
+  let src = write_embedded_model(manifest_dir, &out_dir, path).unwrap();
+  let expr = fs::read_to_string(out_dir.join("punkt/data/english/english.json.rs")).unwrap();
+
+  assert_eq!(src, manifest_dir.join(path));
+  assert_eq!(expr, emit_expr(&TrainingData::english()));
+  assert!(expr.starts_with("::punkt::CompiledData {"));
+
+  match write_embedded_model(manifest_dir, &out_dir, Path::new("missing.json")) {
+    Err(LoadError::Io(p, _)) => assert_eq!(p, manifest_dir.join("missing.json")),
+    r => panic!("unexpected result: {:?}", r),
+  }
+
+  fs::remove_dir_all(&out_dir).unwrap();
+}
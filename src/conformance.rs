@@ -0,0 +1,269 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Pins the sentences that a model, and parameters produce for a set of
+//! documents, so changes in segmentation are caught when this crate, or the
+//! model is upgraded.
+//!
+//! A suite is a list of raw documents, and their expected sentences. The
+//! expected sentences are stored the way the NLTK comparison corpora of this
+//! crate store them (see `format_sentence`), so a suite can be loaded from
+//! a pair of directories, like `test/raw`, and `test/sentence`, or built
+//! in code.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "english")]
+//! # {
+//! # use punkt::TrainingData;
+//! # use punkt::conformance::ConformanceSuite;
+//! # use punkt::params::Standard;
+//! #
+//! let mut suite = ConformanceSuite::new();
+//!
+//! suite.add(
+//!   "abbreviations",
+//!   "Mr. Smith went to Washington. He arrived at 5 p.m. sharp.",
+//!   &["Mr. Smith went to Washington.", "He arrived at 5 p.m. sharp."],
+//! );
+//!
+//! let data = TrainingData::english();
+//!
+//! assert!(suite.run::<Standard, _>(&data).is_empty());
+//! suite.assert_conforms::<Standard, _>(&data);
+//! # }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use corpus::{Corpus, Document};
+use model::Model;
+use prelude::TokenizerParameters;
+use tokenizer::SentenceTokenizer;
+
+/// Formats a sentence the way the expected sentences are stored: in square
+/// brackets, with double quotes, and line breaks escaped, and carriage
+/// returns removed, so each sentence fits on one line.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::conformance::format_sentence;
+/// #
+/// assert_eq!(format_sentence("\"Hi,\"\nshe said."), r#"[\"Hi,\"\nshe said.]"#);
+/// ```
+pub fn format_sentence(sent: &str) -> String {
+  format!("[{}]", sent)
+    .replace("\"", "\\\"")
+    .replace("\n", "\\n")
+    .replace("\r", "")
+}
+
+/// A sentence that differs from the expected one. See
+/// `ConformanceSuite::run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+  /// The name of the document.
+  pub name: String,
+  /// The position of the sentence in the document.
+  pub index: usize,
+  /// The sentence that was produced, formatted with `format_sentence`.
+  /// `None` if there were fewer sentences than expected.
+  pub actual: Option<String>,
+  /// The expected sentence, formatted with `format_sentence`. `None` if
+  /// there were more sentences than expected.
+  pub expected: Option<String>,
+}
+
+impl fmt::Display for Mismatch {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{} ({}) - you: {} != exp: {}",
+      self.name,
+      self.index,
+      self.actual.as_ref().map(|s| &s[..]).unwrap_or("<none>"),
+      self.expected.as_ref().map(|s| &s[..]).unwrap_or("<none>")
+    )
+  }
+}
+
+/// Raw documents, and their expected sentences.
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceSuite {
+  docs: Vec<Document>,
+}
+
+impl ConformanceSuite {
+  /// Creates an empty suite.
+  #[inline(always)]
+  pub fn new() -> ConformanceSuite {
+    Default::default()
+  }
+
+  /// Loads a suite from a directory of raw documents, and a directory with
+  /// the expected sentences of each, one on each line, formatted with
+  /// `format_sentence`. See `Corpus::load`.
+  pub fn load<R, G>(raw_dir: R, gold_dir: G) -> io::Result<ConformanceSuite>
+  where
+    R: AsRef<Path>,
+    G: AsRef<Path>,
+  {
+    Ok(ConformanceSuite::from(Corpus::load(raw_dir, gold_dir)?))
+  }
+
+  /// Adds a document, and the sentences it is expected to be split into.
+  pub fn add<N, R, S>(&mut self, name: N, raw: R, expected: &[S])
+  where
+    N: Into<String>,
+    R: Into<String>,
+    S: AsRef<str>,
+  {
+    self.docs.push(Document {
+      name: name.into(),
+      raw: raw.into(),
+      gold: expected
+        .iter()
+        .map(|s| format_sentence(s.as_ref()))
+        .collect(),
+    });
+  }
+
+  /// The number of documents.
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    self.docs.len()
+  }
+
+  /// Checks if there are no documents.
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.docs.is_empty()
+  }
+
+  /// Splits each document into sentences, and returns every sentence that
+  /// differs from the expected one, in the order of the documents. Returns
+  /// nothing if every document is split as expected.
+  pub fn run<P, D>(&self, data: &D) -> Vec<Mismatch>
+  where
+    P: TokenizerParameters,
+    D: Model,
+  {
+    let mut mismatches = Vec::new();
+
+    for doc in self.docs.iter() {
+      let actual: Vec<String> = SentenceTokenizer::<P, D>::new(&doc.raw, data)
+        .map(format_sentence)
+        .collect();
+
+      for i in 0..::std::cmp::max(actual.len(), doc.gold.len()) {
+        let actual = actual.get(i).cloned();
+        let expected = doc.gold.get(i).map(|e| e.trim().to_string());
+
+        if actual != expected {
+          mismatches.push(Mismatch {
+            name: doc.name.clone(),
+            index: i,
+            actual: actual,
+            expected: expected,
+          });
+        }
+      }
+    }
+
+    mismatches
+  }
+
+  /// Runs the suite, and panics with the first few sentences that differ,
+  /// if any do.
+  pub fn assert_conforms<P, D>(&self, data: &D)
+  where
+    P: TokenizerParameters,
+    D: Model,
+  {
+    let mismatches = self.run::<P, D>(data);
+
+    if !mismatches.is_empty() {
+      let shown: Vec<String> = mismatches.iter().take(10).map(|m| m.to_string()).collect();
+
+      panic!(
+        "{} sentences differ from the expected sentences:\n{}",
+        mismatches.len(),
+        shown.join("\n")
+      );
+    }
+  }
+}
+
+impl From<Corpus> for ConformanceSuite {
+  /// Uses the expected output of each document of a corpus as its expected
+  /// sentences.
+  #[inline]
+  fn from(corpus: Corpus) -> ConformanceSuite {
+    ConformanceSuite {
+      docs: corpus.iter().cloned().collect(),
+    }
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn conformance_suite_test() {
+  use prelude::Standard;
+  use trainer::TrainingData;
+
+  let data = TrainingData::english();
+  let mut suite = ConformanceSuite::new();
+
+  suite.add(
+    "quote",
+    "\"Stop!\" He left.\nThen\r\nhe ran.",
+    &["\"Stop!", "\" He left.", "Then\nhe ran."],
+  );
+  suite.add("short", "One. Two. Three.", &["One.", "Two."]);
+  suite.add("long", "One two.", &["One.", "Two."]);
+
+  let mismatches = suite.run::<Standard, _>(&data);
+
+  assert_eq!(suite.len(), 3);
+  assert_eq!(
+    mismatches,
+    vec![
+      Mismatch {
+        name: "short".to_string(),
+        index: 2,
+        actual: Some("[Three.]".to_string()),
+        expected: None,
+      },
+      Mismatch {
+        name: "long".to_string(),
+        index: 0,
+        actual: Some("[One two.]".to_string()),
+        expected: Some("[One.]".to_string()),
+      },
+      Mismatch {
+        name: "long".to_string(),
+        index: 1,
+        actual: None,
+        expected: Some("[Two.]".to_string()),
+      },
+    ]
+  );
+  assert_eq!(
+    mismatches[2].to_string(),
+    "long (1) - you: <none> != exp: [Two.]"
+  );
+
+  let suite = ConformanceSuite::load("test/raw", "test/sentence").unwrap();
+
+  assert!(!suite.is_empty());
+  assert!(ConformanceSuite::load("test/raw", "test/missing").is_err());
+}
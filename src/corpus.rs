@@ -0,0 +1,189 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Loads corpora of raw documents, and their expected output, for training,
+//! and evaluation.
+//!
+//! A corpus is a pair of directories. One holds raw documents, and the other
+//! holds the expected output of each document, like its sentences, with one
+//! entry on each line, in a file with the same name. The tests of this crate
+//! are laid out this way, in `test/raw`, and `test/sentence`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use punkt::{SentenceTokenizer, Trainer, TrainingData};
+//! # use punkt::corpus::Corpus;
+//! # use punkt::params::Standard;
+//! #
+//! let corpus = Corpus::load("test/raw", "test/sentence").unwrap();
+//! let (train, test) = corpus.split(0.2);
+//! let trainer: Trainer<Standard> = Trainer::new();
+//! let mut data = TrainingData::new();
+//!
+//! trainer.finalize_counts(train.counts::<Standard>(), &mut data);
+//!
+//! for doc in test.iter() {
+//!   let sents: Vec<&str> = SentenceTokenizer::<Standard>::new(&doc.raw, &data).collect();
+//!
+//!   println!("{}: {} sentences, {} expected", doc.name, sents.len(), doc.gold.len());
+//! }
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::slice;
+
+use prelude::{
+  DefinesMultiChars, DefinesNonPrefixCharacters, DefinesNonWordCharacters, DefinesTypeNormalization,
+};
+use trainer::{corpus_counts, CorpusCounts};
+
+/// A raw document, and its expected output.
+#[derive(Debug, Clone)]
+pub struct Document {
+  /// The file name of the document.
+  pub name: String,
+  /// The contents of the raw document.
+  pub raw: String,
+  /// The lines of the expected output.
+  pub gold: Vec<String>,
+}
+
+/// Documents, ordered by name.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+  docs: Vec<Document>,
+}
+
+impl Corpus {
+  /// Loads every file in `gold_dir`, and the file with the same name in
+  /// `raw_dir`. Fails if a file can't be read, or doesn't have a raw
+  /// document. Subdirectories are skipped.
+  pub fn load<R, G>(raw_dir: R, gold_dir: G) -> io::Result<Corpus>
+  where
+    R: AsRef<Path>,
+    G: AsRef<Path>,
+  {
+    let mut docs = Vec::new();
+
+    for entry in fs::read_dir(gold_dir)? {
+      let path = entry?.path();
+
+      if !path.is_file() {
+        continue;
+      }
+
+      let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => continue,
+      };
+      let raw = fs::read_to_string(raw_dir.as_ref().join(&name))?;
+      let gold = fs::read_to_string(&path)?
+        .lines()
+        .map(String::from)
+        .collect();
+
+      docs.push(Document {
+        name: name,
+        raw: raw,
+        gold: gold,
+      });
+    }
+
+    docs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(Corpus { docs: docs })
+  }
+
+  /// The number of documents.
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    self.docs.len()
+  }
+
+  /// Checks if there are no documents.
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.docs.is_empty()
+  }
+
+  /// Iterates over the documents, ordered by name.
+  #[inline(always)]
+  pub fn iter(&self) -> slice::Iter<'_, Document> {
+    self.docs.iter()
+  }
+
+  /// Splits the documents into a training, and a test corpus, with about
+  /// `test_ratio` of the documents in the test corpus. Test documents are
+  /// spread evenly across the corpus, so the split is always the same.
+  pub fn split(&self, test_ratio: f64) -> (Corpus, Corpus) {
+    let mut train = Corpus::default();
+    let mut test = Corpus::default();
+
+    for (i, doc) in self.docs.iter().enumerate() {
+      if ((i + 1) as f64 * test_ratio).floor() > (i as f64 * test_ratio).floor() {
+        test.docs.push(doc.clone());
+      } else {
+        train.docs.push(doc.clone());
+      }
+    }
+
+    (train, test)
+  }
+
+  /// The merged `corpus_counts` of the raw documents, for
+  /// `Trainer::finalize_counts`.
+  pub fn counts<P>(&self) -> CorpusCounts
+  where
+    P: DefinesMultiChars
+      + DefinesNonPrefixCharacters
+      + DefinesNonWordCharacters
+      + DefinesTypeNormalization,
+  {
+    let mut counts = CorpusCounts::new();
+
+    for doc in self.docs.iter() {
+      counts.merge(corpus_counts::<P>(&doc.raw));
+    }
+
+    counts
+  }
+}
+
+impl<'a> IntoIterator for &'a Corpus {
+  type Item = &'a Document;
+  type IntoIter = slice::Iter<'a, Document>;
+
+  #[inline(always)]
+  fn into_iter(self) -> slice::Iter<'a, Document> {
+    self.iter()
+  }
+}
+
+#[test]
+fn corpus_load_split_test() {
+  let corpus = Corpus::load("test/raw", "test/sentence").unwrap();
+  let names: Vec<&str> = corpus.iter().map(|d| &d.name[..]).collect();
+
+  assert_eq!(names.len(), fs::read_dir("test/sentence").unwrap().count());
+  assert!(names.windows(2).all(|w| w[0] < w[1]));
+  assert!(corpus
+    .iter()
+    .all(|d| !d.raw.is_empty() && !d.gold.is_empty()));
+
+  let (train, test) = corpus.split(0.5);
+
+  assert_eq!(train.len() + test.len(), corpus.len());
+  assert_eq!(test.len(), corpus.len() / 2);
+  assert_eq!(corpus.split(0.0).1.len(), 0);
+  assert_eq!(corpus.split(1.0).0.len(), 0);
+  assert!(Corpus::load("test/raw", "test/missing").is_err());
+  assert!(Corpus::load("test/missing", "test/sentence").is_err());
+}
@@ -0,0 +1,271 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use memory;
+use metadata::Fnv;
+
+/// Counts how often each type occurs in the documents that a trainer is
+/// finalizing. The type counts grow with the vocabulary of the corpus, so
+/// they can be replaced with an approximate counter, like `CountMinSketch`,
+/// to bound their memory. See `Trainer::finalize_counts_with`.
+///
+/// Only the type counts are approximate. The trainer still needs every
+/// token of the corpus, in order, which `CorpusCounts` keeps, and counts
+/// the collocations, and sentence starters that follow a period exactly.
+pub trait TypeCounter {
+  /// Adds `n` occurrences of a type.
+  fn add(&mut self, typ: &str, n: usize);
+
+  /// The number of occurrences of a type. Can be an estimate.
+  fn count(&self, typ: &str) -> usize;
+
+  /// The number of occurrences of every type.
+  fn total(&self) -> usize;
+
+  /// The estimated heap usage of the counter, in bytes, which a
+  /// `MemoryBudget` limits. Counters with a fixed size can't be made
+  /// smaller, so they report 0, which is the default.
+  #[inline(always)]
+  fn memory_usage(&self) -> usize {
+    0
+  }
+
+  /// Drops the types that occurred `threshold` times or less, to stay within
+  /// a `MemoryBudget`. Dropped types count as never seen, but the total
+  /// stays the same. Returns the number of types that were dropped. Does
+  /// nothing by default.
+  #[inline(always)]
+  fn prune(&mut self, _threshold: usize) -> usize {
+    0
+  }
+}
+
+/// Counts types exactly. Used by `Trainer::finalize_counts`.
+#[derive(Debug, Clone, Default)]
+pub struct ExactCounter {
+  counts: HashMap<String, usize>,
+  total: usize,
+  // The length of every type that is counted, to estimate the heap usage
+  // without walking the map.
+  type_bytes: usize,
+}
+
+impl ExactCounter {
+  /// Creates an empty counter.
+  #[inline(always)]
+  pub fn new() -> ExactCounter {
+    Default::default()
+  }
+}
+
+impl TypeCounter for ExactCounter {
+  #[inline]
+  fn add(&mut self, typ: &str, n: usize) {
+    match self.counts.get_mut(typ) {
+      Some(c) => *c += n,
+      None => {
+        self.counts.insert(typ.to_string(), n);
+        self.type_bytes += typ.len();
+      }
+    }
+
+    self.total += n;
+  }
+
+  #[inline]
+  fn count(&self, typ: &str) -> usize {
+    self.counts.get(typ).cloned().unwrap_or(0)
+  }
+
+  #[inline(always)]
+  fn total(&self) -> usize {
+    self.total
+  }
+
+  #[inline]
+  fn memory_usage(&self) -> usize {
+    memory::table_size(self.counts.capacity(), size_of::<(String, usize)>()) + self.type_bytes
+  }
+
+  fn prune(&mut self, threshold: usize) -> usize {
+    let before = self.counts.len();
+    let mut type_bytes = self.type_bytes;
+
+    self.counts.retain(|typ, &mut n| {
+      if n > threshold {
+        true
+      } else {
+        type_bytes -= typ.len();
+        false
+      }
+    });
+    self.counts.shrink_to_fit();
+    self.type_bytes = type_bytes;
+
+    before - self.counts.len()
+  }
+}
+
+/// Counts types approximately, in a fixed amount of memory. The rest of the
+/// memory of training isn't bounded by the sketch. See `TypeCounter`.
+///
+/// Each type is counted in one cell of each of `depth` rows of `width`
+/// cells, and its count is the smallest of its cells. Counts are never
+/// underestimated, and are overestimated by at most `e * total / width`
+/// with a probability of at least `1 - 0.5^depth`. Overestimated counts
+/// mostly affect rare types, which can make the trainer miss some rare
+/// abbreviations, and collocations.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::{corpus_counts, CountMinSketch, Trainer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "The U.S. economy grew. The U.S. team won. It rained.";
+/// let trainer: Trainer<Standard> = Trainer::new();
+/// let mut data = TrainingData::new();
+///
+/// trainer.finalize_counts_with(
+///   corpus_counts::<Standard>(doc),
+///   CountMinSketch::new(1 << 12, 4),
+///   &mut data,
+/// );
+///
+/// assert!(data.contains_abbrev("u.s"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CountMinSketch {
+  width: usize,
+  depth: usize,
+  cells: Vec<usize>,
+  total: usize,
+}
+
+impl CountMinSketch {
+  /// Creates an empty sketch with `depth` rows of `width` cells. Uses
+  /// `width * depth` counters of memory, no matter how many types are
+  /// counted.
+  ///
+  /// # Panics
+  ///
+  /// If `width`, or `depth` is 0.
+  pub fn new(width: usize, depth: usize) -> CountMinSketch {
+    assert!(width > 0 && depth > 0, "sketch must have at least one cell");
+
+    CountMinSketch {
+      width: width,
+      depth: depth,
+      cells: vec![0; width * depth],
+      total: 0,
+    }
+  }
+
+  /// The index of the cell of a type in a row. The hashes of the rows are
+  /// derived from the two halves of one hash.
+  #[inline]
+  fn index(&self, h: u64, row: usize) -> usize {
+    let (h1, h2) = (h & 0xffff_ffff, (h >> 32) | 1);
+    let col = h1.wrapping_add((row as u64).wrapping_mul(h2)) % self.width as u64;
+
+    row * self.width + col as usize
+  }
+}
+
+#[inline]
+fn hash(typ: &str) -> u64 {
+  let mut h = Fnv::new();
+
+  h.write(typ.as_bytes());
+  h.0
+}
+
+impl TypeCounter for CountMinSketch {
+  #[inline]
+  fn add(&mut self, typ: &str, n: usize) {
+    let h = hash(typ);
+
+    for row in 0..self.depth {
+      let i = self.index(h, row);
+
+      self.cells[i] += n;
+    }
+
+    self.total += n;
+  }
+
+  #[inline]
+  fn count(&self, typ: &str) -> usize {
+    let h = hash(typ);
+
+    (0..self.depth)
+      .map(|row| self.cells[self.index(h, row)])
+      .min()
+      .unwrap_or(0)
+  }
+
+  #[inline(always)]
+  fn total(&self) -> usize {
+    self.total
+  }
+}
+
+#[test]
+fn type_counter_test() {
+  let mut exact = ExactCounter::new();
+  let mut sketch = CountMinSketch::new(64, 4);
+  let mut tiny = CountMinSketch::new(1, 1);
+
+  for counter in [&mut exact as &mut dyn TypeCounter, &mut sketch, &mut tiny].iter_mut() {
+    counter.add("the", 3);
+    counter.add("u.s.", 1);
+    counter.add("the", 2);
+  }
+
+  assert_eq!(exact.count("the"), 5);
+  assert_eq!(exact.count("u.s."), 1);
+  assert_eq!(exact.count("missing"), 0);
+  assert_eq!(exact.total(), 6);
+
+  assert!(sketch.count("the") >= 5);
+  assert!(sketch.count("u.s.") >= 1);
+  assert_eq!(sketch.total(), 6);
+
+  // A single cell counts everything together.
+  assert_eq!(tiny.count("missing"), 6);
+
+  // Counts are never underestimated.
+  let mut sketch = CountMinSketch::new(16, 3);
+  let words: Vec<String> = (0..200).map(|i| format!("w{}", i)).collect();
+
+  for (i, w) in words.iter().enumerate() {
+    sketch.add(w, i % 7 + 1);
+  }
+
+  assert!(words
+    .iter()
+    .enumerate()
+    .all(|(i, w)| sketch.count(w) >= i % 7 + 1));
+
+  // Pruning drops the rare types, but keeps the total.
+  let usage = exact.memory_usage();
+
+  assert!(usage > 0);
+  assert_eq!(exact.prune(1), 1);
+  assert_eq!(exact.count("u.s."), 0);
+  assert_eq!(exact.count("the"), 5);
+  assert_eq!(exact.total(), 6);
+  assert!(exact.memory_usage() < usage);
+  assert_eq!(exact.prune(5), 1);
+  assert_eq!(exact.memory_usage(), 0);
+  assert_eq!(sketch.prune(1_000), 0);
+  assert_eq!(sketch.memory_usage(), 0);
+}
@@ -0,0 +1,107 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+/// A position in a document that the scanners of the tokenizers move forward
+/// one character at a time. Reading the character at the position decodes
+/// ASCII directly from the bytes, without slicing the document, which checks
+/// the position is a character boundary again for every character.
+#[derive(Clone, Copy)]
+pub(crate) struct CharCursor<'a> {
+  doc: &'a str,
+  pos: usize,
+}
+
+impl<'a> CharCursor<'a> {
+  #[inline(always)]
+  pub fn new(doc: &'a str) -> CharCursor<'a> {
+    CharCursor { doc: doc, pos: 0 }
+  }
+
+  /// The byte offset of the cursor in the document.
+  #[inline(always)]
+  pub fn pos(&self) -> usize {
+    self.pos
+  }
+
+  /// Moves the cursor to a byte offset, that should be at a character
+  /// boundary, or at the end of the document. Otherwise, the cursor is moved
+  /// to the next character boundary.
+  #[inline(always)]
+  pub fn seek(&mut self, pos: usize) {
+    let mut pos = pos.min(self.doc.len());
+
+    while !self.doc.is_char_boundary(pos) {
+      pos += 1;
+    }
+
+    self.pos = pos;
+  }
+
+  /// Returns the character at the cursor, or `None` at the end of the
+  /// document.
+  #[inline(always)]
+  pub fn peek(&self) -> Option<char> {
+    match self.doc.as_bytes().get(self.pos) {
+      Some(&b) if b < 0x80 => Some(b as char),
+      Some(_) => self.doc.get(self.pos..).and_then(|s| s.chars().next()),
+      None => None,
+    }
+  }
+
+  /// Moves the cursor past a character, which should be the one at the
+  /// cursor.
+  #[inline(always)]
+  pub fn advance(&mut self, c: char) {
+    self.pos += c.len_utf8();
+  }
+
+  /// Returns the rest of the document, from the cursor.
+  #[inline(always)]
+  pub fn rest(&self) -> &'a str {
+    self.doc.get(self.pos..).unwrap_or("")
+  }
+}
+
+#[test]
+fn char_cursor_test() {
+  let doc = "a\u{e9}\u{202f}.";
+  let mut cursor = CharCursor::new(doc);
+  let mut chars = Vec::new();
+
+  while let Some(c) = cursor.peek() {
+    chars.push((cursor.pos(), c));
+    cursor.advance(c);
+  }
+
+  assert_eq!(chars, doc.char_indices().collect::<Vec<_>>());
+  assert_eq!(cursor.rest(), "");
+
+  cursor.seek(1);
+  assert_eq!(cursor.peek(), Some('\u{e9}'));
+  assert_eq!(cursor.rest(), "\u{e9}\u{202f}.");
+
+  // Offsets inside of a character, or past the end are rounded up.
+  cursor.seek(2);
+  assert_eq!(cursor.pos(), 3);
+  cursor.seek(100);
+  assert_eq!(cursor.pos(), doc.len());
+}
@@ -0,0 +1,218 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use format::LoadError;
+use json::Json;
+use trainer::TrainingData;
+
+/// Named models, stored together in one JSON file. Lets a service that
+/// segments text from several domains ship a single model file.
+///
+/// The file is an object with a `models` object, that maps each name to a
+/// model in the format written by `TrainingData::to_json`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{TrainingData, TrainingDataSet};
+/// #
+/// let mut set = TrainingDataSet::new();
+///
+/// set.insert("news", TrainingData::english());
+/// set.insert("chat", TrainingData::new());
+///
+/// let set: TrainingDataSet = set.to_json().parse().unwrap();
+///
+/// assert!(set.get("news").unwrap().contains_abbrev("jan"));
+/// assert!(set.get("legal").is_none());
+/// assert_eq!(set.names().collect::<Vec<&str>>(), vec!["chat", "news"]);
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct TrainingDataSet {
+  models: BTreeMap<String, TrainingData>,
+}
+
+impl TrainingDataSet {
+  /// Creates an empty set.
+  #[inline(always)]
+  pub fn new() -> TrainingDataSet {
+    Default::default()
+  }
+
+  /// Loads a set from a file written by `save`.
+  pub fn load<T: AsRef<Path>>(path: T) -> Result<TrainingDataSet, LoadError> {
+    let path = path.as_ref();
+    let s = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+
+    TrainingDataSet::from_json(&s)
+  }
+
+  /// Writes the set to a file, as JSON.
+  #[inline]
+  pub fn save<T: AsRef<Path>>(&self, path: T) -> io::Result<()> {
+    fs::write(path, self.to_json())
+  }
+
+  /// Deserializes a set from JSON.
+  fn from_json(s: &str) -> Result<TrainingDataSet, LoadError> {
+    let mut obj = match Json::from_str(s) {
+      Ok(Json::Object(obj)) => obj,
+      _ => return Err(LoadError::Json("no json object found containing models")),
+    };
+    let mut set = TrainingDataSet::new();
+
+    match obj.remove("models") {
+      Some(Json::Object(models)) => {
+        for (name, model) in models.into_iter() {
+          let data = TrainingData::from_json_value(model)?;
+
+          set.models.insert(name, data);
+        }
+      }
+      _ => return Err(LoadError::Json("failed to parse models section")),
+    }
+
+    Ok(set)
+  }
+
+  /// Serializes the set into JSON. Models are written in order of their
+  /// names, so the output is deterministic.
+  pub fn to_json(&self) -> String {
+    let mut obj = BTreeMap::new();
+
+    obj.insert(
+      "models".to_string(),
+      Json::Object(
+        self
+          .models
+          .iter()
+          .map(|(name, data)| (name.clone(), data.to_json_value()))
+          .collect(),
+      ),
+    );
+
+    Json::Object(obj).to_string()
+  }
+
+  /// Returns the model with a name.
+  #[inline]
+  pub fn get(&self, name: &str) -> Option<&TrainingData> {
+    self.models.get(name)
+  }
+
+  /// Returns the model with a name, for updating it.
+  #[inline]
+  pub fn get_mut(&mut self, name: &str) -> Option<&mut TrainingData> {
+    self.models.get_mut(name)
+  }
+
+  /// Adds a model with a name. Returns the model that had the name before,
+  /// if there was one.
+  #[inline]
+  pub fn insert(&mut self, name: &str, data: TrainingData) -> Option<TrainingData> {
+    self.models.insert(name.to_string(), data)
+  }
+
+  /// Removes the model with a name, and returns it.
+  #[inline]
+  pub fn remove(&mut self, name: &str) -> Option<TrainingData> {
+    self.models.remove(name)
+  }
+
+  /// Returns an iterator over the names of the models, in sorted order.
+  #[inline]
+  pub fn names(&self) -> impl Iterator<Item = &str> {
+    self.models.keys().map(|s| &s[..])
+  }
+
+  /// The number of models.
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    self.models.len()
+  }
+
+  /// Checks if there are no models.
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.models.is_empty()
+  }
+}
+
+impl FromStr for TrainingDataSet {
+  type Err = &'static str;
+
+  /// Deserializes a set from JSON.
+  #[inline]
+  fn from_str(s: &str) -> Result<TrainingDataSet, &'static str> {
+    TrainingDataSet::from_json(s).map_err(|e| e.message())
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn training_data_set_test() {
+  use std::env;
+
+  let mut set = TrainingDataSet::new();
+  let mut legal = TrainingData::new();
+
+  legal.insert_abbrev("art");
+
+  assert!(set.is_empty());
+  assert!(set.insert("news", TrainingData::english()).is_none());
+  assert!(set.insert("legal", legal).is_none());
+  assert!(set.insert("chat", TrainingData::new()).is_none());
+  assert_eq!(set.len(), 3);
+
+  let path = env::temp_dir().join(format!("punkt-set-{}.json", ::std::process::id()));
+
+  set.save(&path).unwrap();
+
+  let loaded = TrainingDataSet::load(&path).unwrap();
+
+  fs::remove_file(&path).unwrap();
+
+  assert_eq!(
+    loaded.names().collect::<Vec<&str>>(),
+    vec!["chat", "legal", "news"]
+  );
+  assert!(loaded.get("legal").unwrap().contains_abbrev("art"));
+  assert!(!loaded.get("news").unwrap().contains_abbrev("art"));
+  assert_eq!(
+    loaded.get("news").unwrap().content_hash(),
+    TrainingData::english().content_hash()
+  );
+  assert_eq!(loaded.to_json(), set.to_json());
+
+  assert!(set.remove("chat").is_some());
+  assert!(set.get("chat").is_none());
+  assert!(set.get_mut("legal").is_some());
+
+  assert_eq!(
+    "[]".parse::<TrainingDataSet>().unwrap_err(),
+    "no json object found containing models"
+  );
+  assert_eq!(
+    "{}".parse::<TrainingDataSet>().unwrap_err(),
+    "failed to parse models section"
+  );
+  assert_eq!(
+    r#"{"models": {"a": []}}"#.parse::<TrainingDataSet>().unwrap_err(),
+    "no json object found containing training data"
+  );
+  assert!(TrainingDataSet::load("test/missing.json").is_err());
+}
@@ -0,0 +1,168 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Hashes the sentences of documents, to find sentences that are repeated
+//! within, or across documents.
+//!
+//! Hashes are stable across versions, and platforms, so they can be stored,
+//! and compared with the hashes of documents that are processed later.
+//! Sentences are normalized before they are hashed, so sentences that only
+//! differ in line wrapping, or case, can be treated as the same.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "english")]
+//! # {
+//! # use std::collections::HashSet;
+//! # use punkt::TrainingData;
+//! # use punkt::dedup::sentence_hashes;
+//! #
+//! let data = TrainingData::english();
+//! let a = "The sky is blue. Mr. Smith agreed.";
+//! let b = "It rained. The sky\nis blue.";
+//! let seen: HashSet<u64> = sentence_hashes(a, &data).into_iter().map(|(h, _)| h).collect();
+//!
+//! let repeated: Vec<&str> = sentence_hashes(b, &data)
+//!   .into_iter()
+//!   .filter(|&(h, _)| seen.contains(&h))
+//!   .map(|(_, (start, end))| &b[start..end])
+//!   .collect();
+//!
+//! assert_eq!(repeated, vec!["The sky\nis blue."]);
+//! # }
+//! ```
+
+use metadata::Fnv;
+use model::Model;
+use prelude::{Standard, TokenizerParameters};
+use tokenizer::SentenceByteOffsetTokenizer;
+
+/// How a sentence is normalized before it is hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalization {
+  /// Trims the sentence, and collapses runs of whitespace to a single space.
+  pub whitespace: bool,
+  /// Lowercases the sentence.
+  pub case: bool,
+}
+
+impl Default for Normalization {
+  /// Normalizes whitespace, but keeps case.
+  #[inline]
+  fn default() -> Normalization {
+    Normalization {
+      whitespace: true,
+      case: false,
+    }
+  }
+}
+
+/// Hashes a single sentence, the same way the sentences of a document are
+/// hashed.
+pub fn hash_sentence(sent: &str, norm: Normalization) -> u64 {
+  let mut h = Fnv::new();
+
+  if norm.whitespace {
+    for (i, word) in sent.split_whitespace().enumerate() {
+      if i > 0 {
+        h.write(b" ");
+      }
+
+      write_word(&mut h, word, norm.case);
+    }
+  } else {
+    write_word(&mut h, sent, norm.case);
+  }
+
+  h.0
+}
+
+#[inline]
+fn write_word(h: &mut Fnv, word: &str, lowercase: bool) {
+  if lowercase {
+    let mut buf = [0; 4];
+
+    for c in word.chars().flat_map(char::to_lowercase) {
+      h.write(c.encode_utf8(&mut buf).as_bytes());
+    }
+  } else {
+    h.write(word.as_bytes());
+  }
+}
+
+/// Hashes the sentences of a document with the standard parameters, and the
+/// default normalization. See `sentence_hashes_with_params`.
+#[inline]
+pub fn sentence_hashes<D>(doc: &str, data: &D) -> Vec<(u64, (usize, usize))>
+where
+  D: Model,
+{
+  sentence_hashes_with_params::<Standard, D>(doc, data, Normalization::default())
+}
+
+/// Returns the hash of each sentence in a document, and its byte offsets.
+pub fn sentence_hashes_with_params<P, D>(
+  doc: &str,
+  data: &D,
+  norm: Normalization,
+) -> Vec<(u64, (usize, usize))>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  SentenceByteOffsetTokenizer::<P, D>::new(doc, data)
+    .map(|(start, end)| (hash_sentence(&doc[start..end], norm), (start, end)))
+    .collect()
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn sentence_hashes_test() {
+  use trainer::TrainingData;
+
+  let data = TrainingData::english();
+  let exact = Normalization {
+    whitespace: false,
+    case: false,
+  };
+  let folded = Normalization {
+    whitespace: true,
+    case: true,
+  };
+
+  // Pinned, so hashes stay comparable with ones that were stored.
+  assert_eq!(hash_sentence("", exact), 0xcbf2_9ce4_8422_2325);
+  assert_eq!(hash_sentence("a", exact), 0xaf63_dc4c_8601_ec8c);
+
+  assert_eq!(
+    hash_sentence("  The  sky\nis blue. ", Normalization::default()),
+    hash_sentence("The sky is blue.", exact)
+  );
+  assert!(hash_sentence("The sky\nis blue.", exact) != hash_sentence("The sky is blue.", exact));
+  assert!(
+    hash_sentence("THE SKY IS BLUE.", Normalization::default())
+      != hash_sentence("the sky is blue.", Normalization::default())
+  );
+  assert_eq!(
+    hash_sentence("THE SKY IS BLUE.", folded),
+    hash_sentence("the sky is blue.", folded)
+  );
+
+  let doc = "Dr. Smith left. It rained. Dr. Smith  left.";
+  let hashes = sentence_hashes(doc, &data);
+
+  assert_eq!(hashes.len(), 3);
+  assert_eq!(hashes[0].0, hashes[2].0);
+  assert!(hashes[0].0 != hashes[1].0);
+  assert_eq!(&doc[(hashes[1].1).0..(hashes[1].1).1], "It rained.");
+  assert!(
+    sentence_hashes_with_params::<Standard, _>(doc, &data, exact)[0].0
+      != sentence_hashes_with_params::<Standard, _>(doc, &data, exact)[2].0
+  );
+}
@@ -0,0 +1,352 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports on how well a model fits a document.
+//!
+//! A model trained on text from one domain can do poorly on another, where
+//! it doesn't know the abbreviations. `model_fit` shows what decided each
+//! period in a document. If most periods are decided by default, and there
+//! are many abbreviation candidates, retraining on text from the new domain
+//! is likely to help.
+//!
+//! A model also does poorly on sentences in another language.
+//! `foreign_sentences` finds sentences whose letters don't look like the
+//! ones the model was trained on, so they can be sent to another model.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "english")]
+//! # {
+//! # use punkt::TrainingData;
+//! # use punkt::diagnostics::model_fit;
+//! #
+//! let doc = "The approx. cost was low. It rose to 5 dollars, i.e. not much.";
+//! let report = model_fit(doc, &TrainingData::english());
+//!
+//! assert_eq!(report.period_contexts, 4);
+//! assert_eq!(
+//!   report.abbrev_candidates,
+//!   vec![("approx".to_string(), 1), ("i.e".to_string(), 1)]
+//! );
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use model::Model;
+use prelude::{Standard, TokenizerParameters};
+use token::Token;
+use tokenizer::{annotate_second_pass, SecondPassRule, SentenceByteOffsetTokenizer, WordTokenizer};
+use trainer::TrainingData;
+
+/// How the periods in a document were decided by a model. See `model_fit`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FitReport {
+  /// The number of tokens ending with a period, other than ellipses.
+  pub period_contexts: usize,
+  /// Periods decided by a known abbreviation.
+  pub abbreviations: usize,
+  /// Periods decided by a known collocation with the next word.
+  pub collocations: usize,
+  /// Periods decided by the orthographic context of the next word.
+  pub orthographic: usize,
+  /// Periods after an abbreviation that were decided by a known sentence
+  /// starter.
+  pub sentence_starters: usize,
+  /// Periods after a number that were decided by
+  /// `TokenizerParameters::NUMBER_PERIODS`, instead of the model.
+  pub numbers: usize,
+  /// Periods that nothing in the model applied to, and were decided to end
+  /// a sentence.
+  pub defaults: usize,
+  /// Words that were decided to end a sentence by default, but look like
+  /// abbreviations, because they contain a period, or are followed by a
+  /// lowercase word. Sorted by how often they were seen, most often first.
+  pub abbrev_candidates: Vec<(String, usize)>,
+}
+
+impl FitReport {
+  /// The fraction of periods that were decided by default. Returns 0 if
+  /// there are no periods.
+  #[inline]
+  pub fn default_ratio(&self) -> f64 {
+    if self.period_contexts == 0 {
+      0.0
+    } else {
+      self.defaults as f64 / self.period_contexts as f64
+    }
+  }
+}
+
+/// Reports how the periods in a document are decided by a model, using the
+/// standard parameters.
+#[inline]
+pub fn model_fit<D>(doc: &str, data: &D) -> FitReport
+where
+  D: Model,
+{
+  model_fit_with_params::<Standard, D>(doc, data)
+}
+
+/// Reports how the periods in a document are decided by a model.
+pub fn model_fit_with_params<P, D>(doc: &str, data: &D) -> FitReport
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  let mut report = FitReport::default();
+  let mut candidates = HashMap::new();
+  let mut prv: Option<Token> = None;
+
+  for mut t in WordTokenizer::<P>::new(doc) {
+    ::util::annotate_first_pass::<P, D>(&mut t, data);
+
+    if let Some(mut p) = prv.take() {
+      let rule = annotate_second_pass::<P, D>(&mut t, &mut p, data);
+
+      record(&mut report, &mut candidates, &p, Some(&t), rule);
+    }
+
+    prv = Some(t);
+  }
+
+  if let Some(p) = prv {
+    record(&mut report, &mut candidates, &p, None, None);
+  }
+
+  report.abbrev_candidates = candidates.into_iter().collect();
+  report
+    .abbrev_candidates
+    .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+  report
+}
+
+/// Adds the decision made for a token to a report.
+fn record(
+  report: &mut FitReport,
+  candidates: &mut HashMap<String, usize>,
+  tok: &Token,
+  nxt: Option<&Token>,
+  rule: Option<SecondPassRule>,
+) {
+  if !tok.has_final_period() || tok.is_ellipsis() {
+    return;
+  }
+
+  report.period_contexts += 1;
+
+  match rule {
+    Some(SecondPassRule::Collocation) => report.collocations += 1,
+    Some(SecondPassRule::AbbrevOrthographic) | Some(SecondPassRule::Orthographic) => {
+      report.orthographic += 1
+    }
+    Some(SecondPassRule::AbbrevSentenceStarter) => report.sentence_starters += 1,
+    Some(SecondPassRule::NumberPeriods) => report.numbers += 1,
+    Some(SecondPassRule::AbbrevContext) => report.abbreviations += 1,
+    None if tok.is_abbrev() => report.abbreviations += 1,
+    None => {
+      report.defaults += 1;
+
+      let word = tok.tok_without_period();
+      let looks_abbrev = word.contains('.') || nxt.map(|t| t.is_lowercase()).unwrap_or(false);
+
+      if looks_abbrev && !tok.is_numeric() && !tok.is_initial() && tok.is_non_punct() {
+        *candidates.entry(word.to_string()).or_insert(0) += 1;
+      }
+    }
+  }
+}
+
+/// How the letters of a text are spread over the blocks of 128 Unicode code
+/// points. Scripts mostly occupy their own blocks, so comparing profiles
+/// tells apart text in different scripts. Languages that share a script,
+/// like English and German, have similar profiles.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CharProfile {
+  blocks: HashMap<u32, f64>,
+}
+
+impl CharProfile {
+  /// The profile of the letters in a text.
+  pub fn of_text(text: &str) -> CharProfile {
+    CharProfile::of_words(Some(text))
+  }
+
+  /// The profile of the words a model saw while training. Each word counts
+  /// once, no matter how often it was seen.
+  pub fn of_model(data: &TrainingData) -> CharProfile {
+    CharProfile::of_words(data.orthographic_contexts().map(|(w, _)| w))
+  }
+
+  fn of_words<'a, I>(words: I) -> CharProfile
+  where
+    I: IntoIterator<Item = &'a str>,
+  {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+    let mut total = 0;
+
+    for c in words.into_iter().flat_map(|w| w.chars()) {
+      if c.is_alphabetic() {
+        *counts.entry(c as u32 >> 7).or_insert(0) += 1;
+        total += 1;
+      }
+    }
+
+    CharProfile {
+      blocks: counts
+        .into_iter()
+        .map(|(b, n)| (b, n as f64 / total as f64))
+        .collect(),
+    }
+  }
+
+  /// Checks if the text had no letters.
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.blocks.is_empty()
+  }
+
+  /// How different two profiles are, from 0 (the letters are spread the
+  /// same way) to 1 (no letters in the same blocks). Returns 0 if either
+  /// profile is empty.
+  pub fn distance(&self, other: &CharProfile) -> f64 {
+    if self.is_empty() || other.is_empty() {
+      return 0.0;
+    }
+
+    let shared: f64 = self
+      .blocks
+      .iter()
+      .map(|(b, &p)| p.min(other.blocks.get(b).cloned().unwrap_or(0.0)))
+      .sum();
+
+    (1.0 - shared).max(0.0)
+  }
+}
+
+/// A sentence whose letters differ from the letters a model was trained on.
+/// See `foreign_sentences`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForeignSentence {
+  /// The byte offset of the start of the sentence.
+  pub start: usize,
+  /// The byte offset of the end of the sentence.
+  pub end: usize,
+  /// The distance between the profile of the sentence, and the profile of
+  /// the model. See `CharProfile::distance`.
+  pub distance: f64,
+}
+
+/// Segments a document, and returns the sentences whose `CharProfile` is
+/// further than `max_distance` from `profile`, which is usually the profile
+/// of the model (`CharProfile::of_model`). This is a heuristic. It finds
+/// sentences in another script, like Russian in an English document, but
+/// not in another language with the same script. Sentences without letters
+/// are never returned.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::TrainingData;
+/// # use punkt::diagnostics::{foreign_sentences, CharProfile};
+/// # use punkt::params::Standard;
+/// #
+/// let data = TrainingData::english();
+/// let profile = CharProfile::of_model(&data);
+/// let doc = "He said hello. \u{41e}\u{43d} \u{443}\u{448}\u{435}\u{43b}. It rained.";
+/// let foreign = foreign_sentences::<Standard, _>(doc, &data, &profile, 0.5);
+///
+/// assert_eq!(foreign.len(), 1);
+///
+/// let sent = &doc[foreign[0].start..foreign[0].end];
+///
+/// assert_eq!(sent, "\u{41e}\u{43d} \u{443}\u{448}\u{435}\u{43b}.");
+/// # }
+/// ```
+pub fn foreign_sentences<P, D>(
+  doc: &str,
+  data: &D,
+  profile: &CharProfile,
+  max_distance: f64,
+) -> Vec<ForeignSentence>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  SentenceByteOffsetTokenizer::<P, D>::new(doc, data)
+    .filter_map(|(start, end)| {
+      let distance = CharProfile::of_text(&doc[start..end]).distance(profile);
+
+      if distance > max_distance {
+        Some(ForeignSentence {
+          start: start,
+          end: end,
+          distance: distance,
+        })
+      } else {
+        None
+      }
+    })
+    .collect()
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn model_fit_test() {
+  use trainer::TrainingData;
+
+  let doc = "Mr. Smith met J. Doe. He paid 5. Then etc. it was done. Done.";
+  let report = model_fit(doc, &TrainingData::english());
+
+  assert_eq!(
+    report.period_contexts,
+    report.abbreviations
+      + report.collocations
+      + report.orthographic
+      + report.sentence_starters
+      + report.numbers
+      + report.defaults
+  );
+  assert_eq!(report.period_contexts, 7);
+  assert_eq!(report.abbreviations, 1);
+  assert_eq!(report.orthographic, 1);
+  assert_eq!(report.abbrev_candidates, vec![("etc".to_string(), 1)]);
+
+  let report = model_fit(doc, &TrainingData::new());
+
+  assert_eq!(report.abbreviations, 0);
+  assert_eq!(report.defaults, 6);
+  assert_eq!(report.abbrev_candidates, vec![("etc".to_string(), 1)]);
+  assert_eq!(model_fit("", &TrainingData::new()), FitReport::default());
+  assert_eq!(FitReport::default().default_ratio(), 0.0);
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn foreign_sentences_test() {
+  let data = TrainingData::english();
+  let profile = CharProfile::of_model(&data);
+  let doc = "The talks went well. \u{414}\u{430}, \u{442}\u{430}\u{43a}. \
+             Der B\u{e4}r schl\u{e4}ft. Then it was 5. \u{393}\u{3b5}\u{3b9}\u{3b1}. Bye.";
+  let foreign = foreign_sentences::<Standard, _>(doc, &data, &profile, 0.5);
+  let sents: Vec<&str> = foreign.iter().map(|s| &doc[s.start..s.end]).collect();
+
+  assert_eq!(sents.len(), 2);
+  assert!(sents[0].starts_with("\u{414}"));
+  assert_eq!(sents[1], "\u{393}\u{3b5}\u{3b9}\u{3b1}.");
+  assert!(foreign.iter().all(|s| s.distance > 0.9));
+
+  assert!(profile.distance(&CharProfile::of_text("Der B\u{e4}r schl\u{e4}ft.")) < 0.5);
+  assert_eq!(profile.distance(&CharProfile::of_text("5.")), 0.0);
+  assert_eq!(profile.distance(&profile), 0.0);
+  assert!(CharProfile::of_text("").is_empty());
+  assert!(foreign_sentences::<Standard, _>("", &data, &profile, 0.0).is_empty());
+}
@@ -0,0 +1,381 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Differential testing against the decision procedure of the Python
+//! implementation of Punkt in NLTK. Requires the `difftest` feature.
+//!
+//! `reference_decisions` is a literal port of
+//! `PunktSentenceTokenizer.debug_decisions`: for each period in a document,
+//! it records the same values NLTK does, and the reason for the decision.
+//! `first_divergence` replays `text_contains_sentbreak` on each period
+//! context, compares it to the decision the sentence tokenizers make, and
+//! returns the first one that differs. Both use the period contexts, and
+//! word tokens of this crate, so only the annotation of the tokens is
+//! compared.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "english")]
+//! # {
+//! # use punkt::TrainingData;
+//! # use punkt::params::Standard;
+//! # use punkt::difftest;
+//! #
+//! let doc = "He works at Acme Inc. in London. It is a big company.";
+//! let data = TrainingData::english();
+//!
+//! for d in difftest::reference_decisions::<Standard, _>(doc, &data) {
+//!   println!("{:?}: {} ({})", d.text, d.break_decision, d.reason);
+//! }
+//!
+//! if let Some(d) = difftest::first_divergence::<Standard, _>(doc, &data) {
+//!   println!("{:?} differs from nltk ({})", d.decision.text, d.decision.reason);
+//! }
+//! # }
+//! ```
+
+use model::Model;
+use ortho::OrthoContext;
+use prelude::TokenizerParameters;
+use token::Token;
+use tokenizer::{annotate_second_pass, PeriodContextTokenizer, WordTokenizer};
+use util::annotate_first_pass;
+
+/// The previous token ends a sentence.
+pub const REASON_DEFAULT_DECISION: &'static str = "default decision";
+/// The tokens are a known collocation.
+pub const REASON_KNOWN_COLLOCATION: &'static str = "known collocation (both words)";
+/// An abbreviation is followed by a word that is usually capitalized.
+pub const REASON_ABBR_WITH_ORTHOGRAPHIC_HEURISTIC: &'static str =
+  "abbreviation + orthographic heuristic";
+/// An abbreviation is followed by a frequent sentence starter.
+pub const REASON_ABBR_WITH_SENTENCE_STARTER: &'static str =
+  "abbreviation + frequent sentence starter";
+/// An initial is followed by a word that is usually lowercase.
+pub const REASON_INITIAL_WITH_ORTHOGRAPHIC_HEURISTIC: &'static str =
+  "initial + orthographic heuristic";
+/// A number is followed by a word that is usually lowercase.
+pub const REASON_NUMBER_WITH_ORTHOGRAPHIC_HEURISTIC: &'static str =
+  "number + orthographic heuristic";
+/// An initial is followed by a capitalized word never seen in lowercase.
+pub const REASON_INITIAL_WITH_SPECIAL_ORTHOGRAPHIC_HEURISTIC: &'static str =
+  "initial + special orthographic heuristic";
+
+/// A decision about a period, with the same fields as the dictionaries
+/// returned by `debug_decisions` in NLTK.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+  /// The byte offset of the period context in the document.
+  pub offset: usize,
+  /// The period context the decision was made on.
+  pub text: String,
+  /// The type of the token with the period.
+  pub type1: String,
+  /// The type of the token after it.
+  pub type2: String,
+  /// Whether the first type, without the period, is an abbreviation.
+  pub type1_in_abbrs: bool,
+  /// Whether the first token is an initial.
+  pub type1_is_initial: bool,
+  /// Whether the second type is a sentence starter.
+  pub type2_is_sent_starter: bool,
+  /// The orthographic heuristic of the second token. `None` is unknown.
+  pub type2_ortho_heuristic: Option<bool>,
+  /// The orthographic context of the second type.
+  pub type2_ortho_contexts: OrthoContext,
+  /// Whether the types are a collocation.
+  pub collocation: bool,
+  /// One of the `REASON_` constants.
+  pub reason: &'static str,
+  /// Whether the period ends a sentence.
+  pub break_decision: bool,
+}
+
+/// A period context that the sentence tokenizers break differently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+  /// The decision of the reference implementation about the first period
+  /// in the context.
+  pub decision: Decision,
+  /// Whether the reference implementation breaks a sentence in the context.
+  pub reference_break: bool,
+  /// Whether the sentence tokenizers break a sentence in the context.
+  pub crate_break: bool,
+}
+
+/// Returns the decisions that NLTK makes about each period context in a
+/// document, in order.
+pub fn reference_decisions<P, D>(doc: &str, data: &D) -> Vec<Decision>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  PeriodContextTokenizer::<P>::new(doc)
+    .filter_map(|(slice, _, _, _, _)| reference_decision::<P, D>(doc, slice, data))
+    .collect()
+}
+
+/// Returns the first decision the sentence tokenizers make differently than
+/// NLTK, if there is one.
+pub fn first_divergence<P, D>(doc: &str, data: &D) -> Option<Divergence>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  for (slice, _, _, _, _) in PeriodContextTokenizer::<P>::new(doc) {
+    let decision = match reference_decision::<P, D>(doc, slice, data) {
+      Some(d) => d,
+      None => continue,
+    };
+    let reference_break = reference_contains_break::<P, D>(slice, data);
+    let crate_break = crate_decision::<P, D>(slice, data);
+
+    if crate_break != reference_break {
+      return Some(Divergence {
+        decision,
+        reference_break,
+        crate_break,
+      });
+    }
+  }
+
+  None
+}
+
+/// Annotates the tokens of a period context the same way as
+/// `SentenceByteOffsetTokenizer`, and checks if any of them breaks a
+/// sentence.
+fn crate_decision<P, D>(slice: &str, data: &D) -> bool
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  let mut prv: Option<Token> = None;
+
+  for mut t in WordTokenizer::<P>::new(slice) {
+    annotate_first_pass::<P, D>(&mut t, data);
+
+    if let Some(mut p) = prv {
+      annotate_second_pass::<P, D>(&mut t, &mut p, data);
+
+      if p.is_sentence_break() {
+        return true;
+      }
+    }
+
+    prv = Some(t);
+  }
+
+  false
+}
+
+fn reference_decision<P, D>(doc: &str, slice: &str, data: &D) -> Option<Decision>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  let mut tokens = WordTokenizer::<P>::new(slice)
+    .map(|mut t| {
+      first_pass::<P, D>(&mut t, data);
+      t
+    })
+    // Skip to the token with the period.
+    .skip_while(|t| {
+      !t.tok()
+        .chars()
+        .last()
+        .is_some_and(|c| P::is_sentence_ending(&c))
+    });
+  let mut tok1 = tokens.next()?;
+  let tok2 = tokens.next()?;
+  let next_typ = type_no_sentperiod(&tok2).to_string();
+
+  let mut decision = Decision {
+    offset: slice.as_ptr() as usize - doc.as_ptr() as usize,
+    text: slice.to_string(),
+    type1: tok1.typ().to_string(),
+    type2: tok2.typ().to_string(),
+    type1_in_abbrs: data.contains_abbrev(tok1.typ_without_period()),
+    type1_is_initial: tok1.is_initial(),
+    type2_is_sent_starter: data.contains_sentence_starter(&next_typ),
+    type2_ortho_heuristic: ortho_heuristic(&tok2, data),
+    type2_ortho_contexts: data.get_orthographic_context(&next_typ),
+    collocation: data.contains_collocation(tok1.typ_without_period(), &next_typ),
+    reason: REASON_DEFAULT_DECISION,
+    break_decision: false,
+  };
+
+  if let Some(reason) = second_pass(&mut tok1, &tok2, data) {
+    decision.reason = reason;
+  }
+
+  decision.break_decision = tok1.is_sentence_break();
+
+  Some(decision)
+}
+
+/// `PunktSentenceTokenizer.text_contains_sentbreak`. All of the tokens are
+/// annotated, and any token, other than the last, can break a sentence.
+fn reference_contains_break<P, D>(slice: &str, data: &D) -> bool
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  let mut tokens: Vec<Token> = WordTokenizer::<P>::new(slice).collect();
+
+  for t in tokens.iter_mut() {
+    first_pass::<P, D>(t, data);
+  }
+
+  for i in 1..tokens.len() {
+    let (prv, cur) = tokens.split_at_mut(i);
+
+    second_pass(&mut prv[i - 1], &cur[0], data);
+  }
+
+  tokens
+    .split_last()
+    .is_some_and(|(_, rest)| rest.iter().any(|t| t.is_sentence_break()))
+}
+
+/// `PunktSentenceTokenizer._first_pass_annotation`.
+fn first_pass<P, D>(tok: &mut Token, data: &D)
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  let s = tok.tok().to_string();
+  let mut chars = s.chars();
+
+  match (chars.next(), chars.next()) {
+    (Some(c), None) if P::is_sentence_ending(&c) => tok.set_is_sentence_break(true),
+    _ if tok.is_ellipsis() || (s.len() > 1 && s.chars().all(|c| c == '.')) => {
+      tok.set_is_ellipsis(true)
+    }
+    _ if tok.has_final_period() && !s.ends_with("..") => {
+      let word = &s[..s.len() - 1];
+
+      if data.contains_abbrev(word) || data.contains_abbrev(word.rsplit('-').next().unwrap()) {
+        tok.set_is_abbrev(true);
+      } else {
+        tok.set_is_sentence_break(true);
+      }
+    }
+    _ => (),
+  }
+}
+
+/// `PunktSentenceTokenizer._second_pass_annotation`. Returns the reason for
+/// a decision, if one was made.
+fn second_pass<D>(tok1: &mut Token, tok2: &Token, data: &D) -> Option<&'static str>
+where
+  D: Model,
+{
+  if !tok1.has_final_period() {
+    return None;
+  }
+
+  let typ = tok1.typ_without_period().to_string();
+  let next_typ = type_no_sentperiod(tok2);
+  let is_initial = tok1.is_initial();
+
+  if data.contains_collocation(&typ, next_typ) {
+    tok1.set_is_sentence_break(false);
+    tok1.set_is_abbrev(true);
+    return Some(REASON_KNOWN_COLLOCATION);
+  }
+
+  if (tok1.is_abbrev() || tok1.is_ellipsis()) && !is_initial {
+    if ortho_heuristic(tok2, data) == Some(true) {
+      tok1.set_is_sentence_break(true);
+      return Some(REASON_ABBR_WITH_ORTHOGRAPHIC_HEURISTIC);
+    }
+
+    if tok2.is_uppercase() && data.contains_sentence_starter(next_typ) {
+      tok1.set_is_sentence_break(true);
+      return Some(REASON_ABBR_WITH_SENTENCE_STARTER);
+    }
+  }
+
+  if is_initial || typ == "##number##" {
+    let is_sent_starter = ortho_heuristic(tok2, data);
+
+    if is_sent_starter == Some(false) {
+      tok1.set_is_sentence_break(false);
+      tok1.set_is_abbrev(true);
+
+      return Some(if is_initial {
+        REASON_INITIAL_WITH_ORTHOGRAPHIC_HEURISTIC
+      } else {
+        REASON_NUMBER_WITH_ORTHOGRAPHIC_HEURISTIC
+      });
+    }
+
+    if is_sent_starter.is_none()
+      && is_initial
+      && tok2.is_uppercase()
+      && !data.get_orthographic_context(next_typ).seen_lower()
+    {
+      tok1.set_is_sentence_break(false);
+      tok1.set_is_abbrev(true);
+      return Some(REASON_INITIAL_WITH_SPECIAL_ORTHOGRAPHIC_HEURISTIC);
+    }
+  }
+
+  None
+}
+
+/// `PunktSentenceTokenizer._ortho_heuristic`. `None` is NLTK's "unknown".
+fn ortho_heuristic<D>(tok: &Token, data: &D) -> Option<bool>
+where
+  D: Model,
+{
+  if !tok.tok().is_empty() && ";:,.!?".contains(tok.tok()) {
+    return Some(false);
+  }
+
+  let ctxt = data.get_orthographic_context(type_no_sentperiod(tok));
+
+  if tok.is_uppercase() && ctxt.seen_lower() && !ctxt.seen_sentence_internal_upper() {
+    Some(true)
+  } else if tok.is_lowercase() && (ctxt.seen_upper() || !ctxt.seen_sentence_initial_lower()) {
+    Some(false)
+  } else {
+    None
+  }
+}
+
+/// `PunktToken.type_no_sentperiod`.
+#[inline]
+fn type_no_sentperiod(tok: &Token) -> &str {
+  tok.typ_without_break_or_period()
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn reference_decisions_test() {
+  use prelude::Standard;
+  use trainer::TrainingData;
+
+  let doc = "I saw Mr. Smith. He was late. The U.S. is big.";
+  let data = TrainingData::english();
+  let decisions = reference_decisions::<Standard, _>(doc, &data);
+
+  assert_eq!(decisions.len(), 4);
+  assert_eq!(decisions[0].type1, "mr.");
+  assert!(decisions[0].type1_in_abbrs);
+  assert!(!decisions[0].break_decision);
+  assert!(decisions[1].break_decision);
+  assert_eq!(decisions[1].reason, REASON_DEFAULT_DECISION);
+  assert_eq!(
+    &doc[decisions[1].offset..][..decisions[1].text.len()],
+    decisions[1].text
+  );
+  assert_eq!(first_divergence::<Standard, _>(doc, &data), None);
+}
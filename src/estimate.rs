@@ -0,0 +1,128 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Estimates of the number of sentences, and tokens in a document, for
+//! when only the counts are needed.
+//!
+//! The estimates skip the work that the tokenizers do to find the exact
+//! boundaries of each sentence. `sentence_count` only looks at the period
+//! contexts of a document, and makes the first pass decisions on them: a
+//! sentence ending ends a sentence, unless it's part of an abbreviation, or
+//! an ellipsis. The orthographic heuristic of the second pass isn't applied,
+//! so initials, and numbers followed by a lowercase word, and abbreviations
+//! followed by a sentence starter can be counted differently. Finding the
+//! period contexts takes most of the time of both, so the estimate is only
+//! somewhat faster than counting the sentences of a
+//! `SentenceByteOffsetTokenizer` (see the `bench_sentence_count_*` benches).
+//!
+//! The estimates only follow the standard parameters. Parameters that change
+//! where sentences end, or split them afterwards (`DIALOGUE_AWARE`,
+//! `BRACKET_PAIRS`, `UNSPACED_SENTENCE_ENDINGS`, `MAX_SENTENCE_LEN`), would
+//! need the work the estimates skip, so count the sentences of a tokenizer
+//! to use them.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "english")]
+//! # {
+//! # use punkt::TrainingData;
+//! # use punkt::estimate;
+//! #
+//! let data = TrainingData::english();
+//! let doc = "Mr. Smith left the U.S. in May. He was tired. Then he slept";
+//!
+//! assert_eq!(estimate::sentence_count(doc, &data), 3);
+//! assert_eq!(estimate::token_count(doc), 13);
+//! # }
+//! ```
+
+use model::Model;
+use prelude::Standard;
+use tokenizer::{PeriodContextTokenizer, WordTokenizer};
+use util::annotate_first_pass;
+
+/// Estimates the number of sentences in a document, with the standard
+/// parameters. Only the period contexts are tokenized into words, and no
+/// sentences are collected.
+pub fn sentence_count<D>(doc: &str, data: &D) -> usize
+where
+  D: Model,
+{
+  let mut count = 0;
+  let mut next = 0;
+
+  for (slice, tok_start, _, _, _) in PeriodContextTokenizer::<Standard>::new(doc) {
+    let mut prv_is_break = false;
+
+    // The context ends a sentence if any token before the last one is a
+    // sentence break.
+    for mut t in WordTokenizer::<Standard>::new(slice) {
+      if prv_is_break {
+        count += 1;
+        next = tok_start;
+        break;
+      }
+
+      annotate_first_pass::<Standard, D>(&mut t, data);
+      prv_is_break = t.is_sentence_break();
+    }
+  }
+
+  // Like the tokenizers, the rest of the document is a sentence, even if it
+  // is only whitespace.
+  if next < doc.len() {
+    count += 1;
+  }
+
+  count
+}
+
+/// Estimates the number of word tokens in a document, by counting runs of
+/// characters that aren't whitespace. Punctuation that the tokenizers split
+/// off of words isn't counted separately, so this is a lower bound on the
+/// number of tokens of a `WordTokenizer`.
+pub fn token_count(doc: &str) -> usize {
+  doc.split_whitespace().count()
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn sentence_count_test() {
+  use tokenizer::SentenceByteOffsetTokenizer;
+  use trainer::TrainingData;
+
+  let data = TrainingData::english();
+  let docs = [
+    "",
+    "   ",
+    "No ending",
+    "One sentence.  ",
+    "Mr. Smith left. He was tired.",
+    "Really? Yes! Ok.",
+    "It costs 5 dollars. Bye",
+  ];
+
+  for doc in docs.iter() {
+    assert_eq!(
+      sentence_count(doc, &data),
+      SentenceByteOffsetTokenizer::<Standard>::new(doc, &data).count(),
+      "{:?}",
+      doc
+    );
+  }
+}
+
+#[test]
+fn token_count_test() {
+  use tokenizer::WordTokenizer;
+
+  assert_eq!(token_count(""), 0);
+  assert_eq!(token_count(" a  b\nc\t"), 3);
+  assert!(token_count("Hi, there.") <= WordTokenizer::<Standard>::new("Hi, there.").count());
+}
@@ -0,0 +1,171 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use prelude::{TokenizerParameters, ORTHO_MAP};
+use segmenter::Segmenter;
+use token::{OrthographyPosition, Token};
+use tokenizer::WordTokenizer;
+use trainer::{Provenance, TrainingData};
+
+/// A correction to a sentence boundary, reported by a user of a `Segmenter`.
+/// Offsets are in bytes, and are usually the end of a sentence span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanCorrection<'a> {
+  /// The text was split at `at`, but the sentence continues past it.
+  Join {
+    /// The text that was segmented.
+    text: &'a str,
+    /// The offset of the boundary that should be removed.
+    at: usize,
+  },
+  /// The text should have been split at `at`.
+  Split {
+    /// The text that was segmented.
+    text: &'a str,
+    /// The offset of the missing boundary.
+    at: usize,
+  },
+}
+
+impl<P> Segmenter<P, TrainingData>
+where
+  P: TokenizerParameters,
+{
+  /// Updates the model from a correction to a sentence boundary, so the same
+  /// mistake isn't made again. Returns false if nothing could be learned from
+  /// the correction, like when there is no word on one side of the boundary.
+  ///
+  /// A boundary that should be removed teaches an abbreviation, or a
+  /// collocation if the word before it is an initial, or a number. A missing
+  /// boundary teaches a sentence starter, and forgets any collocation across
+  /// the boundary. In both cases, the orthographic context of the word after
+  /// the boundary is updated.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use punkt::{Segmenter, SentenceSegmenter, SpanCorrection, TrainingData};
+  /// #
+  /// let doc = "We met the Gen. Manager today.";
+  /// let mut segmenter: Segmenter = Segmenter::new(TrainingData::new());
+  ///
+  /// assert_eq!(segmenter.segment_spans(doc), vec![0..15, 16..30]);
+  /// assert!(segmenter.feedback(SpanCorrection::Join { text: doc, at: 15 }));
+  /// assert_eq!(segmenter.segment_spans(doc), vec![0..30]);
+  /// assert!(segmenter.data().contains_abbrev("gen"));
+  /// ```
+  pub fn feedback(&mut self, correction: SpanCorrection) -> bool {
+    let (text, at, split) = match correction {
+      SpanCorrection::Join { text, at } => (text, at, false),
+      SpanCorrection::Split { text, at } => (text, at, true),
+    };
+
+    let (prv, cur) = match tokens_around::<P>(text, at, self.data()) {
+      Some(pair) => pair,
+      None => return false,
+    };
+
+    let data = self.data_mut();
+    let left = prv.typ_without_period();
+    let right = cur.typ_without_break_or_period();
+    let is_abbrev_like = prv.has_final_period() && !prv.is_ellipsis();
+    let mut changed;
+
+    if split {
+      changed = data.remove_collocation(left, right);
+
+      if is_abbrev_like && cur.is_uppercase() {
+        changed |= data.insert_sentence_starter(right);
+      }
+    } else if is_abbrev_like && !(prv.is_initial() || prv.is_numeric()) {
+      changed = data.insert_abbrev_with_provenance(prv.tok_without_period(), Provenance::Inserted);
+
+      // A sentence starter after an abbreviation always ends a sentence.
+      if cur.is_uppercase() && data.contains_sentence_starter(right) {
+        changed |= data.insert_collocation(left, right);
+      }
+    } else {
+      changed = data.insert_collocation(left, right);
+    }
+
+    let pos = if split {
+      OrthographyPosition::Initial
+    } else {
+      OrthographyPosition::Internal
+    };
+
+    if let Some(&flag) = ORTHO_MAP.get(&(pos.as_byte() | cur.first_case().as_byte())) {
+      let before = data.get_orthographic_context(right);
+
+      data.insert_orthographic_context(right, flag);
+      changed |= data.get_orthographic_context(right) != before;
+    }
+
+    changed
+  }
+}
+
+/// Returns the last token that ends at, or before an offset, and the first
+/// token that starts at, or after it. Both tokens are annotated with the
+/// first pass.
+fn tokens_around<P>(text: &str, at: usize, data: &TrainingData) -> Option<(Token, Token)>
+where
+  P: TokenizerParameters,
+{
+  let mut words = WordTokenizer::<P>::new(text);
+  let mut prv = None;
+
+  while let Some(mut t) = words.next() {
+    let (start, end) = words.last_span();
+
+    ::util::annotate_first_pass::<P, _>(&mut t, data);
+
+    if start >= at {
+      return prv.map(|p| (p, t));
+    }
+
+    if end <= at {
+      prv = Some(t);
+    }
+  }
+
+  None
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn feedback_test() {
+  use segmenter::SentenceSegmenter;
+
+  let doc = "I met Dr. Watson. Then I met Prof. Moriarty.";
+  let mut segmenter: Segmenter = Segmenter::new(TrainingData::english());
+
+  assert_eq!(segmenter.segment_spans(doc), vec![0..17, 18..44]);
+  assert!(segmenter.feedback(SpanCorrection::Split { text: doc, at: 9 }));
+  assert_eq!(segmenter.segment_spans(doc), vec![0..9, 10..17, 18..44]);
+  assert!(segmenter.data().contains_sentence_starter("watson"));
+  assert!(segmenter.data().contains_abbrev("dr"));
+
+  // Nothing is learned without a word on both sides of the boundary.
+  assert!(!segmenter.feedback(SpanCorrection::Join { text: doc, at: 0 }));
+  assert!(!segmenter.feedback(SpanCorrection::Split {
+    text: doc,
+    at: doc.len()
+  }));
+
+  let doc = "I paid 5. Dollars were short.";
+  let mut segmenter: Segmenter = Segmenter::new(TrainingData::new());
+
+  assert_eq!(segmenter.segment_spans(doc), vec![0..9, 10..29]);
+  assert!(segmenter.feedback(SpanCorrection::Join { text: doc, at: 9 }));
+  assert_eq!(segmenter.segment_spans(doc), vec![0..29]);
+  assert!(segmenter
+    .data()
+    .contains_collocation("##number##", "dollars"));
+  assert!(!segmenter.feedback(SpanCorrection::Join { text: doc, at: 9 }));
+}
@@ -0,0 +1,171 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A C interface for segmenting documents. Requires the `ffi` feature.
+//!
+//! Sentences are passed to a callback as they are found, instead of through
+//! an iterator, so no tokenizer state has to be owned, or freed by the
+//! caller. Link this crate into a `cdylib`, or `staticlib` to call these
+//! functions from C, or WebAssembly.
+//!
+//! ```c
+//! void on_sentence(size_t start, size_t end, void *ctx) {
+//!   printf("%.*s\n", (int) (end - start), (const char *) ctx + start);
+//! }
+//!
+//! PunktData *data = punkt_data_from_json(json, json_len);
+//! punkt_segment_cb(doc, doc_len, data, on_sentence, (void *) doc);
+//! punkt_data_free(data);
+//! ```
+
+use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::str::{self, FromStr};
+
+use callback::for_each_sentence;
+use trainer::TrainingData;
+
+/// The training data, as seen by C callers. Only ever used behind a pointer.
+pub type PunktData = TrainingData;
+
+/// Called with the byte offsets of each sentence, and the context pointer
+/// that was passed to `punkt_segment_cb`.
+pub type SentenceCallback = extern "C" fn(start: usize, end: usize, ctx: *mut c_void);
+
+/// Returned when segmenting succeeded.
+pub const PUNKT_OK: c_int = 0;
+
+/// Returned when a pointer was null, or the document wasn't valid UTF-8.
+pub const PUNKT_INVALID_ARGUMENT: c_int = -1;
+
+/// Returned when segmenting panicked, which is a bug in this crate. Some
+/// sentences can already have been passed to the callback.
+pub const PUNKT_PANIC: c_int = -2;
+
+/// Runs `f`, and returns `on_panic` if it panics, since unwinding out of an
+/// `extern "C"` function aborts the process.
+fn catch_panic<T, F>(on_panic: T, f: F) -> T
+where
+  F: FnOnce() -> T,
+{
+  panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(on_panic)
+}
+
+unsafe fn as_str<'a>(ptr: *const u8, len: usize) -> Option<&'a str> {
+  if ptr.is_null() {
+    return None;
+  }
+
+  str::from_utf8(slice::from_raw_parts(ptr, len)).ok()
+}
+
+/// Loads training data from a JSON model. Returns null if the model is not
+/// valid UTF-8, can't be parsed, or loading it panicked. The data has to be
+/// freed with `punkt_data_free`.
+///
+/// # Safety
+///
+/// `json` has to point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn punkt_data_from_json(json: *const u8, len: usize) -> *mut PunktData {
+  catch_panic(ptr::null_mut(), || {
+    match as_str(json, len).map(TrainingData::from_str) {
+      Some(Ok(data)) => Box::into_raw(Box::new(data)),
+      _ => ptr::null_mut(),
+    }
+  })
+}
+
+/// Frees training data that was returned by `punkt_data_from_json`. Does
+/// nothing if `data` is null.
+///
+/// # Safety
+///
+/// `data` has to be null, or returned by `punkt_data_from_json`, and not
+/// freed before.
+#[no_mangle]
+pub unsafe extern "C" fn punkt_data_free(data: *mut PunktData) {
+  if !data.is_null() {
+    drop(Box::from_raw(data));
+  }
+}
+
+/// Segments a document with the standard parameters, and calls `cb` with the
+/// byte offsets of each sentence, in order. Returns `PUNKT_OK`,
+/// `PUNKT_INVALID_ARGUMENT` without calling `cb`, or `PUNKT_PANIC`.
+///
+/// # Safety
+///
+/// `doc` has to point to `len` readable bytes, and `data` has to be returned
+/// by `punkt_data_from_json`, and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn punkt_segment_cb(
+  doc: *const u8,
+  len: usize,
+  data: *const PunktData,
+  cb: SentenceCallback,
+  ctx: *mut c_void,
+) -> c_int {
+  let doc = match as_str(doc, len) {
+    Some(doc) => doc,
+    None => return PUNKT_INVALID_ARGUMENT,
+  };
+
+  if data.is_null() {
+    return PUNKT_INVALID_ARGUMENT;
+  }
+
+  catch_panic(PUNKT_PANIC, || {
+    for_each_sentence(doc, &*data, |r| cb(r.start, r.end, ctx));
+
+    PUNKT_OK
+  })
+}
+
+#[test]
+fn segment_cb_test() {
+  extern "C" fn push(start: usize, end: usize, ctx: *mut c_void) {
+    unsafe { (*(ctx as *mut Vec<(usize, usize)>)).push((start, end)) }
+  }
+
+  let json = r#"{"abbrev_types":["dr"],"collocations":[],"sentence_starters":[],
+    "ortho_context":{}}"#;
+  let doc = "Dr. Jones left. He was tired.";
+  let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+  unsafe {
+    let data = punkt_data_from_json(json.as_ptr(), json.len());
+    assert!(!data.is_null());
+
+    let ctx = &mut ranges as *mut Vec<(usize, usize)> as *mut c_void;
+    assert_eq!(
+      punkt_segment_cb(doc.as_ptr(), doc.len(), data, push, ctx),
+      PUNKT_OK
+    );
+    assert_eq!(
+      punkt_segment_cb(b"\xff".as_ptr(), 1, data, push, ctx),
+      PUNKT_INVALID_ARGUMENT
+    );
+    assert_eq!(
+      punkt_segment_cb(doc.as_ptr(), doc.len(), ptr::null(), push, ctx),
+      PUNKT_INVALID_ARGUMENT
+    );
+
+    punkt_data_free(data);
+    assert!(punkt_data_from_json(b"{".as_ptr(), 1).is_null());
+  }
+
+  assert_eq!(ranges, vec![(0, 15), (16, 29)]);
+  assert_eq!(catch_panic(PUNKT_PANIC, || PUNKT_OK), PUNKT_OK);
+  assert_eq!(
+    catch_panic(PUNKT_PANIC, || -> c_int { panic!("bug") }),
+    PUNKT_PANIC
+  );
+}
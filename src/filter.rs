@@ -0,0 +1,125 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+// Tags that separate the sets sharing a filter.
+pub const ABBREV: u8 = 0;
+pub const COLLOCATION: u8 = 1;
+pub const SENTENCE_STARTER: u8 = 2;
+pub const ORTHOGRAPHIC_CONTEXT: u8 = 3;
+
+// With 3 probes, 16 bits per entry keeps the false positive rate under 1%.
+const BITS_PER_ENTRY: usize = 16;
+const PROBES: u64 = 3;
+
+/// A bloom filter over the keys of several sets. A negative answer is
+/// always correct, so a lookup in the underlying set can be skipped.
+/// Removing keys from a set doesn't require updating the filter, it only
+/// makes false positives more likely.
+#[derive(Clone, Default)]
+pub struct LookupFilter {
+  bits: Vec<u64>,
+  len: usize,
+}
+
+impl LookupFilter {
+  /// Creates a new filter that can hold at least `n` keys.
+  pub fn with_capacity(n: usize) -> LookupFilter {
+    let words = (n.max(64) * BITS_PER_ENTRY / 64).next_power_of_two();
+
+    LookupFilter {
+      bits: vec![0; words],
+      len: 0,
+    }
+  }
+
+  /// Checks if the filter holds as many keys as it was sized for. Inserting
+  /// more keys degrades the false positive rate.
+  #[inline]
+  pub fn is_full(&self) -> bool {
+    self.len >= self.bits.len() * 64 / BITS_PER_ENTRY
+  }
+
+  /// The number of bytes the filter allocated.
+  #[inline]
+  pub fn heap_size(&self) -> usize {
+    self.bits.capacity() * 8
+  }
+
+  /// Adds a key made up of several parts to the filter.
+  #[inline]
+  pub fn insert(&mut self, tag: u8, parts: &[&str]) {
+    let mask = self.bits.len() as u64 * 64 - 1;
+    let (h1, h2) = hash(tag, parts);
+
+    for i in 0..PROBES {
+      let bit = h1.wrapping_add(i.wrapping_mul(h2)) & mask;
+      self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+    }
+
+    self.len += 1;
+  }
+
+  /// Checks if a key might have been added to the filter.
+  #[inline]
+  pub fn may_contain(&self, tag: u8, parts: &[&str]) -> bool {
+    let mask = self.bits.len() as u64 * 64 - 1;
+    let (h1, h2) = hash(tag, parts);
+
+    (0..PROBES).all(|i| {
+      let bit = h1.wrapping_add(i.wrapping_mul(h2)) & mask;
+      self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+    })
+  }
+}
+
+impl fmt::Debug for LookupFilter {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("LookupFilter")
+      .field("bits", &(self.bits.len() * 64))
+      .field("len", &self.len)
+      .finish()
+  }
+}
+
+/// FNV-1a hash of a tag, and the parts of a key. Returns two hashes used to
+/// derive the probe positions.
+#[inline]
+fn hash(tag: u8, parts: &[&str]) -> (u64, u64) {
+  let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+
+  for b in Some(tag)
+    .into_iter()
+    .chain(parts.iter().flat_map(|p| p.bytes().chain(Some(0xff))))
+  {
+    h ^= u64::from(b);
+    h = h.wrapping_mul(0x0000_0100_0000_01b3);
+  }
+
+  (h, (h >> 32 | h << 32) | 1)
+}
+
+#[test]
+fn lookup_filter_has_no_false_negatives() {
+  let mut filter = LookupFilter::with_capacity(100);
+  let keys: Vec<String> = (0..100).map(|i| format!("key{}", i)).collect();
+
+  for k in keys.iter() {
+    filter.insert(ABBREV, &[k]);
+  }
+
+  assert!(keys.iter().all(|k| filter.may_contain(ABBREV, &[k])));
+  assert!(!filter.is_full());
+
+  let false_positives = (100..1100)
+    .filter(|i| filter.may_contain(ABBREV, &[&format!("key{}", i)]))
+    .count();
+
+  assert!(false_positives < 20);
+}
@@ -0,0 +1,231 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::i64;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use score::ScoreMath;
+
+const FRAC_BITS: u32 = 32;
+const ONE: i64 = 1 << FRAC_BITS;
+// ln(2), rounded to the nearest `Fixed`.
+const LN_2: i64 = 2977044472;
+
+/// A signed fixed-point number, with 32 integer bits, and 32 fractional
+/// bits, for computing scores with integer math. Arithmetic saturates,
+/// instead of overflowing, so very large scores stay very large. The
+/// logarithm of 0 is the smallest number, and dividing by 0 gives the
+/// largest number with the sign of the dividend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Fixed(i64);
+
+impl Fixed {
+  /// Clamps a wider intermediate result.
+  #[inline(always)]
+  fn saturate(n: i128) -> Fixed {
+    Fixed(n.max(i64::MIN as i128).min(i64::MAX as i128) as i64)
+  }
+
+  /// Multiplies by 2 to the power of `k`.
+  #[inline]
+  fn shift(self, k: i64) -> Fixed {
+    if k >= 0 {
+      Fixed::saturate((self.0 as i128) << k.min(64))
+    } else {
+      Fixed(self.0 >> (-k).min(63))
+    }
+  }
+}
+
+impl Add for Fixed {
+  type Output = Fixed;
+
+  #[inline(always)]
+  fn add(self, other: Fixed) -> Fixed {
+    Fixed(self.0.saturating_add(other.0))
+  }
+}
+
+impl Sub for Fixed {
+  type Output = Fixed;
+
+  #[inline(always)]
+  fn sub(self, other: Fixed) -> Fixed {
+    Fixed(self.0.saturating_sub(other.0))
+  }
+}
+
+impl Mul for Fixed {
+  type Output = Fixed;
+
+  #[inline(always)]
+  fn mul(self, other: Fixed) -> Fixed {
+    Fixed::saturate((self.0 as i128 * other.0 as i128) >> FRAC_BITS)
+  }
+}
+
+impl Div for Fixed {
+  type Output = Fixed;
+
+  #[inline]
+  fn div(self, other: Fixed) -> Fixed {
+    match (self.0, other.0) {
+      (0, _) => Fixed(0),
+      (n, 0) if n > 0 => Fixed(i64::MAX),
+      (_, 0) => Fixed(i64::MIN),
+      (n, d) => Fixed::saturate(((n as i128) << FRAC_BITS) / d as i128),
+    }
+  }
+}
+
+impl Neg for Fixed {
+  type Output = Fixed;
+
+  #[inline(always)]
+  fn neg(self) -> Fixed {
+    Fixed(0) - self
+  }
+}
+
+impl ScoreMath for Fixed {
+  #[inline(always)]
+  fn ulp(_n: f64) -> f64 {
+    1.0 / ONE as f64
+  }
+
+  #[inline]
+  fn from_f64(n: f64) -> Fixed {
+    // Casts saturate, and NaN becomes 0.
+    Fixed((n * ONE as f64).round() as i64)
+  }
+
+  #[inline]
+  fn from_count(n: usize) -> Fixed {
+    Fixed::saturate((n as i128) << FRAC_BITS)
+  }
+
+  #[inline]
+  fn to_f64(self) -> f64 {
+    self.0 as f64 / ONE as f64
+  }
+
+  /// Splits the number into `m * 2^k`, with `m` in `[1, 2)`, and sums the
+  /// series `ln(m) = 2 * (t + t^3 / 3 + t^5 / 5 + ...)`, with
+  /// `t = (m - 1) / (m + 1)`.
+  fn ln(self) -> Fixed {
+    if self.0 <= 0 {
+      return Fixed(i64::MIN);
+    }
+
+    let k = (63 - self.0.leading_zeros() as i64) - FRAC_BITS as i64;
+    let m = self.shift(-k);
+    let t = (m - Fixed(ONE)) / (m + Fixed(ONE));
+    let t2 = t * t;
+    let mut term = t;
+    let mut sum = Fixed(0);
+    let mut i = 1;
+
+    while term.0 != 0 {
+      sum = sum + Fixed(term.0 / i);
+      term = term * t2;
+      i += 2;
+    }
+
+    Fixed(sum.0 * 2) + Fixed::saturate(k as i128 * LN_2 as i128)
+  }
+
+  /// Splits the number into `k * ln(2) + r`, with `r` in `[0, ln(2))`, and
+  /// sums the Taylor series of `e^r`.
+  fn exp(self) -> Fixed {
+    let k = self.0.div_euclid(LN_2);
+    let r = Fixed(self.0 - k * LN_2);
+    let mut term = Fixed(ONE);
+    let mut sum = Fixed(ONE);
+    let mut i = 1;
+
+    while term.0 != 0 {
+      term = Fixed((term * r).0 / i);
+      sum = sum + term;
+      i += 1;
+    }
+
+    sum.shift(k)
+  }
+
+  fn powi(self, n: i32) -> Fixed {
+    let mut base = self;
+    let mut exp = (n as i64).abs();
+    let mut acc = Fixed(ONE);
+
+    while exp > 0 {
+      if exp & 1 != 0 {
+        acc = acc * base;
+      }
+
+      base = base * base;
+      exp >>= 1;
+    }
+
+    if n < 0 {
+      Fixed(ONE) / acc
+    } else {
+      acc
+    }
+  }
+
+  #[inline]
+  fn quantize(self, step: Fixed) -> i64 {
+    (self.0 as i128 + step.0 as i128 / 2).div_euclid(step.0 as i128) as i64
+  }
+}
+
+#[test]
+fn fixed_score_math_test() {
+  let close = |a: Fixed, b: f64| (a.to_f64() - b).abs() <= 1e-6 * b.abs().max(1.0);
+
+  for &x in [
+    1e-6, 0.01, 0.3, 0.5, 0.99, 1.0, 2.0, 7.88, 30.0, 12345.678, 2e9,
+  ]
+  .iter()
+  {
+    assert!(close(Fixed::from_f64(x).ln(), x.ln()), "ln({})", x);
+  }
+
+  for &x in [-20.0, -9.0, -1.0, -0.5, 0.0, 0.3, 1.0, 5.0, 20.0].iter() {
+    assert!(close(Fixed::from_f64(x).exp(), x.exp()), "exp({})", x);
+  }
+
+  for &(x, n) in [(2.0, 10), (3.0, -2), (0.5, 3), (7.0, -1), (1.0, -1000)].iter() {
+    assert!(
+      close(Fixed::from_f64(x).powi(n), f64::powi(x, n)),
+      "{}^{}",
+      x,
+      n
+    );
+  }
+
+  let (a, b) = (Fixed::from_f64(-12.5), Fixed::from_count(4));
+
+  assert!(close(a * b, -50.0));
+  assert!(close(a / b, -3.125));
+  assert!(close(a + b, -8.5));
+  assert!(close(a - b, -16.5));
+  assert!(close(-a, 12.5));
+
+  // Saturates, instead of overflowing.
+  assert_eq!(Fixed::from_count(1 << 40).0, i64::MAX);
+  assert_eq!(
+    Fixed::from_f64(-1e12) * Fixed::from_f64(1e12),
+    Fixed(i64::MIN)
+  );
+  assert_eq!(Fixed::from_f64(100.0).exp(), Fixed(i64::MAX));
+  assert_eq!(Fixed::from_f64(-100.0).exp(), Fixed(0));
+  assert_eq!(Fixed(0).ln(), Fixed(i64::MIN));
+  assert_eq!(Fixed(ONE) / Fixed(0), Fixed(i64::MAX));
+  assert_eq!(Fixed(0).powi(-1), Fixed(i64::MAX));
+}
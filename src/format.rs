@@ -0,0 +1,808 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::str;
+
+use json::Json;
+
+use metadata::{ModelEntries, ParamsSnapshot};
+use trainer::{Provenance, TrainingData};
+
+/// Magic bytes at the start of a model in the binary format.
+const MAGIC: &'static [u8] = b"PNKT";
+
+/// The version of the model format written by this version of the crate.
+///
+/// Exported models (JSON, and binary) always have the current version.
+/// Models of any version up to the current version can be loaded, and are
+/// loaded as-is; `TrainingData::migrate` upgrades them to the current
+/// version. Models with a newer version are rejected with
+/// `LoadError::UnsupportedVersion`. The version is only incremented when the
+/// meaning, or layout of a model changes.
+///
+/// Version 0 is the JSON format of the pretrained models, which were
+/// converted from NLTK, and have no version field.
+///
+/// Version 2 added metadata, and a checksum to binary models.
+///
+/// Version 3 added the parameters a model was trained with to binary
+/// models.
+///
+/// Version 4 added the right contexts of abbreviations (see
+/// `TrainingData::insert_abbrev_context`). They aren't stored in the
+/// punkt_tab format.
+pub const MODEL_VERSION: u32 = 4;
+
+/// An error encountered while loading `TrainingData`.
+#[derive(Debug)]
+pub enum LoadError {
+  /// The model file could not be read.
+  Io(PathBuf, io::Error),
+  /// The JSON was malformed, or is missing a section.
+  Json(&'static str),
+  /// The binary data was malformed, or truncated.
+  Binary(&'static str),
+  /// A file in a punkt_tab directory was malformed.
+  PunktTab(&'static str),
+  /// The model was written by a newer version of the crate.
+  UnsupportedVersion(u32),
+  /// The content of the model does not match its checksum.
+  ChecksumMismatch,
+}
+
+impl LoadError {
+  /// A short description of the error.
+  pub(crate) fn message(&self) -> &'static str {
+    match *self {
+      LoadError::Io(..) => "failed to read model",
+      LoadError::Json(e) | LoadError::Binary(e) | LoadError::PunktTab(e) => e,
+      LoadError::UnsupportedVersion(_) => "unsupported model version",
+      LoadError::ChecksumMismatch => "model does not match its checksum",
+    }
+  }
+}
+
+impl fmt::Display for LoadError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      LoadError::Io(ref path, ref e) => write!(f, "failed to read {}: {}", path.display(), e),
+      LoadError::Json(e) => write!(f, "invalid json model: {}", e),
+      LoadError::Binary(e) => write!(f, "invalid binary model: {}", e),
+      LoadError::PunktTab(e) => write!(f, "invalid punkt_tab model: {}", e),
+      LoadError::UnsupportedVersion(v) => write!(
+        f,
+        "model version {} is newer than the supported version {}",
+        v, MODEL_VERSION
+      ),
+      LoadError::ChecksumMismatch => write!(f, "model does not match its checksum"),
+    }
+  }
+}
+
+impl Error for LoadError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match *self {
+      LoadError::Io(_, ref e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+impl TrainingData {
+  /// Loads data from a file containing JSON, in the format of the
+  /// pretrained models.
+  pub fn from_json_file<T: AsRef<Path>>(path: T) -> Result<TrainingData, LoadError> {
+    let path = path.as_ref();
+    let s = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+
+    TrainingData::from_json(&s)
+  }
+
+  /// Loads data from a file in the binary format written by `to_binary`.
+  pub fn from_binary_file<T: AsRef<Path>>(path: T) -> Result<TrainingData, LoadError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+
+    TrainingData::from_binary(&bytes)
+  }
+
+  /// Loads data in the binary format written by `to_binary`.
+  pub fn from_binary(bytes: &[u8]) -> Result<TrainingData, LoadError> {
+    let mut r = Reader { bytes };
+    let mut data = TrainingData::new();
+
+    if r.take(MAGIC.len())? != MAGIC {
+      return Err(LoadError::Binary("missing magic bytes"));
+    }
+
+    match r.u32()? {
+      v if v > MODEL_VERSION => return Err(LoadError::UnsupportedVersion(v)),
+      // Binary models were introduced in version 1.
+      0 => return Err(LoadError::Binary("invalid version")),
+      v => data.version = v,
+    }
+
+    let checksum = if data.version >= 2 {
+      if let Some(s) = r.opt_str()? {
+        data.metadata_mut().set_language(s);
+      }
+
+      if let Some(s) = r.opt_str()? {
+        data.metadata_mut().set_corpus(s);
+      }
+
+      if let Some(s) = r.opt_str()? {
+        data.metadata_mut().set_crate_version(s);
+      }
+
+      // The parameters are stored as a JSON object.
+      if data.version >= 3 {
+        if let Some(s) = r.opt_str()? {
+          match s.parse::<Json>().ok().and_then(ParamsSnapshot::from_json) {
+            Some(params) => data.metadata_mut().set_params(params),
+            None => return Err(LoadError::Binary("failed to parse params")),
+          }
+        }
+      }
+
+      Some(r.u64()?)
+    } else {
+      None
+    };
+
+    let mut entries = ModelEntries::default();
+
+    for _ in 0..r.u32()? {
+      entries.abbrevs.push(r.str()?);
+    }
+
+    for _ in 0..r.u32()? {
+      let (left, right) = (r.str()?, r.str()?);
+
+      entries.collocations.push((left, right));
+    }
+
+    for _ in 0..r.u32()? {
+      entries.sentence_starters.push(r.str()?);
+    }
+
+    for _ in 0..r.u32()? {
+      let tok = r.str()?;
+      let ctxt = r.take(1)?[0];
+
+      entries.orthographic_contexts.push((tok, ctxt));
+    }
+
+    if data.version >= 4 {
+      for _ in 0..r.u32()? {
+        let (abbrev, right) = (r.str()?, r.str()?);
+
+        entries.abbrev_contexts.push((abbrev, right));
+      }
+    }
+
+    if !r.bytes.is_empty() {
+      return Err(LoadError::Binary("trailing bytes"));
+    }
+
+    // The checksum is of the entries as they were stored, since inserting
+    // them normalizes some of them.
+    match checksum {
+      Some(h) if h != entries.hash() => return Err(LoadError::ChecksumMismatch),
+      _ => (),
+    }
+
+    data.insert_entries(&entries);
+    Ok(data)
+  }
+
+  /// Serializes the data into a compact binary format, that is faster to
+  /// load than JSON, along with the metadata, and a checksum. Entries are
+  /// written in sorted order, so the output is deterministic.
+  pub fn to_binary(&self) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut abbrevs: Vec<&str> = self.abbrevs().collect();
+    let mut collocations: Vec<(&str, &str)> = self.collocations().collect();
+    let mut sentence_starters: Vec<&str> = self.sentence_starters().collect();
+    let mut orthographic_contexts: Vec<(&str, u8)> = self
+      .orthographic_contexts()
+      .map(|(s, c)| (s, c.bits()))
+      .collect();
+    let mut abbrev_contexts: Vec<(&str, &str)> = self.abbrev_contexts().collect();
+
+    abbrevs.sort();
+    collocations.sort();
+    sentence_starters.sort();
+    orthographic_contexts.sort();
+    abbrev_contexts.sort();
+
+    out.extend_from_slice(MAGIC);
+    write_u32(&mut out, MODEL_VERSION as usize);
+    write_opt_str(&mut out, self.metadata().language());
+    write_opt_str(&mut out, self.metadata().corpus());
+    write_opt_str(&mut out, self.metadata().crate_version());
+    write_opt_str(
+      &mut out,
+      self
+        .metadata()
+        .params()
+        .map(|p| p.to_json().to_string())
+        .as_ref()
+        .map(|s| &s[..]),
+    );
+    out.extend_from_slice(&self.content_hash().to_le_bytes());
+
+    write_u32(&mut out, abbrevs.len());
+    for s in abbrevs {
+      write_str(&mut out, s);
+    }
+
+    write_u32(&mut out, collocations.len());
+    for (l, r) in collocations {
+      write_str(&mut out, l);
+      write_str(&mut out, r);
+    }
+
+    write_u32(&mut out, sentence_starters.len());
+    for s in sentence_starters {
+      write_str(&mut out, s);
+    }
+
+    write_u32(&mut out, orthographic_contexts.len());
+    for (s, c) in orthographic_contexts {
+      write_str(&mut out, s);
+      out.push(c);
+    }
+
+    write_u32(&mut out, abbrev_contexts.len());
+    for (a, r) in abbrev_contexts {
+      write_str(&mut out, a);
+      write_str(&mut out, r);
+    }
+
+    out
+  }
+}
+
+/// The files in a punkt_tab directory.
+const ABBREV_TYPES_FILE: &'static str = "abbrev_types.txt";
+const COLLOCATIONS_FILE: &'static str = "collocations.tab";
+const ORTHO_CONTEXT_FILE: &'static str = "ortho_context.tab";
+const SENT_STARTERS_FILE: &'static str = "sent_starters.txt";
+
+impl TrainingData {
+  /// Loads data from a directory in the punkt_tab format, that newer versions
+  /// of NLTK distribute their models in. The directory contains
+  /// `abbrev_types.txt`, and `sent_starters.txt`, with one entry on each
+  /// line, and `collocations.tab`, and `ortho_context.tab`, with tab
+  /// separated columns.
+  ///
+  /// Like the pretrained models, the loaded data has version 0, and can be
+  /// upgraded with `migrate`.
+  pub fn from_punkt_tab_dir<T: AsRef<Path>>(dir: T) -> Result<TrainingData, LoadError> {
+    let dir = dir.as_ref();
+    let read = |name: &str| {
+      let path = dir.join(name);
+
+      fs::read_to_string(&path).map_err(|e| LoadError::Io(path, e))
+    };
+    let mut data = TrainingData::new();
+
+    data.version = 0;
+
+    for line in read(ABBREV_TYPES_FILE)?.lines().filter(|l| !l.is_empty()) {
+      data.insert_abbrev(line);
+    }
+
+    for line in read(COLLOCATIONS_FILE)?.lines().filter(|l| !l.is_empty()) {
+      match tab_columns(line) {
+        Some((l, r)) => data.insert_collocation(l, r),
+        None => return Err(LoadError::PunktTab("failed to parse collocations")),
+      };
+    }
+
+    for line in read(ORTHO_CONTEXT_FILE)?.lines().filter(|l| !l.is_empty()) {
+      match tab_columns(line).and_then(|(tok, c)| c.parse().ok().map(|c| (tok, c))) {
+        Some((tok, c)) => data.insert_orthographic_context(tok, c),
+        None => return Err(LoadError::PunktTab("failed to parse orthographic context")),
+      };
+    }
+
+    for line in read(SENT_STARTERS_FILE)?.lines().filter(|l| !l.is_empty()) {
+      data.insert_sentence_starter(line);
+    }
+
+    Ok(data)
+  }
+
+  /// Writes the data into a directory in the punkt_tab format, creating the
+  /// directory if it doesn't exist. The format has no version, or metadata,
+  /// so neither is written. Entries are written in sorted order, so the
+  /// output is deterministic.
+  pub fn to_punkt_tab_dir<T: AsRef<Path>>(&self, dir: T) -> io::Result<()> {
+    let dir = dir.as_ref();
+    let mut abbrevs: Vec<&str> = self.abbrevs().collect();
+    let mut collocations: Vec<(&str, &str)> = self.collocations().collect();
+    let mut sentence_starters: Vec<&str> = self.sentence_starters().collect();
+    let mut orthographic_contexts: Vec<(&str, u8)> = self
+      .orthographic_contexts()
+      .map(|(s, c)| (s, c.bits()))
+      .collect();
+
+    abbrevs.sort();
+    collocations.sort();
+    sentence_starters.sort();
+    orthographic_contexts.sort();
+
+    fs::create_dir_all(dir)?;
+
+    write_lines(
+      dir.join(ABBREV_TYPES_FILE),
+      abbrevs.into_iter().map(String::from),
+    )?;
+    write_lines(
+      dir.join(COLLOCATIONS_FILE),
+      collocations
+        .into_iter()
+        .map(|(l, r)| format!("{}\t{}", l, r)),
+    )?;
+    write_lines(
+      dir.join(ORTHO_CONTEXT_FILE),
+      orthographic_contexts
+        .into_iter()
+        .map(|(tok, c)| format!("{}\t{}", tok, c)),
+    )?;
+    write_lines(
+      dir.join(SENT_STARTERS_FILE),
+      sentence_starters.into_iter().map(String::from),
+    )
+  }
+}
+
+impl TrainingData {
+  /// Adds the abbreviations in a plain text list, with one abbreviation on
+  /// each line. Everything after a `#` is a comment, and blank lines are
+  /// skipped. Abbreviations are normalized like the types of tokens, so
+  /// surrounding whitespace, and a final period are removed, and they are
+  /// lowercased (`Dr.` is added as `dr`). Returns the number of
+  /// abbreviations that weren't already known.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use punkt::TrainingData;
+  /// #
+  /// let list = "# Titles\nDr.\nProf.  # professor\n\ne.g.\n";
+  /// let mut data = TrainingData::new();
+  ///
+  /// assert_eq!(data.import_abbrev_list(list.as_bytes()).unwrap(), 3);
+  /// assert!(data.contains_abbrev("prof"));
+  /// assert!(data.contains_abbrev("e.g"));
+  /// ```
+  pub fn import_abbrev_list<R: BufRead>(&mut self, list: R) -> io::Result<usize> {
+    let mut inserted = 0;
+
+    for line in list.lines() {
+      let line = line?;
+      let abbrev = line.split('#').next().unwrap_or("").trim();
+      let abbrev = abbrev.strip_suffix('.').unwrap_or(abbrev);
+
+      if !abbrev.is_empty() && self.insert_abbrev_with_provenance(abbrev, Provenance::Inserted) {
+        inserted += 1;
+      }
+    }
+
+    Ok(inserted)
+  }
+
+  /// Writes the abbreviations as a plain text list, with one abbreviation on
+  /// each line, in sorted order. The list can be read back with
+  /// `import_abbrev_list`.
+  pub fn export_abbrev_list<W: Write>(&self, mut out: W) -> io::Result<()> {
+    let mut abbrevs: Vec<&str> = self.abbrevs().collect();
+
+    abbrevs.sort();
+
+    for abbrev in abbrevs {
+      writeln!(out, "{}", abbrev)?;
+    }
+
+    out.flush()
+  }
+}
+
+/// Writes each line, followed by a newline, to a file.
+fn write_lines<I>(path: PathBuf, lines: I) -> io::Result<()>
+where
+  I: Iterator<Item = String>,
+{
+  let mut out = String::new();
+
+  for line in lines {
+    out.push_str(&line);
+    out.push('\n');
+  }
+
+  fs::write(path, out)
+}
+
+/// Splits a line into exactly two tab separated columns.
+#[inline]
+fn tab_columns(line: &str) -> Option<(&str, &str)> {
+  let mut cols = line.split('\t');
+
+  match (cols.next(), cols.next(), cols.next()) {
+    (Some(l), Some(r), None) => Some((l, r)),
+    _ => None,
+  }
+}
+
+impl<'a> TryFrom<&'a Path> for TrainingData {
+  type Error = LoadError;
+
+  /// Loads data from a file, in either the binary or JSON format, or from a
+  /// punkt_tab directory. The format of a file is detected from its
+  /// contents.
+  fn try_from(path: &'a Path) -> Result<TrainingData, LoadError> {
+    if path.is_dir() {
+      return TrainingData::from_punkt_tab_dir(path);
+    }
+
+    let bytes = fs::read(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+
+    if bytes.starts_with(MAGIC) {
+      TrainingData::from_binary(&bytes)
+    } else {
+      str::from_utf8(&bytes)
+        .map_err(|_| LoadError::Json("file is not valid utf-8"))
+        .and_then(TrainingData::from_json)
+    }
+  }
+}
+
+#[inline]
+pub(crate) fn write_u32(out: &mut Vec<u8>, n: usize) {
+  out.extend_from_slice(&(n as u32).to_le_bytes());
+}
+
+#[inline]
+pub(crate) fn write_str(out: &mut Vec<u8>, s: &str) {
+  write_u32(out, s.len());
+  out.extend_from_slice(s.as_bytes());
+}
+
+#[inline]
+fn write_opt_str(out: &mut Vec<u8>, s: Option<&str>) {
+  match s {
+    Some(s) => {
+      out.push(1);
+      write_str(out, s);
+    }
+    None => out.push(0),
+  }
+}
+
+/// Reads values written by the `write_` functions from the front of a slice
+/// of bytes.
+pub(crate) struct Reader<'a> {
+  pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  #[inline]
+  pub fn take(&mut self, n: usize) -> Result<&'a [u8], LoadError> {
+    if self.bytes.len() < n {
+      return Err(LoadError::Binary("unexpected end of data"));
+    }
+
+    let (head, tail) = self.bytes.split_at(n);
+
+    self.bytes = tail;
+    Ok(head)
+  }
+
+  #[inline]
+  pub fn u32(&mut self) -> Result<u32, LoadError> {
+    let b = self.take(4)?;
+
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+  }
+
+  #[inline]
+  fn u64(&mut self) -> Result<u64, LoadError> {
+    let mut b = [0; 8];
+
+    b.copy_from_slice(self.take(8)?);
+    Ok(u64::from_le_bytes(b))
+  }
+
+  #[inline]
+  fn opt_str(&mut self) -> Result<Option<&'a str>, LoadError> {
+    match self.take(1)?[0] {
+      0 => Ok(None),
+      1 => self.str().map(Some),
+      _ => Err(LoadError::Binary("invalid optional string")),
+    }
+  }
+
+  #[inline]
+  fn str(&mut self) -> Result<&'a str, LoadError> {
+    let len = self.u32()? as usize;
+
+    str::from_utf8(self.take(len)?).map_err(|_| LoadError::Binary("string is not valid utf-8"))
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn binary_format_round_trip_test() {
+  let data = TrainingData::english();
+  let bytes = data.to_binary();
+  let loaded = TrainingData::from_binary(&bytes).unwrap();
+
+  assert_eq!(bytes, loaded.to_binary());
+  assert!(loaded.contains_abbrev("w.va"));
+  assert!(loaded.contains_collocation("##number##", "corrections"));
+  assert!(TrainingData::from_binary(&bytes[..bytes.len() - 1]).is_err());
+  assert!(TrainingData::from_binary(b"{}").is_err());
+}
+
+#[test]
+fn abbrev_context_round_trip_test() {
+  let mut data = TrainingData::new();
+
+  data.insert_abbrev("no");
+
+  let hash = data.content_hash();
+
+  assert!(data.insert_abbrev_context("No", "5"));
+  assert!(data.insert_abbrev_context("no", "Pets"));
+  assert!(!data.insert_abbrev_context("no", "12"));
+  assert!(data.contains_abbrev_context("no", "##number##"));
+  assert!(data.allows_abbrev_context("no", "pets"));
+  assert!(!data.allows_abbrev_context("no", "then"));
+  assert!(data.allows_abbrev_context("mr", "then"));
+  assert!(data.content_hash() != hash);
+
+  let json = TrainingData::from_json(&data.to_json()).unwrap();
+  let binary = TrainingData::from_binary(&data.to_binary()).unwrap();
+
+  assert_eq!(json, data);
+  assert_eq!(binary, data);
+  assert_eq!(binary.abbrev_contexts().count(), 2);
+  assert!(!TrainingData::new().to_json().contains("abbrev_contexts"));
+
+  assert!(data.remove_abbrev_context("no", "pets"));
+  assert!(data.remove_abbrev_context("no", "7"));
+  assert!(!data.remove_abbrev_context("no", "7"));
+  assert!(data.allows_abbrev_context("no", "then"));
+  assert_eq!(data.content_hash(), hash);
+}
+
+#[test]
+fn model_params_round_trip_test() {
+  use prelude::Standard;
+  use trainer::Trainer;
+
+  let mut data = TrainingData::new();
+
+  Trainer::<Standard>::new().train("Mr. Smith went home. He slept.", &mut data);
+
+  assert_eq!(
+    data.metadata().params(),
+    Some(&ParamsSnapshot::of::<Standard>())
+  );
+  assert_eq!(
+    TrainingData::from_binary(&data.to_binary())
+      .unwrap()
+      .metadata(),
+    data.metadata()
+  );
+  assert_eq!(
+    TrainingData::from_json(&data.to_json()).unwrap().metadata(),
+    data.metadata()
+  );
+}
+
+#[test]
+fn load_from_path_test() {
+  let path = Path::new("data/english/english.json");
+  let data = TrainingData::try_from(path).unwrap();
+
+  assert!(data.contains_abbrev("w.va"));
+  assert!(TrainingData::from_json_file(path).is_ok());
+
+  match TrainingData::from_json_file("data/missing.json") {
+    Err(LoadError::Io(ref p, _)) => assert_eq!(p, Path::new("data/missing.json")),
+    _ => panic!("expected an io error"),
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn punkt_tab_round_trip_test() {
+  use std::{env, process};
+
+  let dir = env::temp_dir().join(format!("punkt_tab_test_{}", process::id()));
+  let data = TrainingData::english();
+
+  data.to_punkt_tab_dir(&dir).unwrap();
+
+  let loaded = TrainingData::try_from(dir.as_path()).unwrap();
+
+  assert_eq!(loaded.version(), 0);
+  assert_eq!(loaded.content_hash(), data.content_hash());
+  assert!(loaded.contains_collocation("##number##", "corrections"));
+
+  fs::write(dir.join(COLLOCATIONS_FILE), "a\tb\tc\n").unwrap();
+
+  match TrainingData::from_punkt_tab_dir(&dir) {
+    Err(LoadError::PunktTab(_)) => (),
+    _ => panic!("expected a punkt_tab error"),
+  }
+
+  fs::remove_dir_all(&dir).unwrap();
+
+  match TrainingData::from_punkt_tab_dir(&dir) {
+    Err(LoadError::Io(..)) => (),
+    _ => panic!("expected an io error"),
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn abbrev_list_round_trip_test() {
+  let data = TrainingData::english();
+  let mut out = Vec::new();
+
+  data.export_abbrev_list(&mut out).unwrap();
+
+  let mut loaded = TrainingData::new();
+  let count = loaded.import_abbrev_list(&out[..]).unwrap();
+
+  // The pretrained model has an abbreviation with surrounding whitespace,
+  // that is trimmed when it is imported.
+  assert_eq!(count, data.abbrevs().count());
+  assert!(loaded
+    .abbrevs()
+    .filter(|a| !a.contains(' '))
+    .all(|a| data.contains_abbrev(a)));
+
+  let list = "  # comment\nMr.\nmr\n\t U.S.A. # country\n#\n.\n";
+
+  assert_eq!(loaded.import_abbrev_list(list.as_bytes()).unwrap(), 0);
+  assert_eq!(
+    TrainingData::new()
+      .import_abbrev_list(list.as_bytes())
+      .unwrap(),
+    2
+  );
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn model_version_test() {
+  use std::str::FromStr;
+
+  let mut data = TrainingData::english();
+
+  assert_eq!(data.version(), 0);
+  assert!(data.migrate());
+  assert!(!data.migrate());
+  assert_eq!(data.version(), MODEL_VERSION);
+  assert!(data.contains_collocation("##number##", "corrections"));
+
+  let json = data.to_json();
+  let loaded = TrainingData::from_str(&json).unwrap();
+
+  assert_eq!(loaded.version(), MODEL_VERSION);
+  assert_eq!(loaded.to_json(), json);
+  assert_eq!(
+    TrainingData::from_binary(&loaded.to_binary())
+      .unwrap()
+      .version(),
+    MODEL_VERSION
+  );
+
+  // The version is the last key. "version" is also a word in the
+  // orthographic context.
+  let version = format!("\"version\":{}", MODEL_VERSION);
+  let i = json.rfind(&version[..]).unwrap();
+  let newer = format!("{}\"version\":99{}", &json[..i], &json[i + version.len()..]);
+
+  match TrainingData::from_json(&newer) {
+    Err(LoadError::UnsupportedVersion(99)) => (),
+    _ => panic!("expected an unsupported version error"),
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn model_checksum_test() {
+  let mut data = TrainingData::english();
+
+  data.metadata_mut().set_language("en");
+
+  let json = data.to_json();
+  let mut bytes = data.to_binary();
+
+  assert_eq!(
+    TrainingData::from_binary(&bytes).unwrap().metadata(),
+    data.metadata()
+  );
+  assert_eq!(
+    TrainingData::from_json(&json).unwrap().metadata(),
+    data.metadata()
+  );
+
+  // Corrupt the last orthographic context, before the count of the
+  // abbreviation contexts.
+  let last = bytes.len() - 5;
+
+  bytes[last] ^= 0b10;
+
+  match TrainingData::from_binary(&bytes) {
+    Err(LoadError::ChecksumMismatch) => (),
+    _ => panic!("expected a checksum mismatch"),
+  }
+
+  match TrainingData::from_json(&json.replacen("\"w.va\"", "\"w.vb\"", 1)) {
+    Err(LoadError::ChecksumMismatch) => (),
+    _ => panic!("expected a checksum mismatch"),
+  }
+}
+
+#[test]
+fn model_checksum_as_stored_test() {
+  // Models written by other tools can store entries that the loaders
+  // normalize, like uppercase abbreviations. The checksum is of the entries
+  // as they were stored.
+  let mut entries = ModelEntries::default();
+
+  entries.abbrevs.push("Dr");
+  entries.abbrev_contexts.push(("No", "5"));
+
+  let checksum = entries.hash();
+  let json = format!(
+    "{{\"version\": 4, \"metadata\": {{\"checksum\": \"{:016x}\"}}, \"abbrev_types\": [\"Dr\"], \
+     \"sentence_starters\": [], \"collocations\": [], \"ortho_context\": {{}}, \
+     \"abbrev_contexts\": [[\"No\", \"5\"]]}}",
+    checksum
+  );
+  let mut bytes = Vec::new();
+
+  bytes.extend_from_slice(MAGIC);
+  write_u32(&mut bytes, MODEL_VERSION as usize);
+  // No language, corpus, crate version, or params.
+  bytes.extend_from_slice(&[0, 0, 0, 0]);
+  bytes.extend_from_slice(&checksum.to_le_bytes());
+  write_u32(&mut bytes, 1);
+  write_str(&mut bytes, "Dr");
+  write_u32(&mut bytes, 0);
+  write_u32(&mut bytes, 0);
+  write_u32(&mut bytes, 0);
+  write_u32(&mut bytes, 1);
+  write_str(&mut bytes, "No");
+  write_str(&mut bytes, "5");
+
+  for data in vec![
+    TrainingData::from_json(&json),
+    TrainingData::from_binary(&bytes),
+  ] {
+    let data = data.unwrap();
+
+    assert!(data.contains_abbrev("dr"));
+    assert!(data.contains_abbrev_context("no", "##number##"));
+  }
+}
@@ -0,0 +1,344 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use fst::{Map, Set, Streamer};
+
+use memory::MemoryReport;
+use metadata::ParamsSnapshot;
+use model::Model;
+use ortho::OrthoContext;
+use trainer::{Provenance, TrainingData};
+
+/// Training data that can no longer be trained, with its abbreviations, their
+/// provenance, and sentence starters stored as finite state transducers,
+/// instead of hash maps, and sets. Created with `TrainingData::freeze`.
+/// Requires the `fst` feature.
+///
+/// A transducer shares the common prefixes, and suffixes of its strings, so
+/// large sets take a fraction of the memory of a `HashSet<String>`, while a
+/// lookup only takes time in the length of the token. The other parts of
+/// the model are kept as they are. The lookups are the same as those of
+/// `TrainingData`, and the data segments the same.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{SentenceTokenizer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let data = TrainingData::english().freeze();
+/// let doc = "Mr. Smith left. He was tired.";
+///
+/// assert!(data.contains_abbrev("mr"));
+/// assert_eq!(SentenceTokenizer::<Standard, _>::new(doc, &data).count(), 2);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FrozenTrainingData {
+  abbrevs: Set<Vec<u8>>,
+  abbrev_provenance: Map<Vec<u8>>,
+  sentence_starters: Set<Vec<u8>>,
+  data: TrainingData,
+}
+
+impl FrozenTrainingData {
+  /// Check if a token is considered to be an abbreviation.
+  #[inline]
+  pub fn contains_abbrev(&self, tok: &str) -> bool {
+    self.abbrevs.contains(tok)
+  }
+
+  /// Returns why a token is considered to be an abbreviation, or `None` if
+  /// it isn't one. See `TrainingData::abbrev_provenance`.
+  #[inline]
+  pub fn abbrev_provenance(&self, tok: &str) -> Option<Provenance> {
+    if self.contains_abbrev(tok) {
+      Some(
+        self
+          .abbrev_provenance
+          .get(tok)
+          .map(decode_provenance)
+          .unwrap_or(Provenance::Seeded),
+      )
+    } else {
+      None
+    }
+  }
+
+  /// Checks if a pair of words are commonly known to appear together.
+  #[inline]
+  pub fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    self.data.contains_collocation(left, right)
+  }
+
+  /// Check if a token is considered to be a token that commonly starts a
+  /// sentence.
+  #[inline]
+  pub fn contains_sentence_starter(&self, tok: &str) -> bool {
+    self.sentence_starters.contains(tok)
+  }
+
+  /// Gets the orthographic context for a token. Returns an empty context if
+  /// the token was not yet encountered.
+  #[inline]
+  pub fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    self.data.get_orthographic_context(tok)
+  }
+
+  /// Checks if an abbreviation can be followed by a word without ending the
+  /// sentence. True for abbreviations without right contexts.
+  #[inline]
+  pub fn allows_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    self.data.allows_abbrev_context(abbrev, right)
+  }
+
+  /// Estimates the heap memory used by each part of the data. The sets that
+  /// were frozen are counted by the size of their transducers.
+  pub fn memory_footprint(&self) -> MemoryReport {
+    let mut report = self.data.memory_footprint();
+
+    report.abbrevs += self.abbrevs.as_fst().as_bytes().len();
+    report.abbrevs += self.abbrev_provenance.as_fst().as_bytes().len();
+    report.sentence_starters += self.sentence_starters.as_fst().as_bytes().len();
+    report
+  }
+
+  /// Converts the data back into `TrainingData`, so it can be trained, or
+  /// changed again.
+  pub fn thaw(self) -> TrainingData {
+    let mut data = self.data;
+    let abbrevs = thaw_set(&self.abbrevs);
+    let sentence_starters = thaw_set(&self.sentence_starters);
+
+    data.restore_abbrevs_and_starters(abbrevs, sentence_starters);
+    data.restore_abbrev_provenance(thaw_provenance(&self.abbrev_provenance));
+    data
+  }
+}
+
+/// Summarizes the sizes of the model, instead of listing its contents.
+impl fmt::Debug for FrozenTrainingData {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("FrozenTrainingData")
+      .field("abbrevs", &self.abbrevs.len())
+      .field("abbrev_provenance", &self.abbrev_provenance.len())
+      .field("sentence_starters", &self.sentence_starters.len())
+      .field("data", &self.data)
+      .finish()
+  }
+}
+
+impl Model for FrozenTrainingData {
+  #[inline(always)]
+  fn contains_abbrev(&self, tok: &str) -> bool {
+    FrozenTrainingData::contains_abbrev(self, tok)
+  }
+
+  #[inline(always)]
+  fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    FrozenTrainingData::contains_collocation(self, left, right)
+  }
+
+  #[inline(always)]
+  fn contains_sentence_starter(&self, tok: &str) -> bool {
+    FrozenTrainingData::contains_sentence_starter(self, tok)
+  }
+
+  #[inline(always)]
+  fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    FrozenTrainingData::get_orthographic_context(self, tok)
+  }
+
+  #[inline(always)]
+  fn allows_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    FrozenTrainingData::allows_abbrev_context(self, abbrev, right)
+  }
+
+  #[inline(always)]
+  fn params(&self) -> Option<&ParamsSnapshot> {
+    self.data.metadata().params()
+  }
+}
+
+/// Builds a transducer from a set of strings.
+fn freeze_set(set: HashSet<String>) -> Set<Vec<u8>> {
+  let mut keys: Vec<String> = set.into_iter().collect();
+
+  keys.sort();
+
+  // The keys are sorted, and unique, and are written to memory, so building
+  // the transducer can't fail.
+  Set::from_iter(keys).expect("keys are sorted, and unique")
+}
+
+/// The value of an abbreviation that was inserted by a user. The values of
+/// scores are their bits, and these two are NaNs that a score never has,
+/// because NaN scores are stored as `f64::NAN`.
+const INSERTED: u64 = ::std::u64::MAX;
+
+/// The value of an abbreviation that was learned without a score.
+const TRAINED_WITHOUT_SCORE: u64 = ::std::u64::MAX - 1;
+
+/// Encodes a provenance as the value of a transducer.
+fn encode_provenance(provenance: Provenance) -> u64 {
+  match provenance {
+    Provenance::Trained { score: Some(score) } if score.is_nan() => ::std::f64::NAN.to_bits(),
+    Provenance::Trained { score: Some(score) } => score.to_bits(),
+    Provenance::Trained { score: None } => TRAINED_WITHOUT_SCORE,
+    Provenance::Inserted => INSERTED,
+    // The provenance of seeded abbreviations is never stored.
+    Provenance::Seeded => unreachable!("seeded abbreviations have no provenance entry"),
+  }
+}
+
+/// Decodes the value of a transducer into a provenance.
+fn decode_provenance(value: u64) -> Provenance {
+  match value {
+    INSERTED => Provenance::Inserted,
+    TRAINED_WITHOUT_SCORE => Provenance::Trained { score: None },
+    bits => Provenance::Trained {
+      score: Some(f64::from_bits(bits)),
+    },
+  }
+}
+
+/// Builds a transducer from the provenance of abbreviations.
+fn freeze_provenance(map: HashMap<String, Provenance>) -> Map<Vec<u8>> {
+  let mut entries: Vec<(String, u64)> = map
+    .into_iter()
+    .map(|(k, p)| (k, encode_provenance(p)))
+    .collect();
+
+  entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+  // Same as `freeze_set`.
+  Map::from_iter(entries).expect("keys are sorted, and unique")
+}
+
+/// Collects the entries of a transducer into a provenance map.
+fn thaw_provenance(map: &Map<Vec<u8>>) -> HashMap<String, Provenance> {
+  let mut entries = HashMap::with_capacity(map.len());
+  let mut stream = map.stream();
+
+  while let Some((key, value)) = stream.next() {
+    entries.insert(
+      String::from_utf8_lossy(key).into_owned(),
+      decode_provenance(value),
+    );
+  }
+
+  entries
+}
+
+/// Collects the strings of a transducer into a set.
+fn thaw_set(set: &Set<Vec<u8>>) -> HashSet<String> {
+  let mut keys = HashSet::with_capacity(set.len());
+  let mut stream = set.stream();
+
+  while let Some(key) = stream.next() {
+    keys.insert(String::from_utf8_lossy(key).into_owned());
+  }
+
+  keys
+}
+
+impl TrainingData {
+  /// Freezes the data, storing its abbreviations, their provenance, and
+  /// sentence starters in finite state transducers. Meant for models with
+  /// very large sets, that won't be trained further. See
+  /// `FrozenTrainingData`.
+  pub fn freeze(mut self) -> FrozenTrainingData {
+    let (abbrevs, sentence_starters) = self.take_abbrevs_and_starters();
+    let abbrev_provenance = self.take_abbrev_provenance();
+
+    FrozenTrainingData {
+      abbrevs: freeze_set(abbrevs),
+      abbrev_provenance: freeze_provenance(abbrev_provenance),
+      sentence_starters: freeze_set(sentence_starters),
+      data: self,
+    }
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn frozen_training_data_test() {
+  use prelude::Standard;
+  use tokenizer::SentenceByteOffsetTokenizer;
+
+  let data = TrainingData::english();
+  let frozen = data.clone().freeze();
+
+  for tok in data.abbrevs() {
+    assert!(frozen.contains_abbrev(tok));
+  }
+
+  for tok in data.sentence_starters() {
+    assert!(frozen.contains_sentence_starter(tok));
+  }
+
+  assert!(!frozen.contains_abbrev("zzzz"));
+  assert!(!frozen.contains_sentence_starter("zzzz"));
+
+  let before = data.memory_footprint();
+  let after = frozen.memory_footprint();
+
+  assert!(after.abbrevs < before.abbrevs);
+  assert!(after.sentence_starters < before.sentence_starters);
+  assert_eq!(after.collocations, before.collocations);
+
+  // The frozen data segments the same as the data it was frozen from.
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let expected: Vec<_> = SentenceByteOffsetTokenizer::<Standard>::new(doc, &data).collect();
+  let actual: Vec<_> = SentenceByteOffsetTokenizer::<Standard, _>::new(doc, &frozen).collect();
+
+  assert_eq!(actual, expected);
+  assert_eq!(frozen.thaw(), data);
+  assert_eq!(TrainingData::new().freeze().thaw(), TrainingData::new());
+}
+
+#[test]
+fn frozen_training_data_provenance_test() {
+  use prelude::Standard;
+  use trainer::Trainer;
+
+  let doc = "The U.S. economy grew. The U.S. team won. It rained. See Fig. 3.";
+  let mut data = TrainingData::new();
+
+  Trainer::<Standard>::new().train(doc, &mut data);
+  data.import_abbrev_list("acct.".as_bytes()).unwrap();
+
+  assert!(data.abbrevs().count() > 1);
+
+  let frozen = data.clone().freeze();
+
+  for tok in data.abbrevs() {
+    assert_eq!(frozen.abbrev_provenance(tok), data.abbrev_provenance(tok));
+  }
+
+  assert_eq!(frozen.abbrev_provenance("acct"), Some(Provenance::Inserted));
+  assert!(match frozen.abbrev_provenance("u.s") {
+    Some(Provenance::Trained { score: Some(_) }) => true,
+    _ => false,
+  });
+  assert_eq!(frozen.abbrev_provenance("economy"), None);
+
+  // Nothing is left behind in the hash maps of the frozen data.
+  assert_eq!(frozen.data.memory_footprint().abbrevs, 0);
+
+  let thawed = frozen.thaw();
+
+  for tok in data.abbrevs() {
+    assert_eq!(thawed.abbrev_provenance(tok), data.abbrev_provenance(tok));
+  }
+}
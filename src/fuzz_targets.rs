@@ -0,0 +1,239 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Entry points for fuzzing the tokenizers, the trainer, and the model
+//! loaders. Requires the `fuzzing` feature.
+//!
+//! Each target takes arbitrary bytes, is deterministic, and checks the
+//! invariants of the APIs it runs, returning a `Violation` if one doesn't
+//! hold. Invalid UTF-8 is decoded lossily, so every input reaches the
+//! tokenizers. The targets are meant to be called from `cargo-fuzz`
+//! harnesses in other crates:
+//!
+//! ```ignore
+//! #![no_main]
+//! #[macro_use] extern crate libfuzzer_sys;
+//! extern crate punkt;
+//!
+//! fuzz_target!(|input: &[u8]| {
+//!   punkt::fuzz_targets::segment(input).unwrap();
+//! });
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use model::Model;
+use prelude::{
+  Aggressive, Biomedical, Legal, Precise, Rtl, Standard, TokenizerParameters, TrainerParameters,
+  Web,
+};
+use tokenizer::{SentenceByteOffsetTokenizer, SentenceTokenizer, WordTokenizer};
+use trainer::{Trainer, TrainingData};
+
+/// A document that loaded models are checked against.
+const SAMPLE_DOC: &'static str = "Mr. Smith arrived at 5 p.m. on Jan. 3. He left... Did he? Yes!";
+
+/// An invariant that didn't hold for an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation(pub &'static str);
+
+impl fmt::Display for Violation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "invariant violated: {}", self.0)
+  }
+}
+
+impl Error for Violation {}
+
+/// Segments the input with empty training data, and the standard
+/// parameters.
+pub fn segment(input: &[u8]) -> Result<(), Violation> {
+  let doc = String::from_utf8_lossy(input);
+
+  segment_with::<Standard, _>(&doc, &TrainingData::new())
+}
+
+/// Segments the input with every preset, with empty training data, and with
+/// training data trained on the input.
+pub fn segment_presets(input: &[u8]) -> Result<(), Violation> {
+  let doc = String::from_utf8_lossy(input);
+  let mut trained = TrainingData::new();
+
+  Trainer::<Standard>::new().train(&doc, &mut trained);
+
+  for data in [TrainingData::new(), trained].iter() {
+    segment_with::<Standard, _>(&doc, data)?;
+    segment_with::<Precise, _>(&doc, data)?;
+    segment_with::<Aggressive, _>(&doc, data)?;
+    segment_with::<Legal, _>(&doc, data)?;
+    segment_with::<Biomedical, _>(&doc, data)?;
+    segment_with::<Web, _>(&doc, data)?;
+    segment_with::<Rtl, _>(&doc, data)?;
+  }
+
+  Ok(())
+}
+
+/// Segments a document with any parameters, and model. The sentences have to
+/// be ordered, not overlap, and be slices of the document. Every word of the
+/// document is tokenized as well.
+pub fn segment_with<P, D>(doc: &str, data: &D) -> Result<(), Violation>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  let mut last = 0;
+  let mut sents = SentenceTokenizer::<P, D>::new(doc, data);
+
+  for (start, end) in SentenceByteOffsetTokenizer::<P, D>::new(doc, data) {
+    if start > end || end > doc.len() {
+      return Err(Violation("sentence is out of bounds"));
+    }
+
+    if start < last {
+      return Err(Violation("sentences overlap"));
+    }
+
+    if !doc.is_char_boundary(start) || !doc.is_char_boundary(end) {
+      return Err(Violation("sentence is not at character boundaries"));
+    }
+
+    if sents.next() != Some(&doc[start..end]) {
+      return Err(Violation("sentence slices differ from byte offsets"));
+    }
+
+    last = end;
+  }
+
+  if sents.next().is_some() {
+    return Err(Violation("sentence slices differ from byte offsets"));
+  }
+
+  let mut words = WordTokenizer::<P>::new(doc);
+
+  while let Some(_) = words.next() {
+    let (start, end) = words.last_span();
+
+    if start > end || end > doc.len() {
+      return Err(Violation("word is out of bounds"));
+    }
+  }
+
+  Ok(())
+}
+
+/// Trains new training data on the input, with the standard parameters, and
+/// segments it with the result. The trained model has to survive a round
+/// trip through the binary format.
+pub fn train(input: &[u8]) -> Result<(), Violation> {
+  let doc = String::from_utf8_lossy(input);
+
+  train_with::<Standard>(&doc)
+}
+
+/// Trains new training data on a document with any parameters, and segments
+/// it with the result.
+pub fn train_with<P>(doc: &str) -> Result<(), Violation>
+where
+  P: TokenizerParameters + TrainerParameters,
+{
+  let mut data = TrainingData::new();
+
+  Trainer::<P>::new().train(doc, &mut data);
+  segment_with::<P, _>(doc, &data)?;
+
+  match TrainingData::from_binary(&data.to_binary()) {
+    Ok(_) => Ok(()),
+    Err(_) => Err(Violation(
+      "trained model does not load from the binary format",
+    )),
+  }
+}
+
+/// Loads the input as a model in the binary format, and in the JSON format.
+/// Malformed models have to be rejected with an error. Models that load are
+/// written back, loaded again, and used to segment a sample document.
+pub fn load_model(input: &[u8]) -> Result<(), Violation> {
+  if let Ok(data) = TrainingData::from_binary(input) {
+    check_model(&data)?;
+  }
+
+  if let Ok(s) = str::from_utf8(input) {
+    if let Ok(data) = TrainingData::from_json(s) {
+      check_model(&data)?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Checks a model that was loaded from a fuzzed input.
+fn check_model(data: &TrainingData) -> Result<(), Violation> {
+  if TrainingData::from_binary(&data.to_binary()).is_err() {
+    return Err(Violation(
+      "loaded model does not load from the binary format",
+    ));
+  }
+
+  if TrainingData::from_json(&data.to_json()).is_err() {
+    return Err(Violation("loaded model does not load from the json format"));
+  }
+
+  segment_with::<Standard, _>(SAMPLE_DOC, data)
+}
+
+#[test]
+fn fuzz_targets_test() {
+  let inputs: &[&[u8]] = &[
+    b"",
+    b".",
+    b"Hi. There.",
+    b"\xff\xfe. A\xc3",
+    b"PNKT\x04\x00\x00\x00",
+    b"{\"version\": 4}",
+    "\u{e9}.\u{202f}!\n\n?".as_bytes(),
+  ];
+
+  for input in inputs {
+    assert_eq!(segment(input), Ok(()));
+    assert_eq!(segment_presets(input), Ok(()));
+    assert_eq!(train(input), Ok(()));
+    assert_eq!(load_model(input), Ok(()));
+  }
+
+  assert_eq!(load_model(&TrainingData::new().to_binary()), Ok(()));
+  assert_eq!(load_model(TrainingData::new().to_json().as_bytes()), Ok(()));
+}
+
+#[test]
+fn segment_presets_random_test() {
+  // Characters that the presets treat specially, and some that are more
+  // than one byte long.
+  let alphabet = [
+    ".", " ", "a", "A", "1", ")", "(", "\"", "'", "\n", "!", "?", "-", ";", "\u{e9}", "\u{200f}",
+    "\u{3002}", "\u{6d4}", "\u{bb}", "\u{2026}", "Mr.", "e.g.",
+  ];
+  let mut state = 0x2545_f491_4f6c_dd1du64;
+  let mut next = || {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state as usize
+  };
+
+  for _ in 0..2000 {
+    let len = next() % 48;
+    let doc: String = (0..len)
+      .map(|_| alphabet[next() % alphabet.len()])
+      .collect();
+
+    assert_eq!(segment_presets(doc.as_bytes()), Ok(()), "{:?}", doc);
+  }
+}
@@ -0,0 +1,446 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+/// Maximum depth of nested arrays, and objects. Models are only a few levels
+/// deep, so anything deeper is rejected, instead of overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+/// A JSON value. Objects are sorted by key, so they are written in the same
+/// order every time.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+  I64(i64),
+  U64(u64),
+  F64(f64),
+  String(String),
+  Boolean(bool),
+  Array(Vec<Json>),
+  Object(BTreeMap<String, Json>),
+  Null,
+}
+
+impl Json {
+  /// Returns the value if it's a non-negative integer.
+  #[inline]
+  pub(crate) fn as_u64(&self) -> Option<u64> {
+    match *self {
+      Json::I64(n) if n >= 0 => Some(n as u64),
+      Json::U64(n) => Some(n),
+      _ => None,
+    }
+  }
+
+  /// Returns the value if it's a boolean.
+  #[inline]
+  pub(crate) fn as_boolean(&self) -> Option<bool> {
+    match *self {
+      Json::Boolean(b) => Some(b),
+      _ => None,
+    }
+  }
+}
+
+impl FromStr for Json {
+  type Err = &'static str;
+
+  /// Parses a single JSON value, that can be surrounded by whitespace.
+  fn from_str(s: &str) -> Result<Json, &'static str> {
+    let mut parser = Parser {
+      src: s.as_bytes(),
+      pos: 0,
+    };
+    let value = parser.value(0)?;
+
+    parser.whitespace();
+
+    if parser.pos == parser.src.len() {
+      Ok(value)
+    } else {
+      Err("trailing characters")
+    }
+  }
+}
+
+impl fmt::Display for Json {
+  /// Writes the value as compact JSON.
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Json::I64(n) => write!(f, "{}", n),
+      Json::U64(n) => write!(f, "{}", n),
+      Json::F64(n) if n.is_finite() => write!(f, "{:?}", n),
+      Json::F64(_) | Json::Null => f.write_str("null"),
+      Json::String(ref s) => write_str(f, s),
+      Json::Boolean(b) => write!(f, "{}", b),
+      Json::Array(ref arr) => {
+        f.write_char('[')?;
+
+        for (i, v) in arr.iter().enumerate() {
+          if i > 0 {
+            f.write_char(',')?;
+          }
+
+          write!(f, "{}", v)?;
+        }
+
+        f.write_char(']')
+      }
+      Json::Object(ref obj) => {
+        f.write_char('{')?;
+
+        for (i, (k, v)) in obj.iter().enumerate() {
+          if i > 0 {
+            f.write_char(',')?;
+          }
+
+          write_str(f, k)?;
+          write!(f, ":{}", v)?;
+        }
+
+        f.write_char('}')
+      }
+    }
+  }
+}
+
+/// Writes a quoted string, escaping quotes, backslashes, and control
+/// characters.
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+  f.write_char('"')?;
+
+  for c in s.chars() {
+    match c {
+      '"' => f.write_str("\\\"")?,
+      '\\' => f.write_str("\\\\")?,
+      '\n' => f.write_str("\\n")?,
+      '\r' => f.write_str("\\r")?,
+      '\t' => f.write_str("\\t")?,
+      '\u{8}' => f.write_str("\\b")?,
+      '\u{c}' => f.write_str("\\f")?,
+      c if c < '\u{20}' || c == '\u{7f}' => write!(f, "\\u{:04x}", c as u32)?,
+      c => f.write_char(c)?,
+    }
+  }
+
+  f.write_char('"')
+}
+
+struct Parser<'a> {
+  src: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Parser<'a> {
+  #[inline]
+  fn peek(&self) -> Option<u8> {
+    self.src.get(self.pos).cloned()
+  }
+
+  #[inline]
+  fn whitespace(&mut self) {
+    while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+      self.pos += 1;
+    }
+  }
+
+  /// Consumes a literal, like `true`.
+  fn literal(&mut self, lit: &[u8], value: Json) -> Result<Json, &'static str> {
+    if self.src[self.pos..].starts_with(lit) {
+      self.pos += lit.len();
+      Ok(value)
+    } else {
+      Err("invalid literal")
+    }
+  }
+
+  fn value(&mut self, depth: usize) -> Result<Json, &'static str> {
+    if depth > MAX_DEPTH {
+      return Err("nested too deeply");
+    }
+
+    self.whitespace();
+
+    match self.peek() {
+      Some(b'{') => self.object(depth),
+      Some(b'[') => self.array(depth),
+      Some(b'"') => self.string().map(Json::String),
+      Some(b't') => self.literal(b"true", Json::Boolean(true)),
+      Some(b'f') => self.literal(b"false", Json::Boolean(false)),
+      Some(b'n') => self.literal(b"null", Json::Null),
+      Some(b'-') | Some(b'0'..=b'9') => self.number(),
+      Some(_) => Err("unexpected character"),
+      None => Err("unexpected end of input"),
+    }
+  }
+
+  fn array(&mut self, depth: usize) -> Result<Json, &'static str> {
+    let mut arr = Vec::new();
+
+    self.pos += 1;
+    self.whitespace();
+
+    if self.peek() == Some(b']') {
+      self.pos += 1;
+      return Ok(Json::Array(arr));
+    }
+
+    loop {
+      arr.push(self.value(depth + 1)?);
+      self.whitespace();
+
+      match self.peek() {
+        Some(b',') => self.pos += 1,
+        Some(b']') => {
+          self.pos += 1;
+          return Ok(Json::Array(arr));
+        }
+        _ => return Err("expected `,` or `]`"),
+      }
+    }
+  }
+
+  fn object(&mut self, depth: usize) -> Result<Json, &'static str> {
+    let mut obj = BTreeMap::new();
+
+    self.pos += 1;
+    self.whitespace();
+
+    if self.peek() == Some(b'}') {
+      self.pos += 1;
+      return Ok(Json::Object(obj));
+    }
+
+    loop {
+      self.whitespace();
+
+      if self.peek() != Some(b'"') {
+        return Err("expected a key");
+      }
+
+      let key = self.string()?;
+
+      self.whitespace();
+
+      if self.peek() != Some(b':') {
+        return Err("expected `:`");
+      }
+
+      self.pos += 1;
+      obj.insert(key, self.value(depth + 1)?);
+      self.whitespace();
+
+      match self.peek() {
+        Some(b',') => self.pos += 1,
+        Some(b'}') => {
+          self.pos += 1;
+          return Ok(Json::Object(obj));
+        }
+        _ => return Err("expected `,` or `}`"),
+      }
+    }
+  }
+
+  fn string(&mut self) -> Result<String, &'static str> {
+    let mut out = String::new();
+
+    self.pos += 1;
+
+    loop {
+      // Copy everything up to the next quote, or escape at once. Both are
+      // ASCII, so the run always ends on a character boundary.
+      let start = self.pos;
+
+      while let Some(c) = self.peek() {
+        if c == b'"' || c == b'\\' || c < 0x20 {
+          break;
+        }
+
+        self.pos += 1;
+      }
+
+      match ::std::str::from_utf8(&self.src[start..self.pos]) {
+        Ok(s) => out.push_str(s),
+        Err(_) => return Err("invalid utf-8 in string"),
+      }
+
+      match self.peek() {
+        Some(b'"') => {
+          self.pos += 1;
+          return Ok(out);
+        }
+        Some(b'\\') => {
+          self.pos += 1;
+
+          let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => self.unicode_escape()?,
+            _ => return Err("invalid escape"),
+          };
+
+          self.pos += 1;
+          out.push(c);
+        }
+        Some(_) => return Err("control character in string"),
+        None => return Err("unterminated string"),
+      }
+    }
+  }
+
+  /// Parses the digits of a `\u` escape, and a second escape if the first is
+  /// a high surrogate. Leaves the position on the last digit.
+  fn unicode_escape(&mut self) -> Result<char, &'static str> {
+    let hi = self.hex4()?;
+
+    let code = if (0xd800..0xdc00).contains(&hi) {
+      if !self.src[self.pos + 1..].starts_with(b"\\u") {
+        return Err("unpaired surrogate");
+      }
+
+      self.pos += 2;
+
+      let lo = self.hex4()?;
+
+      if !(0xdc00..0xe000).contains(&lo) {
+        return Err("unpaired surrogate");
+      }
+
+      0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00)
+    } else {
+      hi
+    };
+
+    ::std::char::from_u32(code).ok_or("invalid unicode escape")
+  }
+
+  /// Parses the 4 hex digits after the current position.
+  fn hex4(&mut self) -> Result<u32, &'static str> {
+    let digits = self
+      .src
+      .get(self.pos + 1..self.pos + 5)
+      .and_then(|d| ::std::str::from_utf8(d).ok())
+      .and_then(|d| u32::from_str_radix(d, 16).ok());
+
+    match digits {
+      Some(n) => {
+        self.pos += 4;
+        Ok(n)
+      }
+      None => Err("invalid unicode escape"),
+    }
+  }
+
+  fn number(&mut self) -> Result<Json, &'static str> {
+    let start = self.pos;
+    let mut is_float = false;
+
+    if self.peek() == Some(b'-') {
+      self.pos += 1;
+    }
+
+    while let Some(c) = self.peek() {
+      match c {
+        b'0'..=b'9' => (),
+        b'.' | b'e' | b'E' | b'+' | b'-' => is_float = true,
+        _ => break,
+      }
+
+      self.pos += 1;
+    }
+
+    // Only ASCII was consumed.
+    let s = ::std::str::from_utf8(&self.src[start..self.pos]).unwrap();
+
+    let digits = s.trim_start_matches('-');
+
+    if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0') && !is_float) {
+      return Err("invalid number");
+    }
+
+    if !is_float {
+      if let Ok(n) = s.parse() {
+        return Ok(Json::U64(n));
+      }
+
+      if let Ok(n) = s.parse() {
+        return Ok(Json::I64(n));
+      }
+    }
+
+    s.parse().map(Json::F64).map_err(|_| "invalid number")
+  }
+}
+
+#[test]
+fn json_parse_test() {
+  let json: Json =
+    r#" {"a": [1, -2, 3.5, true, false, null], "b\u00e9\n": "x\"\\\/\ud83d\ude00", "c": {}} "#
+      .parse()
+      .unwrap();
+  let mut obj = BTreeMap::new();
+
+  obj.insert(
+    "a".to_string(),
+    Json::Array(vec![
+      Json::U64(1),
+      Json::I64(-2),
+      Json::F64(3.5),
+      Json::Boolean(true),
+      Json::Boolean(false),
+      Json::Null,
+    ]),
+  );
+  obj.insert(
+    "b\u{e9}\n".to_string(),
+    Json::String("x\"\\/\u{1f600}".to_string()),
+  );
+  obj.insert("c".to_string(), Json::Object(BTreeMap::new()));
+
+  assert_eq!(json, Json::Object(obj));
+
+  for bad in [
+    "",
+    "{",
+    "[1,]",
+    "{\"a\" 1}",
+    "\"abc",
+    "\"\\q\"",
+    "01",
+    "-",
+    "[1] 2",
+    "tru",
+    "\"\\ud800\"",
+    "\"\n\"",
+  ]
+  .iter()
+  {
+    assert!(bad.parse::<Json>().is_err(), "{:?}", bad);
+  }
+
+  let deep = format!("{}{}", "[".repeat(MAX_DEPTH + 2), "]".repeat(MAX_DEPTH + 2));
+
+  assert_eq!(deep.parse::<Json>(), Err("nested too deeply"));
+}
+
+#[test]
+fn json_display_round_trip_test() {
+  let s = r#"{"a":[1,-2,3.5,true,null],"b":"\"\\\n\u0001\u007f\u00e9"}"#;
+  let json: Json = s.parse().unwrap();
+
+  assert_eq!(json.to_string(), s.replace("\\u00e9", "\u{e9}"));
+  assert_eq!(json.to_string().parse::<Json>().unwrap(), json);
+}
@@ -38,14 +38,28 @@
 //! }
 //! ```
 //!
+//! For one-off documents, `split_sentences` does the same in one call.
+//!
+//! ```
+//! # use punkt::split_sentences;
+//! #
+//! # let doc = "I bought $5.50 worth of apples from the store. I gave them to my dog when I came home.";
+//! for s in split_sentences(doc) {
+//!   println!("{:?}", s);
+//! }
+//! ```
+//!
 //! `rust-punkt` also provides pretrained data that can be loaded for certain languages.
 //!
 //! ```
 //! # #![allow(unused_variables)]
 //! #
+//! # #[cfg(feature = "english")]
+//! # {
 //! # use punkt::TrainingData;
 //! #
 //! let data = TrainingData::english();
+//! # }
 //! ```
 //!
 //! `rust-punkt` also allows training data to be incrementally gathered.
@@ -72,6 +86,8 @@
 //! `rust-punkt` exposes a number of traits to customize how the trainer, sentence tokenizer,
 //! and internal tokenizers work. The default settings, which are nearly identical, to the
 //! ones available in the Python library are available in `punkt::params::Standard`.
+//! Presets tuned for different kinds of text are available as well: `Precise`, `Aggressive`,
+//! `Legal`, `Biomedical`, `Web`, and `Rtl`.
 //!
 //! To modify only how the trainer works:
 //!
@@ -81,10 +97,12 @@
 //! struct MyParams;
 //!
 //! impl DefinesInternalPunctuation for MyParams {}
+//! impl DefinesMultiChars for MyParams {}
 //! impl DefinesNonPrefixCharacters for MyParams {}
 //! impl DefinesNonWordCharacters for MyParams {}
 //! impl DefinesPunctuation for MyParams {}
 //! impl DefinesSentenceEndings for MyParams {}
+//! impl DefinesTypeNormalization for MyParams {}
 //!
 //! impl TrainerParameters for MyParams {
 //!   const ABBREV_LOWER_BOUND: f64 = 0.3;
@@ -107,6 +125,7 @@
 //!
 //! impl DefinesSentenceEndings for MyParams {
 //!   // const SENTENCE_ENDINGS: &'static Set<char> = &phf_set![...];
+//!   // const ABBREV_TRAILING_CHARS: &'static Set<char> = &phf_set![...];
 //! }
 //!
 //! impl DefinesInternalPunctuation for MyParams {
@@ -115,6 +134,8 @@
 //!
 //! impl DefinesNonWordCharacters for MyParams {
 //!   // const NONWORD_CHARS: &'static Set<char> = &phf_set![...];
+//!   // const TRAILING_SEPARATORS: &'static Set<char> = &phf_set![...];
+//!   // const MAX_LOOKAHEAD: usize = ...;
 //! }
 //!
 //! impl DefinesPunctuation for MyParams {
@@ -125,6 +146,29 @@
 //!   // const NONPREFIX_CHARS: &'static Set<char> = &phf_set![...];
 //! }
 //!
+//! impl DefinesMultiChars for MyParams {
+//!   // const MULTI_CHAR_STARTS: &'static Set<char> = &phf_set![...];
+//!   // fn multi_char(doc: &str) -> Option<&str> { ... }
+//!   // fn protected_token(doc: &str) -> Option<usize> { ... }
+//! }
+//!
+//! impl DefinesTypeNormalization for MyParams {
+//!   // fn special_type_with_period(word: &str) -> Option<&'static str> { ... }
+//!   // fn fold_case(word: &str, buf: &mut String) { ... }
+//!   // const IGNORABLE_CHARS: &'static Set<char> = &phf_set![...];
+//! }
+//!
+//! impl TokenizerParameters for MyParams {
+//!   // const DIALOGUE_AWARE: bool = ...;
+//!   // const REALIGN_BOUNDARIES: bool = ...;
+//!   // const NUMBER_PERIODS: NumberPeriods = ...;
+//!   // const CLOSING_QUOTES: &'static Set<char> = &phf_set![...];
+//!   // const BRACKET_PAIRS: &'static [(char, char)] = &[...];
+//!   // const MAX_BRACKET_LEN: usize = ...;
+//!   // const UNSPACED_SENTENCE_ENDINGS: &'static Set<char> = &phf_set![...];
+//!   // const MAX_SENTENCE_LEN: usize = ...;
+//! }
+//!
 //! impl TrainerParameters for MyParams {
 //!   // const ABBREV_LOWER_BOUND: f64 = ...;
 //!   // const ABBREV_UPPER_BOUND: f64 = ...;
@@ -134,74 +178,244 @@
 //!   // const INCLUDE_ALL_COLLOCATIONS: bool = ...;
 //!   // const INCLUDE_ABBREV_COLLOCATIONS: bool = true;
 //!   // const COLLOCATION_FREQUENCY_LOWER_BOUND: f64 = ...;
+//!   // const MIN_ABBREV_LEN: usize = ...;
+//!   // const MAX_ABBREV_LEN: usize = ...;
+//!   // const SCORE_EPSILON: f64 = ...;
 //! }
 //! ```
+//!
+//! On targets where `f64` math is slow, the `f32-scores` feature computes the
+//! likelihood scores of the trainer with `f32`, and the `fixed-scores` feature
+//! computes them with 64-bit fixed-point integers. Scores are still `f64` in
+//! the public API. Models trained this way can differ slightly, when a score
+//! is close to a threshold.
+//!
+//! The pretrained models live in their own `punkt-data-<language>` crates, one
+//! per language, and are enabled through features of the same name (`english`,
+//! `german`, ...). The default `all-languages` feature enables every one of them.
+//! To embed only the models you use, turn off the default features:
+//!
+//! ```toml
+//! [dependencies]
+//! punkt = { version = "1.0", default-features = false, features = ["english"] }
+//! ```
+//!
+//! Models with very large abbreviation, and sentence starter lists can be
+//! frozen with `TrainingData::freeze`, which stores those lists as finite
+//! state transducers, using the `fst` feature. Frozen data has the same
+//! lookups, and can be used with any tokenizer, but can't be trained.
+//!
+//! The `tokenizers` feature adds `PunktPreTokenizer`, which splits text into
+//! sentences, or words in a Hugging Face `tokenizers` pipeline.
+//!
+//! # Panics
+//!
+//! The tokenizers (`WordTokenizer`, `SentenceTokenizer`,
+//! `SentenceByteOffsetTokenizer`, `SentenceSpanTokenizer`, and the functions
+//! built on them) never panic, whatever `&str` they are given. This is part of
+//! the stable API. Parameters that return something that doesn't fit the
+//! document, like a `protected_token` length that isn't at a character
+//! boundary, are ignored rather than trusted.
+//!
+//! The modules of the tokenizers deny the clippy lints for indexing, slicing,
+//! and unwrapping, so documents are only sliced with checked methods. The
+//! `fuzzing` feature adds `fuzz_targets::segment_presets`, which checks every
+//! preset on any input.
 
 #![cfg_attr(test, feature(test))]
 #![feature(proc_macro_hygiene)]
 #![warn(missing_docs)]
 
 extern crate freqdist;
-extern crate num;
+#[cfg(feature = "fst")]
+extern crate fst;
+#[cfg(feature = "notify")]
+extern crate notify;
 extern crate phf;
-extern crate rustc_serialize;
+#[cfg(feature = "codegen")]
+extern crate phf_generator;
+#[cfg(feature = "arabic")]
+extern crate punkt_data_arabic;
+#[cfg(feature = "czech")]
+extern crate punkt_data_czech;
+#[cfg(feature = "danish")]
+extern crate punkt_data_danish;
+#[cfg(feature = "dutch")]
+extern crate punkt_data_dutch;
+#[cfg(feature = "english")]
+extern crate punkt_data_english;
+#[cfg(feature = "estonian")]
+extern crate punkt_data_estonian;
+#[cfg(feature = "finnish")]
+extern crate punkt_data_finnish;
+#[cfg(feature = "french")]
+extern crate punkt_data_french;
+#[cfg(feature = "german")]
+extern crate punkt_data_german;
+#[cfg(feature = "greek")]
+extern crate punkt_data_greek;
+#[cfg(feature = "italian")]
+extern crate punkt_data_italian;
+#[cfg(feature = "norwegian")]
+extern crate punkt_data_norwegian;
+#[cfg(feature = "polish")]
+extern crate punkt_data_polish;
+#[cfg(feature = "portuguese")]
+extern crate punkt_data_portuguese;
+#[cfg(feature = "slovene")]
+extern crate punkt_data_slovene;
+#[cfg(feature = "spanish")]
+extern crate punkt_data_spanish;
+#[cfg(feature = "swedish")]
+extern crate punkt_data_swedish;
+#[cfg(feature = "turkish")]
+extern crate punkt_data_turkish;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 #[cfg(test)]
 extern crate test;
-#[cfg(test)]
-extern crate walkdir;
+#[cfg(feature = "tokenizers")]
+extern crate tokenizers;
+
+/// Expands to a `CompiledData` for a JSON model, that was compiled by
+/// `codegen::embed_model` in the build script of the crate. The path must be
+/// the same string that was passed to `codegen::embed_model`.
+///
+/// # Examples
+///
+/// ```ignore
+/// static LEGAL: punkt::CompiledData = punkt::embed_model!("models/legal.json");
+/// ```
+#[macro_export]
+macro_rules! embed_model(
+  ($path:tt) => (include!(concat!(env!("OUT_DIR"), "/punkt/", $path, ".rs")))
+);
 
+mod abbrev;
+mod builder;
+mod cache;
+mod callback;
+mod candidates;
+mod chunk;
+mod count;
+mod cursor;
+mod dataset;
+mod feedback;
+mod filter;
+#[cfg(any(test, feature = "fixed-scores"))]
+mod fixed;
+mod format;
+#[cfg(feature = "fst")]
+mod frozen;
+mod json;
+mod memory;
+mod metadata;
+mod model;
+mod ortho;
+mod overlay;
+mod parallel;
+mod prelude;
+mod pretokenize;
+#[cfg(feature = "regex")]
+mod protect;
+mod quickstart;
+mod redact;
+mod reflow;
+mod score;
+mod segmenter;
+mod spans;
+mod starters;
+mod structure;
+mod tokenizer;
 mod trainer;
+mod truncate;
 mod util;
-mod token;
-mod tokenizer;
-mod prelude;
+mod view;
+#[cfg(feature = "notify")]
+mod watch;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+pub mod conformance;
+pub mod corpus;
+pub mod dedup;
+pub mod diagnostics;
+#[cfg(feature = "difftest")]
+pub mod difftest;
+pub mod estimate;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz_targets;
+pub mod token;
+pub mod tune;
 
-pub use trainer::{Trainer, TrainingData};
-pub use tokenizer::{SentenceByteOffsetTokenizer, SentenceTokenizer};
+pub use abbrev::AbbrevDetector;
+pub use builder::TrainingDataBuilder;
+pub use cache::{cached_sentence_byte_offsets, CacheKey, LruSegmentCache, SegmentCache};
+pub use callback::{for_each_sentence, for_each_sentence_with_params};
+pub use candidates::{
+  boundary_candidates, boundary_candidates_with_params, BoundaryCandidate, BoundaryCandidates,
+  BoundaryDecider, DecidedSentences, PunktDecider,
+};
+pub use chunk::SentenceChunker;
+pub use count::{CountMinSketch, ExactCounter, TypeCounter};
+pub use dataset::TrainingDataSet;
+pub use feedback::SpanCorrection;
+pub use format::{LoadError, MODEL_VERSION};
+#[cfg(feature = "fst")]
+pub use frozen::FrozenTrainingData;
+pub use memory::{MemoryBudget, MemoryReport};
+pub use metadata::{Metadata, ParamsSnapshot};
+pub use model::{CompiledData, Model};
+pub use ortho::OrthoContext;
+pub use overlay::Overlay;
+pub use parallel::ParSentences;
+#[cfg(feature = "tokenizers")]
+pub use pretokenize::PunktPreTokenizer;
+#[cfg(feature = "regex")]
+pub use protect::ProtectedTokens;
+pub use quickstart::{split_sentences, split_sentences_with};
+pub use redact::Redacted;
+pub use reflow::{reflow, Reflowed};
+pub use segmenter::{
+  reconstruct, BoundaryDecision, ScratchSpace, Segment, Segmenter, Segments, SentenceSegmenter,
+};
+pub use spans::{
+  segment_with_token_spans, segment_with_token_spans_with_params, SentenceTokenSpans,
+};
+pub use starters::{sentence_initial_tokens, SentenceInitialTokens};
+pub use structure::StructuredByteOffsetTokenizer;
+pub use token::{normalize_type, TokenKind};
+pub use tokenizer::{
+  SentenceByteOffsetTokenizer, SentenceSpan, SentenceSpanTokenizer, SentenceTokenizer,
+  SpannedWords, WordTokenizer,
+};
+pub use trainer::{
+  corpus_counts, CorpusCounts, Overrides, Provenance, TrainDelta, TrainReport, Trainer,
+  TrainingData,
+};
+pub use truncate::{truncate_at_sentence, truncate_at_sentence_with_params};
+pub use view::TrainingDataView;
+#[cfg(feature = "notify")]
+pub use watch::WatchedModel;
 
 /// Contains traits for configuring all tokenizers, and the trainer. Also
-/// contains default parameters for tokenizers, and the trainer.
+/// contains default parameters for tokenizers, and the trainer, and presets
+/// tuned for different kinds of text.
 pub mod params {
-  pub use prelude::{DefinesInternalPunctuation, DefinesNonPrefixCharacters,
-                    DefinesNonWordCharacters, DefinesPunctuation, DefinesSentenceEndings, Set,
-                    Standard, TrainerParameters};
-}
-
-#[cfg(test)]
-fn get_test_scenarios(dir_path: &str, raw_path: &str) -> Vec<(Vec<String>, String, String)> {
-  #![allow(unused_must_use)]
-
-  use std::fs;
-  use std::path::Path;
-  use std::io::Read;
-
-  use walkdir::WalkDir;
-
-  let mut tests = Vec::new();
-
-  for path in WalkDir::new(dir_path) {
-    let entry = path.unwrap();
-    let fpath = entry.path();
-
-    if fpath.is_file() {
-      let mut exp_strb = String::new();
-      let mut raw_strb = String::new();
-
-      // Files in the directory with raw articles must match the file names of
-      // articles in the directory with test outcomes.
-      let rawp = Path::new(raw_path).join(fpath.file_name().unwrap());
-
-      fs::File::open(&fpath)
-        .unwrap()
-        .read_to_string(&mut exp_strb);
-      fs::File::open(&rawp).unwrap().read_to_string(&mut raw_strb);
-
-      // Expected results, split by newlines.
-      let exps: Vec<String> = exp_strb.split('\n').map(|s| s.to_string()).collect();
-
-      tests.push((exps, raw_strb, format!("{:?}", fpath.file_name().unwrap())));
-    }
-  }
-
-  tests // Returns (Expected cases, File contents, File name)
+  pub use prelude::{
+    Aggressive, Biomedical, DefinesInternalPunctuation, DefinesMultiChars,
+    DefinesNonPrefixCharacters, DefinesNonWordCharacters, DefinesPunctuation,
+    DefinesSentenceEndings, DefinesTypeNormalization, Legal, NumberPeriods, Precise, Rtl, Set,
+    Standard, StructureParameters, TokenizerParameters, TrainerParameters, Web,
+  };
 }
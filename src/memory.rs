@@ -0,0 +1,149 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::mem::size_of;
+
+use freqdist::FrequencyDistribution;
+
+/// An estimate of the heap memory used by the parts of a `TrainingData`, in
+/// bytes. See `TrainingData::memory_footprint`.
+///
+/// The estimates count the tables of the hash maps, and sets, and the
+/// strings they hold, but not the overhead of the allocator, so the memory
+/// actually used is somewhat higher.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+  /// The abbreviations, and the provenance of the ones that were learned,
+  /// or inserted.
+  pub abbrevs: usize,
+  /// The collocations.
+  pub collocations: usize,
+  /// The right contexts of abbreviations.
+  pub abbrev_contexts: usize,
+  /// The sentence starters.
+  pub sentence_starters: usize,
+  /// The orthographic contexts.
+  pub orthographic_contexts: usize,
+  /// The lookup filter, if it is enabled.
+  pub lookup_filter: usize,
+}
+
+impl MemoryReport {
+  /// The total estimated heap usage.
+  #[inline]
+  pub fn total(&self) -> usize {
+    self.abbrevs
+      + self.collocations
+      + self.abbrev_contexts
+      + self.sentence_starters
+      + self.orthographic_contexts
+      + self.lookup_filter
+  }
+}
+
+/// A limit on the estimated heap usage of the frequency tables of a
+/// trainer, in bytes. See `Trainer::with_memory_budget`.
+///
+/// The trainer counts types, and the pairs of tokens around periods that
+/// could be collocations, or sentence starters, in tables that grow with the
+/// vocabulary of the corpus. When the estimated size of the tables goes over
+/// the budget, the entries with the lowest counts are dropped from them,
+/// until they fit again. Rare entries are the least likely to be learned,
+/// but a dropped entry counts as never seen, so a model that is trained
+/// within a budget can differ from one that isn't. The tokens of the corpus
+/// aren't counted in the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBudget {
+  bytes: usize,
+}
+
+impl MemoryBudget {
+  /// Creates a budget of `bytes` bytes.
+  #[inline(always)]
+  pub fn new(bytes: usize) -> MemoryBudget {
+    MemoryBudget { bytes: bytes }
+  }
+
+  /// The number of bytes in the budget.
+  #[inline(always)]
+  pub fn bytes(&self) -> usize {
+    self.bytes
+  }
+}
+
+/// Estimates the size of the table of a hash map, with entries of `entry`
+/// bytes. The table has a power of two number of buckets, that are at most
+/// 7/8 full, and a control byte for each bucket, plus a group of 16.
+#[inline]
+pub(crate) fn table_size(capacity: usize, entry: usize) -> usize {
+  if capacity == 0 {
+    return 0;
+  }
+
+  let buckets = if capacity < 8 {
+    if capacity < 4 {
+      4
+    } else {
+      8
+    }
+  } else {
+    (capacity * 8 / 7).next_power_of_two()
+  };
+
+  buckets * (entry + 1) + 16
+}
+
+/// Estimates the heap usage of a frequency distribution, with a table that
+/// is just large enough for its entries. The keys of the trainer borrow
+/// their tokens, so they have no heap usage of their own.
+#[inline]
+pub(crate) fn fdist_size<K: Eq + Hash>(fdist: &FrequencyDistribution<K>) -> usize {
+  table_size(fdist.keys().len(), size_of::<(K, usize)>())
+}
+
+/// Estimates the heap usage of a set of strings.
+pub(crate) fn set_size(set: &HashSet<String>) -> usize {
+  table_size(set.capacity(), size_of::<String>()) + set.iter().map(String::capacity).sum::<usize>()
+}
+
+/// Estimates the heap usage of a map with string keys, not counting the heap
+/// usage of its values.
+pub(crate) fn map_size<V>(map: &HashMap<String, V>) -> usize {
+  table_size(map.capacity(), size_of::<(String, V)>())
+    + map.keys().map(String::capacity).sum::<usize>()
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn memory_size_test() {
+  let mut set = HashSet::new();
+
+  assert_eq!(set_size(&set), 0);
+
+  set.insert("abc".to_string());
+
+  assert!(set_size(&set) >= 3 + size_of::<String>());
+
+  let mut map: HashMap<String, u8> = HashMap::with_capacity(100);
+
+  map.insert("x".to_string(), 1);
+
+  assert!(map_size(&map) >= 100 * (size_of::<(String, u8)>() + 1));
+
+  let mut data = ::trainer::TrainingData::english();
+  let before = data.memory_footprint();
+
+  data.enable_lookup_filter();
+
+  let after = data.memory_footprint();
+
+  assert!(after.lookup_filter > 0);
+  assert_eq!(after.total(), before.total() + after.lookup_filter);
+}
@@ -0,0 +1,439 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+
+use json::Json;
+
+use prelude::{
+  DefinesNonPrefixCharacters, DefinesNonWordCharacters, Set, TokenizerParameters, TrainerParameters,
+};
+use trainer::{Overrides, TrainingData};
+
+/// The version of the crate, recorded in models that it trains.
+pub const CRATE_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+/// Provenance of a model, that is serialized along with it. None of the
+/// metadata affects tokenization.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::{Trainer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let trainer: Trainer<Standard> = Trainer::new();
+/// let mut data = TrainingData::new();
+///
+/// trainer.train("This is a sentence. This is another.", &mut data);
+/// data.metadata_mut().set_language("en");
+/// data.metadata_mut().set_corpus("Two example sentences");
+///
+/// assert_eq!(data.metadata().language(), Some("en"));
+/// assert!(data.metadata().crate_version().is_some());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+  language: Option<String>,
+  corpus: Option<String>,
+  crate_version: Option<String>,
+  params: Option<ParamsSnapshot>,
+}
+
+impl Metadata {
+  /// A tag for the language of the model (for example, `en`).
+  #[inline]
+  pub fn language(&self) -> Option<&str> {
+    self.language.as_ref().map(|s| &s[..])
+  }
+
+  /// Sets the language of the model.
+  #[inline]
+  pub fn set_language<T: Into<String>>(&mut self, language: T) {
+    self.language = Some(language.into());
+  }
+
+  /// A description of the corpus the model was trained on.
+  #[inline]
+  pub fn corpus(&self) -> Option<&str> {
+    self.corpus.as_ref().map(|s| &s[..])
+  }
+
+  /// Sets the description of the corpus the model was trained on.
+  #[inline]
+  pub fn set_corpus<T: Into<String>>(&mut self, corpus: T) {
+    self.corpus = Some(corpus.into());
+  }
+
+  /// The version of the crate that last trained the model. Models that were
+  /// never trained by this crate (like the pretrained models) have no
+  /// version.
+  #[inline]
+  pub fn crate_version(&self) -> Option<&str> {
+    self.crate_version.as_ref().map(|s| &s[..])
+  }
+
+  #[inline]
+  pub(crate) fn set_crate_version<T: Into<String>>(&mut self, crate_version: T) {
+    self.crate_version = Some(crate_version.into());
+  }
+
+  /// The parameters the model was last trained with.
+  #[inline]
+  pub fn params(&self) -> Option<&ParamsSnapshot> {
+    self.params.as_ref()
+  }
+
+  #[inline]
+  pub(crate) fn set_params(&mut self, params: ParamsSnapshot) {
+    self.params = Some(params);
+  }
+
+  /// Checks if all of the metadata is missing.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.language.is_none()
+      && self.corpus.is_none()
+      && self.crate_version.is_none()
+      && self.params.is_none()
+  }
+}
+
+/// A parameter value, as it's stored in a serialized model.
+trait SnapshotValue: Sized {
+  fn to_json(&self) -> Json;
+  fn from_json(json: Json) -> Option<Self>;
+}
+
+// The JSON encoder rounds floats, so they are stored as strings, that parse
+// back to the exact same value.
+impl SnapshotValue for f64 {
+  #[inline]
+  fn to_json(&self) -> Json {
+    Json::String(self.to_string())
+  }
+
+  #[inline]
+  fn from_json(json: Json) -> Option<f64> {
+    match json {
+      Json::String(s) => s.parse().ok(),
+      _ => None,
+    }
+  }
+}
+
+impl SnapshotValue for bool {
+  #[inline]
+  fn to_json(&self) -> Json {
+    Json::Boolean(*self)
+  }
+
+  #[inline]
+  fn from_json(json: Json) -> Option<bool> {
+    json.as_boolean()
+  }
+}
+
+impl SnapshotValue for String {
+  #[inline]
+  fn to_json(&self) -> Json {
+    Json::String(self.clone())
+  }
+
+  #[inline]
+  fn from_json(json: Json) -> Option<String> {
+    match json {
+      Json::String(s) => Some(s),
+      _ => None,
+    }
+  }
+}
+
+macro_rules! params_snapshot(
+  ($($field:ident: $ty:ty, $doc:expr;)*) => (
+    /// The values of the parameters a trainer was run with. Changing any of
+    /// them changes what a trainer learns.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ParamsSnapshot {
+      $($field: $ty),*
+    }
+
+    impl ParamsSnapshot {
+      $(
+        #[doc = $doc]
+        #[inline(always)]
+        pub fn $field(&self) -> &$ty {
+          &self.$field
+        }
+      )*
+
+      pub(crate) fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+
+        $(obj.insert(stringify!($field).to_string(), self.$field.to_json());)*
+
+        Json::Object(obj)
+      }
+
+      pub(crate) fn from_json(json: Json) -> Option<ParamsSnapshot> {
+        match json {
+          Json::Object(mut obj) => Some(ParamsSnapshot {
+            $($field: obj.remove(stringify!($field)).and_then(SnapshotValue::from_json)?),*
+          }),
+          _ => None,
+        }
+      }
+    }
+  )
+);
+
+params_snapshot!(
+  abbrev_lower_bound: f64, "`TrainerParameters::ABBREV_LOWER_BOUND`";
+  abbrev_upper_bound: f64, "`TrainerParameters::ABBREV_UPPER_BOUND`";
+  ignore_abbrev_penalty: bool, "`TrainerParameters::IGNORE_ABBREV_PENALTY`";
+  collocation_lower_bound: f64, "`TrainerParameters::COLLOCATION_LOWER_BOUND`";
+  sentence_starter_lower_bound: f64, "`TrainerParameters::SENTENCE_STARTER_LOWER_BOUND`";
+  include_all_collocations: bool, "`TrainerParameters::INCLUDE_ALL_COLLOCATIONS`";
+  include_abbrev_collocations: bool, "`TrainerParameters::INCLUDE_ABBREV_COLLOCATIONS`";
+  collocation_frequency_lower_bound: f64,
+    "`TrainerParameters::COLLOCATION_FREQUENCY_LOWER_BOUND`";
+  sentence_endings: String, "`DefinesSentenceEndings::SENTENCE_ENDINGS`, as a sorted string.";
+  internal_punctuation: String,
+    "`DefinesInternalPunctuation::INTERNAL_PUNCTUATION`, as a sorted string.";
+  nonprefix_chars: String, "`DefinesNonPrefixCharacters::NONPREFIX_CHARS`, as a sorted string.";
+  nonword_chars: String, "`DefinesNonWordCharacters::NONWORD_CHARS`, as a sorted string.";
+);
+
+impl ParamsSnapshot {
+  /// Takes a snapshot of the parameters of a trainer.
+  pub fn of<P>() -> ParamsSnapshot
+  where
+    P: TrainerParameters + DefinesNonPrefixCharacters + DefinesNonWordCharacters,
+  {
+    ParamsSnapshot {
+      abbrev_lower_bound: P::ABBREV_LOWER_BOUND,
+      abbrev_upper_bound: P::ABBREV_UPPER_BOUND,
+      ignore_abbrev_penalty: P::IGNORE_ABBREV_PENALTY,
+      collocation_lower_bound: P::COLLOCATION_LOWER_BOUND,
+      sentence_starter_lower_bound: P::SENTENCE_STARTER_LOWER_BOUND,
+      include_all_collocations: P::INCLUDE_ALL_COLLOCATIONS,
+      include_abbrev_collocations: P::INCLUDE_ABBREV_COLLOCATIONS,
+      collocation_frequency_lower_bound: P::COLLOCATION_FREQUENCY_LOWER_BOUND,
+      sentence_endings: sorted_chars(P::SENTENCE_ENDINGS),
+      internal_punctuation: sorted_chars(P::INTERNAL_PUNCTUATION),
+      nonprefix_chars: sorted_chars(P::NONPREFIX_CHARS),
+      nonword_chars: sorted_chars(P::NONWORD_CHARS),
+    }
+  }
+
+  /// Replaces the thresholds that are overridden.
+  pub(crate) fn with_overrides(mut self, overrides: &Overrides) -> ParamsSnapshot {
+    if let Some(v) = overrides.abbrev_lower_bound {
+      self.abbrev_lower_bound = v;
+    }
+
+    if let Some(v) = overrides.collocation_lower_bound {
+      self.collocation_lower_bound = v;
+    }
+
+    if let Some(v) = overrides.sentence_starter_lower_bound {
+      self.sentence_starter_lower_bound = v;
+    }
+
+    self
+  }
+
+  /// Compares the character sets shared by the trainer, and a tokenizer.
+  /// A model is only reliable when it's used with the same word, and
+  /// sentence boundaries it was trained with. Returns the names of the
+  /// constants that differ.
+  pub fn tokenizer_mismatches<P>(&self) -> Vec<&'static str>
+  where
+    P: TokenizerParameters,
+  {
+    let mut mismatches = Vec::new();
+
+    if self.sentence_endings != sorted_chars(P::SENTENCE_ENDINGS) {
+      mismatches.push("SENTENCE_ENDINGS");
+    }
+
+    if self.nonprefix_chars != sorted_chars(P::NONPREFIX_CHARS) {
+      mismatches.push("NONPREFIX_CHARS");
+    }
+
+    if self.nonword_chars != sorted_chars(P::NONWORD_CHARS) {
+      mismatches.push("NONWORD_CHARS");
+    }
+
+    mismatches
+  }
+}
+
+#[inline]
+fn sorted_chars(set: &Set<char>) -> String {
+  let mut chars: Vec<char> = set.iter().cloned().collect();
+
+  chars.sort();
+  chars.into_iter().collect()
+}
+
+impl TrainingData {
+  /// A hash of the abbreviations, collocations, sentence starters,
+  /// orthographic contexts, and right contexts of abbreviations in the data.
+  /// Two models with the same content have the same hash, regardless of how
+  /// they were built, or loaded. The hash is stored in exported models, and
+  /// checked when they are loaded.
+  pub fn content_hash(&self) -> u64 {
+    ModelEntries {
+      abbrevs: self.abbrevs().collect(),
+      collocations: self.collocations().collect(),
+      sentence_starters: self.sentence_starters().collect(),
+      orthographic_contexts: self
+        .orthographic_contexts()
+        .map(|(s, c)| (s, c.bits()))
+        .collect(),
+      abbrev_contexts: self.abbrev_contexts().collect(),
+    }
+    .hash()
+  }
+}
+
+/// The entries of a model, as they were stored in an exported model. The
+/// loaders check the checksum of a model against these, before the inserts
+/// normalize them, like lowercasing abbreviations.
+#[derive(Default)]
+pub(crate) struct ModelEntries<'a> {
+  pub(crate) abbrevs: Vec<&'a str>,
+  pub(crate) collocations: Vec<(&'a str, &'a str)>,
+  pub(crate) sentence_starters: Vec<&'a str>,
+  pub(crate) orthographic_contexts: Vec<(&'a str, u8)>,
+  pub(crate) abbrev_contexts: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> ModelEntries<'a> {
+  /// Hashes the entries, sorting them first. See
+  /// `TrainingData::content_hash`.
+  pub(crate) fn hash(&mut self) -> u64 {
+    let mut h = Fnv::new();
+
+    self.abbrevs.sort();
+    self.collocations.sort();
+    self.sentence_starters.sort();
+    self.orthographic_contexts.sort();
+    self.abbrev_contexts.sort();
+
+    // Each section is tagged, and each string terminated, so entries can't
+    // run into each other.
+    h.write(b"a");
+    for s in self.abbrevs.iter() {
+      h.write_str(s);
+    }
+
+    h.write(b"c");
+    for &(l, r) in self.collocations.iter() {
+      h.write_str(l);
+      h.write_str(r);
+    }
+
+    h.write(b"s");
+    for s in self.sentence_starters.iter() {
+      h.write_str(s);
+    }
+
+    h.write(b"o");
+    for &(s, c) in self.orthographic_contexts.iter() {
+      h.write_str(s);
+      h.write(&[c]);
+    }
+
+    // Only hashed when there are any, so the hashes of models without them
+    // are the same as before they were added.
+    if !self.abbrev_contexts.is_empty() {
+      h.write(b"x");
+      for &(a, r) in self.abbrev_contexts.iter() {
+        h.write_str(a);
+        h.write_str(r);
+      }
+    }
+
+    h.0
+  }
+}
+
+/// 64-bit FNV-1a. Unlike the hashers in the standard library, its output is
+/// stable across versions, and platforms.
+pub(crate) struct Fnv(pub(crate) u64);
+
+impl Fnv {
+  #[inline]
+  pub(crate) fn new() -> Fnv {
+    Fnv(0xcbf2_9ce4_8422_2325)
+  }
+
+  #[inline]
+  pub(crate) fn write(&mut self, bytes: &[u8]) {
+    for &b in bytes {
+      self.0 ^= u64::from(b);
+      self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+  }
+
+  #[inline]
+  pub(crate) fn write_str(&mut self, s: &str) {
+    self.write(s.as_bytes());
+    self.write(&[0xff]);
+  }
+}
+
+#[test]
+fn params_snapshot_test() {
+  use phf::phf_set;
+  use prelude::{
+    DefinesMultiChars, DefinesPunctuation, DefinesSentenceEndings, DefinesTypeNormalization,
+    Standard,
+  };
+
+  struct Custom;
+
+  impl DefinesNonPrefixCharacters for Custom {}
+  impl DefinesNonWordCharacters for Custom {}
+  impl DefinesPunctuation for Custom {}
+  impl DefinesSentenceEndings for Custom {
+    const SENTENCE_ENDINGS: &'static Set<char> = &phf_set!['.', '?', '!', ';'];
+  }
+  impl DefinesMultiChars for Custom {}
+  impl DefinesTypeNormalization for Custom {}
+  impl TokenizerParameters for Custom {}
+
+  let snapshot = ParamsSnapshot::of::<Standard>();
+
+  assert_eq!(snapshot.sentence_endings(), "!.?");
+  assert!(snapshot.tokenizer_mismatches::<Standard>().is_empty());
+  assert_eq!(
+    snapshot.tokenizer_mismatches::<Custom>(),
+    vec!["SENTENCE_ENDINGS"]
+  );
+  assert_eq!(
+    ParamsSnapshot::from_json(snapshot.to_json()),
+    Some(snapshot)
+  );
+}
+
+#[cfg(all(feature = "english", feature = "german"))]
+#[test]
+fn content_hash_test() {
+  let mut data = TrainingData::english();
+  let hash = data.content_hash();
+
+  data.metadata_mut().set_language("en");
+
+  assert_eq!(hash, data.content_hash());
+  assert_eq!(hash, TrainingData::english().content_hash());
+  assert!(hash != TrainingData::german().content_hash());
+  assert!(hash != TrainingData::new().content_hash());
+}
@@ -0,0 +1,156 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use phf::{Map, Set};
+
+use metadata::ParamsSnapshot;
+use ortho::OrthoContext;
+use prelude::OrthographicContext;
+use trainer::TrainingData;
+
+/// The lookups a sentence tokenizer performs on the data learned by a
+/// trainer. Implemented by `TrainingData`, and by `CompiledData` for data
+/// that was compiled into a program.
+pub trait Model {
+  /// Check if a token is considered to be an abbreviation.
+  fn contains_abbrev(&self, tok: &str) -> bool;
+
+  /// Checks if a pair of words are a known collocation.
+  fn contains_collocation(&self, left: &str, right: &str) -> bool;
+
+  /// Check if a token is considered to be a token that commonly starts a
+  /// sentence.
+  fn contains_sentence_starter(&self, tok: &str) -> bool;
+
+  /// Gets the orthographic context for a token. Returns an empty context if
+  /// the token was not yet encountered.
+  fn get_orthographic_context(&self, tok: &str) -> OrthoContext;
+
+  /// Checks if an abbreviation can be followed by a word without ending the
+  /// sentence. Abbreviations can be followed by any word by default.
+  #[inline]
+  fn allows_abbrev_context(&self, _abbrev: &str, _right: &str) -> bool {
+    true
+  }
+
+  /// The parameters the model was trained with, if they were recorded. The
+  /// sentence tokenizers compare them to their own in `param_mismatches`.
+  #[inline]
+  fn params(&self) -> Option<&ParamsSnapshot> {
+    None
+  }
+}
+
+impl Model for TrainingData {
+  #[inline(always)]
+  fn contains_abbrev(&self, tok: &str) -> bool {
+    TrainingData::contains_abbrev(self, tok)
+  }
+
+  #[inline(always)]
+  fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    TrainingData::contains_collocation(self, left, right)
+  }
+
+  #[inline(always)]
+  fn contains_sentence_starter(&self, tok: &str) -> bool {
+    TrainingData::contains_sentence_starter(self, tok)
+  }
+
+  #[inline(always)]
+  fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    TrainingData::get_orthographic_context(self, tok)
+  }
+
+  #[inline(always)]
+  fn allows_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    TrainingData::allows_abbrev_context(self, abbrev, right)
+  }
+
+  #[inline(always)]
+  fn params(&self) -> Option<&ParamsSnapshot> {
+    self.metadata().params()
+  }
+}
+
+/// Data learned by a trainer, that was compiled into static perfect hash
+/// tables using `codegen::emit_rust`. Requires no loading, and no heap
+/// allocations.
+///
+/// # Examples
+///
+/// ```ignore
+/// // Generated by `punkt::codegen::emit_rust`, and written to `english.rs`
+/// // by a build script.
+/// include!(concat!(env!("OUT_DIR"), "/english.rs"));
+///
+/// let doc = "this is a great sentence! this is a sad sentence.";
+///
+/// for s in SentenceTokenizer::<Standard, CompiledData>::new(doc, &COMPILED_DATA) {
+///   println!("{:?}", s);
+/// }
+/// ```
+pub struct CompiledData {
+  #[doc(hidden)]
+  pub abbrevs: Set<&'static str>,
+  #[doc(hidden)]
+  pub collocations: Map<&'static str, Set<&'static str>>,
+  #[doc(hidden)]
+  pub sentence_starters: Set<&'static str>,
+  #[doc(hidden)]
+  pub orthographic_context: Map<&'static str, OrthographicContext>,
+}
+
+// Lookups into an empty phf table panic, so they are checked first.
+impl Model for CompiledData {
+  #[inline]
+  fn contains_abbrev(&self, tok: &str) -> bool {
+    !self.abbrevs.is_empty() && self.abbrevs.contains(tok)
+  }
+
+  #[inline]
+  fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    if self.collocations.is_empty() {
+      return false;
+    }
+
+    self
+      .collocations
+      .get(left)
+      .map(|s| !s.is_empty() && s.contains(right))
+      .unwrap_or(false)
+  }
+
+  #[inline]
+  fn contains_sentence_starter(&self, tok: &str) -> bool {
+    !self.sentence_starters.is_empty() && self.sentence_starters.contains(tok)
+  }
+
+  #[inline]
+  fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    if self.orthographic_context.is_empty() {
+      return OrthoContext::empty();
+    }
+
+    OrthoContext::from_bits(*self.orthographic_context.get(tok).unwrap_or(&0))
+  }
+}
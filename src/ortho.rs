@@ -0,0 +1,249 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+use prelude::{BEG_LC, BEG_UC, MID_LC, MID_UC, ORT_LC, ORT_UC, UNK_LC, UNK_UC};
+
+/// The cases, and positions in a sentence, that a word was seen in while
+/// training. Each flag records one combination of case (upper, or lower),
+/// and position (at the beginning of a sentence, in the middle of one, or
+/// unknown).
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{OrthoContext, TrainingData};
+/// #
+/// let data = TrainingData::english();
+/// let ctxt = data.get_orthographic_context("the");
+///
+/// assert!(ctxt.seen_sentence_initial_upper());
+/// assert!(ctxt.seen_sentence_internal_lower());
+/// assert!(ctxt.contains(OrthoContext::BEG_UC | OrthoContext::MID_LC));
+/// assert!(data.get_orthographic_context("zzzz").is_empty());
+/// # }
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OrthoContext(u8);
+
+impl OrthoContext {
+  /// Seen uppercase at the beginning of a sentence.
+  pub const BEG_UC: OrthoContext = OrthoContext(BEG_UC);
+  /// Seen uppercase in the middle of a sentence.
+  pub const MID_UC: OrthoContext = OrthoContext(MID_UC);
+  /// Seen uppercase, where the position in the sentence is unknown.
+  pub const UNK_UC: OrthoContext = OrthoContext(UNK_UC);
+  /// Seen lowercase at the beginning of a sentence.
+  pub const BEG_LC: OrthoContext = OrthoContext(BEG_LC);
+  /// Seen lowercase in the middle of a sentence.
+  pub const MID_LC: OrthoContext = OrthoContext(MID_LC);
+  /// Seen lowercase, where the position in the sentence is unknown.
+  pub const UNK_LC: OrthoContext = OrthoContext(UNK_LC);
+  /// Seen uppercase anywhere.
+  pub const ORT_UC: OrthoContext = OrthoContext(ORT_UC);
+  /// Seen lowercase anywhere.
+  pub const ORT_LC: OrthoContext = OrthoContext(ORT_LC);
+
+  /// A context with no flags set. The context of a word that was never seen.
+  #[inline(always)]
+  pub fn empty() -> OrthoContext {
+    OrthoContext(0)
+  }
+
+  /// Creates a context from its raw representation. Bits that aren't flags
+  /// are kept, as older models may contain them.
+  #[inline(always)]
+  pub fn from_bits(bits: u8) -> OrthoContext {
+    OrthoContext(bits)
+  }
+
+  /// The raw representation of the context, as stored in models.
+  #[inline(always)]
+  pub fn bits(self) -> u8 {
+    self.0
+  }
+
+  /// Checks if no flags are set.
+  #[inline(always)]
+  pub fn is_empty(self) -> bool {
+    self.0 == 0
+  }
+
+  /// Checks if all of the flags in `other` are set.
+  #[inline(always)]
+  pub fn contains(self, other: OrthoContext) -> bool {
+    self.0 & other.0 == other.0
+  }
+
+  /// Checks if any of the flags in `other` are set.
+  #[inline(always)]
+  pub fn intersects(self, other: OrthoContext) -> bool {
+    self.0 & other.0 != 0
+  }
+
+  /// Seen uppercase at the beginning of a sentence.
+  #[inline(always)]
+  pub fn seen_sentence_initial_upper(self) -> bool {
+    self.intersects(OrthoContext::BEG_UC)
+  }
+
+  /// Seen uppercase in the middle of a sentence.
+  #[inline(always)]
+  pub fn seen_sentence_internal_upper(self) -> bool {
+    self.intersects(OrthoContext::MID_UC)
+  }
+
+  /// Seen uppercase, where the position in the sentence is unknown.
+  #[inline(always)]
+  pub fn seen_unknown_position_upper(self) -> bool {
+    self.intersects(OrthoContext::UNK_UC)
+  }
+
+  /// Seen lowercase at the beginning of a sentence.
+  #[inline(always)]
+  pub fn seen_sentence_initial_lower(self) -> bool {
+    self.intersects(OrthoContext::BEG_LC)
+  }
+
+  /// Seen lowercase in the middle of a sentence.
+  #[inline(always)]
+  pub fn seen_sentence_internal_lower(self) -> bool {
+    self.intersects(OrthoContext::MID_LC)
+  }
+
+  /// Seen lowercase, where the position in the sentence is unknown.
+  #[inline(always)]
+  pub fn seen_unknown_position_lower(self) -> bool {
+    self.intersects(OrthoContext::UNK_LC)
+  }
+
+  /// Seen uppercase anywhere.
+  #[inline(always)]
+  pub fn seen_upper(self) -> bool {
+    self.intersects(OrthoContext::ORT_UC)
+  }
+
+  /// Seen lowercase anywhere.
+  #[inline(always)]
+  pub fn seen_lower(self) -> bool {
+    self.intersects(OrthoContext::ORT_LC)
+  }
+}
+
+impl From<u8> for OrthoContext {
+  #[inline(always)]
+  fn from(bits: u8) -> OrthoContext {
+    OrthoContext(bits)
+  }
+}
+
+impl From<OrthoContext> for u8 {
+  #[inline(always)]
+  fn from(ctxt: OrthoContext) -> u8 {
+    ctxt.0
+  }
+}
+
+impl BitOr for OrthoContext {
+  type Output = OrthoContext;
+
+  #[inline(always)]
+  fn bitor(self, other: OrthoContext) -> OrthoContext {
+    OrthoContext(self.0 | other.0)
+  }
+}
+
+impl BitOrAssign for OrthoContext {
+  #[inline(always)]
+  fn bitor_assign(&mut self, other: OrthoContext) {
+    self.0 |= other.0;
+  }
+}
+
+impl BitAnd for OrthoContext {
+  type Output = OrthoContext;
+
+  #[inline(always)]
+  fn bitand(self, other: OrthoContext) -> OrthoContext {
+    OrthoContext(self.0 & other.0)
+  }
+}
+
+/// Lists the names of the flags that are set, like `BEG_UC | MID_LC`.
+impl fmt::Debug for OrthoContext {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let flags = [
+      ("BEG_UC", BEG_UC),
+      ("MID_UC", MID_UC),
+      ("UNK_UC", UNK_UC),
+      ("BEG_LC", BEG_LC),
+      ("MID_LC", MID_LC),
+      ("UNK_LC", UNK_LC),
+    ];
+    let mut rest = self.0;
+    let mut first = true;
+
+    f.write_str("OrthoContext(")?;
+
+    for &(name, flag) in flags.iter().filter(|&&(_, flag)| self.0 & flag != 0) {
+      if !first {
+        f.write_str(" | ")?;
+      }
+
+      f.write_str(name)?;
+      rest &= !flag;
+      first = false;
+    }
+
+    match (rest, first) {
+      (0, true) => f.write_str("empty")?,
+      (0, false) => (),
+      (r, true) => write!(f, "{:#04x}", r)?,
+      (r, false) => write!(f, " | {:#04x}", r)?,
+    }
+
+    f.write_str(")")
+  }
+}
+
+#[test]
+fn ortho_context_test() {
+  let ctxt = OrthoContext::BEG_UC | OrthoContext::MID_LC;
+
+  assert!(ctxt.seen_upper() && ctxt.seen_lower());
+  assert!(!ctxt.seen_sentence_internal_upper());
+  assert!(!ctxt.contains(OrthoContext::ORT_LC));
+  assert_eq!(u8::from(ctxt), 0b0010_0010);
+  assert_eq!(format!("{:?}", ctxt), "OrthoContext(BEG_UC | MID_LC)");
+  assert_eq!(
+    format!("{:?}", OrthoContext::empty()),
+    "OrthoContext(empty)"
+  );
+  assert_eq!(
+    format!("{:?}", OrthoContext::from_bits(0b1000_0100)),
+    "OrthoContext(MID_UC | 0x80)"
+  );
+}
@@ -0,0 +1,186 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use metadata::ParamsSnapshot;
+use model::Model;
+use ortho::OrthoContext;
+use trainer::{Provenance, TrainingData};
+
+/// A model that layers additions on top of a shared model, without copying
+/// it. Lookups check both, so anything inserted into the overlay is known
+/// in addition to what the shared model knows. Nothing can be removed from
+/// the shared model.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{SentenceTokenizer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "It was sent via courier. Acct. Smith signed.";
+/// let data = TrainingData::english();
+/// let mut overlay = data.cow_overlay();
+///
+/// overlay.insert_abbrev("acct");
+///
+/// let sents: Vec<&str> = SentenceTokenizer::<Standard, _>::new(doc, &overlay).collect();
+///
+/// assert_eq!(sents, vec!["It was sent via courier.", "Acct. Smith signed."]);
+/// assert!(!data.contains_abbrev("acct"));
+/// # }
+/// ```
+pub struct Overlay<'a, D: 'a = TrainingData> {
+  base: &'a D,
+  layer: TrainingData,
+}
+
+impl<'a, D> Overlay<'a, D>
+where
+  D: Model,
+{
+  /// Creates a new, empty overlay on a model.
+  #[inline]
+  pub fn new(base: &'a D) -> Overlay<'a, D> {
+    Overlay {
+      base,
+      layer: TrainingData::new(),
+    }
+  }
+
+  /// The shared model.
+  #[inline(always)]
+  pub fn base(&self) -> &'a D {
+    self.base
+  }
+
+  /// What was inserted into the overlay.
+  #[inline(always)]
+  pub fn layer(&self) -> &TrainingData {
+    &self.layer
+  }
+
+  /// Consumes the overlay, and returns what was inserted into it.
+  #[inline(always)]
+  pub fn into_layer(self) -> TrainingData {
+    self.layer
+  }
+
+  /// Inserts an abbreviation. Returns false if it was already known.
+  #[inline]
+  pub fn insert_abbrev(&mut self, tok: &str) -> bool {
+    let tok = tok.to_lowercase();
+
+    !self.base.contains_abbrev(&tok)
+      && self
+        .layer
+        .insert_abbrev_with_provenance(&tok, Provenance::Inserted)
+  }
+
+  /// Inserts a word that commonly starts a sentence. Returns false if it was
+  /// already known.
+  #[inline]
+  pub fn insert_sentence_starter(&mut self, tok: &str) -> bool {
+    !self.base.contains_sentence_starter(tok) && self.layer.insert_sentence_starter(tok)
+  }
+
+  /// Inserts a pair of words that commonly appear together. Returns false if
+  /// it was already known.
+  #[inline]
+  pub fn insert_collocation(&mut self, left: &str, right: &str) -> bool {
+    !self.base.contains_collocation(left, right) && self.layer.insert_collocation(left, right)
+  }
+
+  /// Adds to the orthographic context of a word. Returns false if the
+  /// context was already known.
+  #[inline]
+  pub fn insert_orthographic_context(&mut self, tok: &str, ctxt: OrthoContext) -> bool {
+    if self.get_orthographic_context(tok).contains(ctxt) {
+      return false;
+    }
+
+    self.layer.insert_orthographic_context(tok, ctxt.bits());
+    true
+  }
+}
+
+impl<'a, D> Model for Overlay<'a, D>
+where
+  D: Model,
+{
+  #[inline]
+  fn contains_abbrev(&self, tok: &str) -> bool {
+    self.layer.contains_abbrev(tok) || self.base.contains_abbrev(tok)
+  }
+
+  #[inline]
+  fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    self.layer.contains_collocation(left, right) || self.base.contains_collocation(left, right)
+  }
+
+  #[inline]
+  fn contains_sentence_starter(&self, tok: &str) -> bool {
+    self.layer.contains_sentence_starter(tok) || self.base.contains_sentence_starter(tok)
+  }
+
+  #[inline]
+  fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    self.layer.get_orthographic_context(tok) | self.base.get_orthographic_context(tok)
+  }
+
+  #[inline]
+  fn allows_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    self.layer.allows_abbrev_context(abbrev, right)
+      && self.base.allows_abbrev_context(abbrev, right)
+  }
+
+  #[inline]
+  fn params(&self) -> Option<&ParamsSnapshot> {
+    self.base.params()
+  }
+}
+
+impl TrainingData {
+  /// Creates an overlay on the data, that new abbreviations, collocations,
+  /// and other data can be inserted into without changing, or copying the
+  /// data. See `Overlay`.
+  #[inline]
+  pub fn cow_overlay(&self) -> Overlay<'_, TrainingData> {
+    Overlay::new(self)
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn overlay_test() {
+  let data = TrainingData::english();
+  let mut overlay = data.cow_overlay();
+
+  assert!(!overlay.insert_abbrev("W.Va"));
+  assert!(overlay.insert_abbrev("Zzzz"));
+  assert!(overlay.contains_abbrev("zzzz"));
+  assert!(!overlay.insert_collocation("##number##", "corrections"));
+  assert!(overlay.insert_collocation("zzzz", "yyyy"));
+  assert!(overlay.insert_sentence_starter("zzzz"));
+  assert!(!overlay.insert_sentence_starter("among"));
+  assert!(!overlay.insert_orthographic_context("the", OrthoContext::BEG_UC));
+  assert!(overlay.insert_orthographic_context("zzzz", OrthoContext::MID_LC));
+  assert!(!overlay.insert_orthographic_context("zzzz", OrthoContext::MID_LC));
+  assert_eq!(
+    overlay.get_orthographic_context("zzzz"),
+    OrthoContext::MID_LC
+  );
+
+  let layer = overlay.into_layer();
+
+  assert_eq!(layer.abbrevs().collect::<Vec<_>>(), vec!["zzzz"]);
+  assert_eq!(layer.collocations().count(), 1);
+  assert_eq!(layer.orthographic_contexts().count(), 1);
+  assert!(!data.contains_abbrev("zzzz"));
+}
@@ -0,0 +1,372 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[cfg(feature = "rayon")]
+use rayon::iter::plumbing::UnindexedConsumer;
+#[cfg(feature = "rayon")]
+use rayon::iter::{ParallelBridge, ParallelIterator};
+
+use model::Model;
+use prelude::TokenizerParameters;
+use tokenizer::SentenceByteOffsetTokenizer;
+use trainer::TrainingData;
+
+/// Runs work on each sentence of a document on several threads, as the
+/// sentences are found. Created with
+/// `SentenceByteOffsetTokenizer::par_sentences`.
+///
+/// The document is segmented on the calling thread, and the byte offsets of
+/// each sentence are handed to the worker threads, so the model doesn't have
+/// to be shared between threads. Only a few sentences, and results are
+/// queued at a time, so the sentences of a large document are never all held
+/// in memory.
+///
+/// With the `rayon` feature, `ParSentences` is also a rayon
+/// `ParallelIterator` over the byte offsets of the sentences, that runs on
+/// the rayon thread pool instead, and shares the model between its threads.
+/// Like other bridged iterators, it doesn't keep the order of the sentences,
+/// but the sentences are ordered by their start offsets.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{SentenceByteOffsetTokenizer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "Mr. Smith went to Washington. He arrived at 5 p.m. sharp. It rained.";
+/// let data = TrainingData::english();
+/// let mut words = Vec::new();
+///
+/// SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+///   .par_sentences()
+///   .with_threads(2)
+///   .map_in_order(
+///     |(start, end)| doc[start..end].split_whitespace().count(),
+///     |n| words.push(n),
+///   );
+///
+/// assert_eq!(words, vec![5, 6, 2]);
+/// # }
+/// ```
+///
+/// With the `rayon` feature:
+///
+/// ```
+/// # #[cfg(feature = "rayon")]
+/// # extern crate rayon;
+/// # #[cfg(feature = "rayon")]
+/// # use rayon::prelude::*;
+/// #
+/// # #[cfg(all(feature = "english", feature = "rayon"))]
+/// # {
+/// # use punkt::{SentenceByteOffsetTokenizer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "Mr. Smith went to Washington. He arrived at 5 p.m. sharp. It rained.";
+/// let data = TrainingData::english();
+/// let words: usize = SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+///   .par_sentences()
+///   .map(|(start, end)| doc[start..end].split_whitespace().count())
+///   .sum();
+///
+/// assert_eq!(words, 13);
+/// # }
+/// ```
+pub struct ParSentences<'a, P, D: 'a = TrainingData> {
+  iter: SentenceByteOffsetTokenizer<'a, P, D>,
+  threads: usize,
+}
+
+impl<'a, P, D> SentenceByteOffsetTokenizer<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Runs work on the sentences on several threads. Uses one thread for
+  /// each available CPU by default.
+  #[inline]
+  pub fn par_sentences(self) -> ParSentences<'a, P, D> {
+    ParSentences {
+      iter: self,
+      threads: thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1),
+    }
+  }
+}
+
+impl<'a, P, D> ParSentences<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Sets the number of worker threads. At least one thread is used. The
+  /// `ParallelIterator` methods use the rayon thread pool instead.
+  #[inline(always)]
+  pub fn with_threads(mut self, threads: usize) -> ParSentences<'a, P, D> {
+    self.threads = threads;
+    self
+  }
+
+  /// Calls `f` with the byte offsets of each sentence on the worker threads,
+  /// and `g` with the results on the calling thread, in the order of the
+  /// sentences. A sentence is only handed out once the results of the
+  /// sentences a few places before it were passed to `g`, so the results are
+  /// never all held in memory. If `f`, or `g` panics, the panic is raised
+  /// once the other threads stop.
+  pub fn map_in_order<R, F, G>(self, f: F, mut g: G)
+  where
+    R: Send,
+    F: Fn((usize, usize)) -> R + Sync,
+    G: FnMut(R),
+  {
+    let threads = ::std::cmp::max(self.threads, 1);
+    let window = threads * 4;
+    let iter = self.iter;
+    let f = &f;
+    let mut panicked: Option<Box<dyn Any + Send>> = None;
+
+    thread::scope(|scope| {
+      // Both queues hold the whole window, so neither side ever waits on a
+      // full queue. The queues are dropped if `g` panics, which stops the
+      // workers.
+      let (jobs, queue) = mpsc::sync_channel::<(usize, (usize, usize))>(window);
+      let (done, results) = mpsc::sync_channel(window);
+      let queue = Arc::new(Mutex::new(queue));
+
+      for _ in 0..threads {
+        let queue = queue.clone();
+        let done = done.clone();
+
+        scope.spawn(move || loop {
+          let job = match queue.lock() {
+            Ok(queue) => queue.recv(),
+            Err(poisoned) => poisoned.into_inner().recv(),
+          };
+
+          match job {
+            Ok((i, span)) => {
+              let r = panic::catch_unwind(AssertUnwindSafe(|| f(span)));
+
+              if done.send((i, r)).is_err() {
+                return;
+              }
+            }
+            Err(_) => return,
+          }
+        });
+      }
+
+      drop(done);
+
+      let mut pending = BTreeMap::new();
+      let mut sent = 0;
+      let mut next = 0;
+      let mut stopped = false;
+
+      // Waits for a result, and passes the results that are next in order to
+      // `g`. Returns false once `f` panicked.
+      let mut receive = |next: &mut usize| {
+        match results.recv() {
+          Ok((i, Ok(r))) => {
+            pending.insert(i, r);
+          }
+          Ok((_, Err(payload))) => {
+            panicked = Some(payload);
+            return false;
+          }
+          Err(_) => return false,
+        }
+
+        while let Some(r) = pending.remove(next) {
+          g(r);
+          *next += 1;
+        }
+
+        true
+      };
+
+      'sentences: for span in iter {
+        while sent - next >= window {
+          if !receive(&mut next) {
+            stopped = true;
+            break 'sentences;
+          }
+        }
+
+        if jobs.send((sent, span)).is_err() {
+          break;
+        }
+
+        sent += 1;
+      }
+
+      drop(jobs);
+
+      while !stopped && next < sent {
+        stopped = !receive(&mut next);
+      }
+    });
+
+    if let Some(payload) = panicked {
+      panic::resume_unwind(payload);
+    }
+  }
+
+  /// Calls `f` with the byte offsets of each sentence.
+  #[inline]
+  pub fn for_each<F>(self, f: F)
+  where
+    F: Fn((usize, usize)) + Sync,
+  {
+    self.map_in_order(f, |()| ());
+  }
+}
+
+/// Bridges the sentences to the rayon thread pool. Requires the `rayon`
+/// feature.
+#[cfg(feature = "rayon")]
+impl<'a, P, D> ParallelIterator for ParSentences<'a, P, D>
+where
+  P: TokenizerParameters + Send,
+  D: Model + Sync,
+{
+  type Item = (usize, usize);
+
+  fn drive_unindexed<C>(self, consumer: C) -> C::Result
+  where
+    C: UnindexedConsumer<(usize, usize)>,
+  {
+    self.iter.par_bridge().drive_unindexed(consumer)
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn par_sentences_test() {
+  use prelude::Standard;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  let data = TrainingData::english();
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let expected: Vec<(usize, usize)> =
+    SentenceByteOffsetTokenizer::<Standard>::new(doc, &data).collect();
+
+  for &threads in [0, 1, 3, 8].iter() {
+    let mut spans = Vec::new();
+
+    SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+      .par_sentences()
+      .with_threads(threads)
+      .map_in_order(|span| span, |span| spans.push(span));
+
+    assert_eq!(spans, expected);
+  }
+
+  let bytes = AtomicUsize::new(0);
+
+  SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+    .par_sentences()
+    .for_each(|(start, end)| {
+      bytes.fetch_add(end - start, Ordering::SeqCst);
+    });
+
+  assert_eq!(
+    bytes.load(Ordering::SeqCst),
+    expected.iter().map(|&(s, e)| e - s).sum::<usize>()
+  );
+
+  // Only a few sentences are handed out ahead of the results that were passed
+  // to `g`, even if the first sentence is the slowest.
+  let started = AtomicUsize::new(0);
+  let mut received = 0;
+  let mut most_ahead = 0;
+
+  SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+    .par_sentences()
+    .with_threads(2)
+    .map_in_order(
+      |(start, _)| {
+        if start == 0 {
+          ::std::thread::sleep(::std::time::Duration::from_millis(50));
+        }
+
+        started.fetch_add(1, Ordering::SeqCst);
+      },
+      |()| {
+        most_ahead = ::std::cmp::max(most_ahead, started.load(Ordering::SeqCst) - received);
+        received += 1;
+      },
+    );
+
+  assert_eq!(received, expected.len());
+  assert!(expected.len() > 8 && most_ahead <= 8);
+
+  let mut calls = 0;
+
+  SentenceByteOffsetTokenizer::<Standard>::new("", &data)
+    .par_sentences()
+    .map_in_order(|span| span, |_| calls += 1);
+
+  assert_eq!(calls, 0);
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn par_sentences_panic_test() {
+  use prelude::Standard;
+
+  let data = TrainingData::english();
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let result = panic::catch_unwind(AssertUnwindSafe(|| {
+    SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+      .par_sentences()
+      .with_threads(3)
+      .for_each(|(start, _)| {
+        if start > 1000 {
+          panic!("stop");
+        }
+      });
+  }));
+
+  assert!(result.is_err());
+}
+
+#[cfg(all(feature = "english", feature = "rayon"))]
+#[test]
+fn par_sentences_rayon_test() {
+  use prelude::Standard;
+
+  let data = TrainingData::english();
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let expected: Vec<(usize, usize)> =
+    SentenceByteOffsetTokenizer::<Standard>::new(doc, &data).collect();
+  let mut spans: Vec<(usize, usize)> = SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+    .par_sentences()
+    .collect();
+
+  spans.sort();
+
+  assert_eq!(spans, expected);
+  assert_eq!(
+    SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+      .par_sentences()
+      .map(|(start, end)| end - start)
+      .sum::<usize>(),
+    expected.iter().map(|&(s, e)| e - s).sum::<usize>()
+  );
+}
@@ -8,6 +8,8 @@
 
 use phf::{self, phf_map, phf_set};
 
+use token::is_str_numeric;
+
 /// Type for character sets.
 pub type Set<T> = phf::Set<T>;
 
@@ -21,6 +23,19 @@ pub trait DefinesSentenceEndings {
   fn is_sentence_ending(c: &char) -> bool {
     Self::SENTENCE_ENDINGS.contains(c)
   }
+
+  /// The set of characters that can follow the period of an abbreviation,
+  /// in the same token (`Nr.»`). The token is still matched against the
+  /// known abbreviations. A sentence ending followed by these characters,
+  /// and whitespace can end a sentence (`«Ja.» Dann`), unless the token is an
+  /// abbreviation. Empty by default.
+  const ABBREV_TRAILING_CHARS: &'static Set<char> = &phf_set![];
+
+  /// Checks if a character can follow the period of an abbreviation.
+  #[inline]
+  fn is_abbrev_trailing_char(c: &char) -> bool {
+    Self::ABBREV_TRAILING_CHARS.contains(c)
+  }
 }
 
 /// Defines a set of punctuation that can occur within a word.
@@ -39,15 +54,34 @@ pub trait DefinesInternalPunctuation {
 /// Defines a set of characters that can not occur inside of a word.
 pub trait DefinesNonWordCharacters {
   /// The set of characters that can not occur inside of a word.
-  const NONWORD_CHARS: &'static Set<char> = &phf_set![
-    '?', '!', ')', '"', ';', '}', ']', '*', ':', '@', '\'', '(', '{', '['
-  ];
+  const NONWORD_CHARS: &'static Set<char> =
+    &phf_set!['?', '!', ')', '"', ';', '}', ']', '*', ':', '@', '\'', '(', '{', '['];
 
   /// Checks if a character is one that can not occur inside of a word.
   #[inline]
   fn is_nonword_char(c: &char) -> bool {
     Self::NONWORD_CHARS.contains(c)
   }
+
+  /// The set of characters that can occur inside of a word (`1,000`), but
+  /// are not part of the word when they end it (`one, two`). Characters that
+  /// can not occur inside of a word always end it.
+  const TRAILING_SEPARATORS: &'static Set<char> = &phf_set![','];
+
+  /// Checks if a character is trimmed from the end of a word.
+  #[inline]
+  fn is_trailing_separator(c: &char) -> bool {
+    Self::TRAILING_SEPARATORS.contains(c)
+  }
+
+  /// The maximum number of bytes scanned after a non-word character that
+  /// follows a sentence ending (`.)`), to find out if the characters after it
+  /// are part of the same token. The scan ends at the next whitespace, so it
+  /// can cover most of a minified document, that has no spaces. When the
+  /// limit is reached, the token is assumed to end at the non-word character.
+  /// A lower limit bounds the time spent on such text, but can split a long
+  /// token that has sentence endings inside of it.
+  const MAX_LOOKAHEAD: usize = 4096;
 }
 
 /// Defines punctuation that can occur within a sentence.
@@ -65,9 +99,8 @@ pub trait DefinesPunctuation {
 /// Defines a set of a characters that can not start a word.
 pub trait DefinesNonPrefixCharacters {
   /// The set of characters that can not start a word.
-  const NONPREFIX_CHARS: &'static Set<char> = &phf_set![
-    '(', '"', '`', '{', '[', ':', ';', '&', '#', '*', '@', ')', '}', ']', '-', ','
-  ];
+  const NONPREFIX_CHARS: &'static Set<char> =
+    &phf_set!['(', '"', '`', '{', '[', ':', ';', '&', '#', '*', '@', ')', '}', ']', '-', ','];
 
   /// Checks if a character can start a word.
   #[inline]
@@ -76,6 +109,161 @@ pub trait DefinesNonPrefixCharacters {
   }
 }
 
+/// Defines how words are normalized into the types stored in a model.
+pub trait DefinesTypeNormalization {
+  /// Returns a special type for a word, instead of the word itself, with a
+  /// period appended to it. The word can end with a period. By default,
+  /// numbers are mapped to `##number##.`, like NLTK. Types are the keys of a
+  /// model, so a model should be used with the normalization that it was
+  /// trained with.
+  #[inline]
+  fn special_type_with_period(word: &str) -> Option<&'static str> {
+    if is_str_numeric(word) {
+      Some("##number##.")
+    } else {
+      None
+    }
+  }
+
+  /// Appends the case folded form of a word to the type of a token. By
+  /// default, each character is lowercased with `char::to_lowercase`. Scripts
+  /// where the model was trained with different conventions can provide their
+  /// own folding, like ICU's. A period at the end of the word has to be kept.
+  #[inline]
+  fn fold_case(word: &str, buf: &mut String) {
+    buf.extend(word.chars().flat_map(char::to_lowercase))
+  }
+
+  /// The set of invisible characters that are left out of the types of words,
+  /// like byte-order marks (`\u{feff}`), zero-width spaces (`\u{200b}`), and
+  /// joiners (`\u{200c}`, `\u{200d}`), and soft hyphens (`\u{ad}`), so
+  /// `co\u{ad}operate` matches `cooperate` in a model. The characters stay in
+  /// the document, so offsets are unchanged. Empty by default.
+  const IGNORABLE_CHARS: &'static Set<char> = &phf_set![];
+
+  /// Checks if a character is left out of the types of words.
+  #[inline]
+  fn is_ignorable_char(c: &char) -> bool {
+    // Looking up a character in an empty `phf` set panics.
+    !Self::IGNORABLE_CHARS.is_empty() && Self::IGNORABLE_CHARS.contains(c)
+  }
+}
+
+/// Defines the multi-character tokens, like ellipses (`...`, `. . .`), and
+/// dashes (`--`), that are tokenized as a single word.
+pub trait DefinesMultiChars {
+  /// The set of characters that can start a multi-character token.
+  const MULTI_CHAR_STARTS: &'static Set<char> = &phf_set!['.', '-'];
+
+  /// Checks if a character can start a multi-character token.
+  #[inline]
+  fn is_multi_char_start(c: &char) -> bool {
+    Self::MULTI_CHAR_STARTS.contains(c)
+  }
+
+  /// Returns the multi-character token at the start of a slice of a document,
+  /// if there is one. The slice always starts with one of the characters in
+  /// `MULTI_CHAR_STARTS`. By default, runs of dashes, and runs of periods that
+  /// can be separated by single spaces are matched. No-break spaces
+  /// (`\u{a0}`, `\u{202f}`), like in French typography, count as spaces.
+  fn multi_char(doc: &str) -> Option<&str> {
+    let is_space = |c: char| c == ' ' || c == '\u{a0}' || c == '\u{202f}';
+    let mut iter = doc.char_indices();
+    let mut prv = match iter.next() {
+      Some((_, c)) => c,
+      None => return None,
+    };
+    let first_len = prv.len_utf8();
+    let mut end = first_len;
+
+    for (i, c) in iter {
+      match c {
+        // Hit a dash, and our previous was a dash --
+        // continue matching dashes.
+        '-' if prv == '-' => (),
+        // Hit a period, and our previous was a period or
+        // space. This is valid, skip.
+        '.' if prv == '.' || is_space(prv) => (),
+        // Hit a space, and our previous was a period.
+        // Could be a ellipsis -- continue.
+        _ if is_space(c) && prv == '.' => (),
+        // Hit a non-multi-char character. Break, and check
+        // if our word was long enough.
+        _ => break,
+      }
+
+      prv = c;
+      end = i + c.len_utf8();
+    }
+
+    // If the previous was a space, truncate it. This also applies when
+    // the end of the document was reached.
+    if is_space(prv) {
+      end -= prv.len_utf8();
+    }
+
+    if end > first_len {
+      Some(&doc[..end])
+    } else {
+      None
+    }
+  }
+
+  /// Returns the length in bytes of a protected token at the start of a
+  /// slice of a document, if there is one. A protected token is returned by
+  /// the word tokenizer as it is, without being split. The sentence endings
+  /// inside of it never end a sentence, but one at its end does, like the
+  /// final period of a word, so a matcher that takes in the period after a
+  /// code at the end of a sentence doesn't lose the sentence break. Useful
+  /// for product codes, like `ABC-123.456.X`. Called where each token can
+  /// start, and matches none by default.
+  ///
+  /// The sentence tokenizer only looks for protected tokens near sentence
+  /// endings, starting after whitespace, so protected tokens shouldn't
+  /// contain whitespace.
+  ///
+  /// With the `regex` feature, `ProtectedTokens` defines protected tokens
+  /// with a set of regular expressions:
+  ///
+  /// ```
+  /// # #[cfg(feature = "regex")]
+  /// # {
+  /// # use std::sync::OnceLock;
+  /// # use punkt::params::*;
+  /// # use punkt::{ProtectedTokens, WordTokenizer};
+  /// #
+  /// # struct MyParams;
+  /// #
+  /// # impl DefinesInternalPunctuation for MyParams {}
+  /// # impl DefinesNonPrefixCharacters for MyParams {}
+  /// # impl DefinesNonWordCharacters for MyParams {}
+  /// # impl DefinesPunctuation for MyParams {}
+  /// # impl DefinesSentenceEndings for MyParams {}
+  /// # impl DefinesTypeNormalization for MyParams {}
+  /// impl DefinesMultiChars for MyParams {
+  ///   fn protected_token(doc: &str) -> Option<usize> {
+  ///     static CODES: OnceLock<ProtectedTokens> = OnceLock::new();
+  ///
+  ///     CODES
+  ///       .get_or_init(|| ProtectedTokens::new(&[r"[A-Z]{3}-\d+(\.\d+)*\.[A-Z]"]).unwrap())
+  ///       .find(doc)
+  ///   }
+  /// }
+  ///
+  /// let code = WordTokenizer::<MyParams>::new("See ABC-123.456.X now.")
+  ///   .nth(1)
+  ///   .unwrap();
+  ///
+  /// assert!(code.is_protected());
+  /// assert_eq!(code.tok(), "abc-123.456.x");
+  /// # }
+  /// ```
+  #[inline(always)]
+  fn protected_token(_doc: &str) -> Option<usize> {
+    None
+  }
+}
+
 /// Configurable parameters for a trainer.
 pub trait TrainerParameters: DefinesSentenceEndings + DefinesInternalPunctuation {
   /// Lower bound score for a token to be considered an abbreviation.
@@ -104,36 +292,335 @@ pub trait TrainerParameters: DefinesSentenceEndings + DefinesInternalPunctuation
   /// Minimum number of times a bigram appears in order to be considered a
   /// collocation.
   const COLLOCATION_FREQUENCY_LOWER_BOUND: f64 = 1f64;
+
+  /// Minimum number of characters, not counting periods, for a token to be
+  /// learned as an abbreviation. Raising it keeps noisy corpora from
+  /// producing single letter abbreviations. Unbounded by default, like NLTK.
+  const MIN_ABBREV_LEN: usize = 0;
+
+  /// Maximum number of characters, not counting periods, for a token to be
+  /// learned as an abbreviation. Unbounded by default, like NLTK.
+  const MAX_ABBREV_LEN: usize = ::std::usize::MAX;
+
+  /// Scores, and lower bounds are rounded to a multiple of this before they
+  /// are compared, and a score that rounds to the same multiple as a bound
+  /// ties with it, and is accepted. Scores are computed from integer counts,
+  /// so the order counts are accumulated in doesn't change them, but `ln`,
+  /// and `exp` can round differently on different platforms. Rounding keeps
+  /// a score that only differs by that from being decided differently,
+  /// unless it is close to the edge between two multiples. The
+  /// `fixed-scores` feature computes the same scores on every platform.
+  const SCORE_EPSILON: f64 = 1e-9;
+}
+
+/// How a sentence tokenizer decides if a period after a number (`2.0.`,
+/// `1999.`) ends a sentence. A period at the end of a paragraph, or of the
+/// document always ends a sentence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberPeriods {
+  /// The period ends a sentence, unless the orthographic context of the next
+  /// word shows that it doesn't start sentences, like NLTK. Depends on what
+  /// the model saw, so `2.0. then` can be split with one model, and not with
+  /// another.
+  Orthographic,
+  /// The period ends a sentence, unless the next word is lowercase. Doesn't
+  /// depend on the model.
+  Case,
+  /// The period only ends a sentence at the end of a paragraph. For text
+  /// with ordinal numbers (`am 3. Oktober`).
+  Paragraph,
+}
+
+/// Configurable parameters for a sentence tokenizer.
+pub trait TokenizerParameters:
+  DefinesMultiChars
+  + DefinesNonPrefixCharacters
+  + DefinesNonWordCharacters
+  + DefinesPunctuation
+  + DefinesSentenceEndings
+  + DefinesTypeNormalization
+{
+  /// Treats a sentence ending inside of a quotation as part of the same
+  /// sentence, if the quotation is followed by a lowercase speech attribution
+  /// (`"Stop!" she said.`). Words that are known sentence starters are never
+  /// considered an attribution.
+  const DIALOGUE_AWARE: bool = false;
+
+  /// Moves closing quotes, closing brackets, and sentence endings that
+  /// follow a sentence break to the end of the sentence before it, like
+  /// NLTK's boundary realignment. `"Stop!" He left.` is split after the
+  /// closing quote, and `Done.)... Next.` after the ellipsis. Only runs that
+  /// are followed by whitespace are moved, and the trailing whitespace of the
+  /// document is left out of the last sentence, like current versions of
+  /// NLTK, unless the document only has whitespace. Enabled by every preset
+  /// except `Standard`, which keeps the sentences of earlier versions of this
+  /// crate.
+  const REALIGN_BOUNDARIES: bool = false;
+
+  /// How a period after a number is decided.
+  const NUMBER_PERIODS: NumberPeriods = NumberPeriods::Orthographic;
+
+  /// The set of characters that can close a quotation.
+  const CLOSING_QUOTES: &'static Set<char> = &phf_set!['"', '\'', '\u{bb}', '\u{2019}', '\u{201d}'];
+
+  /// Checks if a character can close a quotation.
+  #[inline]
+  fn is_closing_quote(c: &char) -> bool {
+    Self::CLOSING_QUOTES.contains(c)
+  }
+
+  /// Pairs of opening, and closing brackets. A sentence break between an
+  /// opening bracket, and its closing bracket is ignored, so inline citations
+  /// (`(see Smith et al. 2020. p. 4)`), and quotations (`「...」`) aren't
+  /// split. A bracket that is never closed doesn't suppress breaks. Empty by
+  /// default.
+  const BRACKET_PAIRS: &'static [(char, char)] = &[];
+
+  /// The number of bytes after a sentence break, that the closing bracket of
+  /// a bracket that is open at the break is looked for in. A bracket that
+  /// isn't closed within them doesn't suppress the break.
+  const MAX_BRACKET_LEN: usize = 2000;
+
+  /// The set of characters that end a sentence in scripts that are written
+  /// without spaces between words, or sentences, like Chinese, and Japanese
+  /// (`\u{3002}`, `\u{ff01}`, `\u{ff1f}`). Punkt finds words by whitespace,
+  /// so these never take part in its decisions. Instead, a sentence found by
+  /// Punkt is split after each of them, along with any closing quotes, and
+  /// brackets that follow, unless it's the end of the sentence already. This
+  /// lets CJK passages embedded in a document be split, while the rest of the
+  /// document is segmented as usual. Empty by default.
+  const UNSPACED_SENTENCE_ENDINGS: &'static Set<char> = &phf_set![];
+
+  /// The maximum length of a sentence, in bytes. Longer sentences are split
+  /// at the last paragraph break (a blank line) within the limit, or else at
+  /// the last whitespace, or else at the limit. Text without any sentence
+  /// endings, which is common in scraped data, is otherwise returned as a
+  /// single sentence. Unlimited by default.
+  const MAX_SENTENCE_LEN: usize = ::std::usize::MAX;
+
+  /// Checks if a character ends a sentence without being followed by
+  /// whitespace.
+  #[inline]
+  fn is_unspaced_sentence_ending(c: &char) -> bool {
+    // Looking up a character in an empty `phf` set panics.
+    !Self::UNSPACED_SENTENCE_ENDINGS.is_empty() && Self::UNSPACED_SENTENCE_ENDINGS.contains(c)
+  }
+}
+
+/// Configurable line-based heuristics used to find headings and list items
+/// before sentence tokenization.
+pub trait StructureParameters: TokenizerParameters {
+  /// The set of characters that mark an item in a bulleted list, when they
+  /// are followed by whitespace.
+  const LIST_BULLETS: &'static Set<char> =
+    &phf_set!['-', '*', '+', '\u{2022}', '\u{2023}', '\u{2043}', '\u{25e6}'];
+
+  /// Detect numbered and lettered list items (`1.`, `2)`, `a)`).
+  const DETECT_NUMBERED_LISTS: bool = true;
+
+  /// Detect headings. Lines starting with a `#` are always headings. Other
+  /// lines are headings if they start a paragraph, are short, contain no
+  /// sentence endings, do not end with punctuation, and are followed by a
+  /// blank line or a capitalized line.
+  const DETECT_HEADINGS: bool = true;
+
+  /// Maximum number of characters in a heading without a `#` marker.
+  const MAX_HEADING_LEN: usize = 72;
+
+  /// Checks if a character is a list bullet.
+  #[inline]
+  fn is_list_bullet(c: &char) -> bool {
+    Self::LIST_BULLETS.contains(c)
+  }
 }
 
 /// Standard settings for all tokenizers, and trainers.
 pub struct Standard;
 
 impl DefinesInternalPunctuation for Standard {}
+impl DefinesMultiChars for Standard {}
 impl DefinesNonPrefixCharacters for Standard {}
 impl DefinesNonWordCharacters for Standard {}
 impl DefinesPunctuation for Standard {}
 impl DefinesSentenceEndings for Standard {}
+impl DefinesTypeNormalization for Standard {}
+impl StructureParameters for Standard {}
+impl TokenizerParameters for Standard {}
 impl TrainerParameters for Standard {}
 
-pub type OrthographicContext = u8;
+/// Settings that favor fewer false sentence breaks over finding every
+/// sentence break. More tokens are considered abbreviations, and
+/// collocations, fewer are considered sentence starters, and quoted
+/// dialogue is kept with its attribution.
+pub struct Precise;
 
-#[derive(PartialEq, Eq)]
-pub enum OrthographyPosition {
-  Initial,
-  Internal,
-  Unknown,
+impl DefinesInternalPunctuation for Precise {}
+impl DefinesMultiChars for Precise {}
+impl DefinesNonPrefixCharacters for Precise {}
+impl DefinesNonWordCharacters for Precise {}
+impl DefinesPunctuation for Precise {}
+impl DefinesSentenceEndings for Precise {}
+impl DefinesTypeNormalization for Precise {}
+impl StructureParameters for Precise {}
+impl TokenizerParameters for Precise {
+  const DIALOGUE_AWARE: bool = true;
+  const REALIGN_BOUNDARIES: bool = true;
+}
+impl TrainerParameters for Precise {
+  const ABBREV_LOWER_BOUND: f64 = 0.2;
+  const COLLOCATION_LOWER_BOUND: f64 = 5f64;
+  const SENTENCE_STARTER_LOWER_BOUND: f64 = 50f64;
+  const INCLUDE_ABBREV_COLLOCATIONS: bool = true;
 }
 
-impl OrthographyPosition {
-  pub fn as_byte(&self) -> u8 {
-    match *self {
-      OrthographyPosition::Initial => 0b01000000,
-      OrthographyPosition::Internal => 0b00100000,
-      OrthographyPosition::Unknown => 0b01100000,
-    }
-  }
+/// Settings that favor finding every sentence break over avoiding false
+/// ones. Fewer tokens are considered abbreviations, and collocations, and
+/// more are considered sentence starters.
+pub struct Aggressive;
+
+impl DefinesInternalPunctuation for Aggressive {}
+impl DefinesMultiChars for Aggressive {}
+impl DefinesNonPrefixCharacters for Aggressive {}
+impl DefinesNonWordCharacters for Aggressive {}
+impl DefinesPunctuation for Aggressive {}
+impl DefinesSentenceEndings for Aggressive {}
+impl DefinesTypeNormalization for Aggressive {}
+impl StructureParameters for Aggressive {}
+impl TokenizerParameters for Aggressive {
+  const REALIGN_BOUNDARIES: bool = true;
+}
+impl TrainerParameters for Aggressive {
+  const ABBREV_LOWER_BOUND: f64 = 0.5;
+  const COLLOCATION_LOWER_BOUND: f64 = 10f64;
+  const SENTENCE_STARTER_LOWER_BOUND: f64 = 15f64;
+}
+
+/// Settings for legal text, which is dense with abbreviations (`Inc.`,
+/// `v.`, `No.`), that also appear without a period, and citations that
+/// abbreviations are followed by (`U.S. Const.`), and section signs.
+pub struct Legal;
+
+impl DefinesInternalPunctuation for Legal {
+  const INTERNAL_PUNCTUATION: &'static Set<char> =
+    &phf_set![',', ':', ';', '\u{2014}', '\u{a7}', '\u{b6}'];
+}
+impl DefinesMultiChars for Legal {}
+impl DefinesNonPrefixCharacters for Legal {}
+impl DefinesNonWordCharacters for Legal {}
+impl DefinesPunctuation for Legal {}
+impl DefinesSentenceEndings for Legal {}
+impl DefinesTypeNormalization for Legal {}
+impl StructureParameters for Legal {}
+impl TokenizerParameters for Legal {
+  const DIALOGUE_AWARE: bool = true;
+  const REALIGN_BOUNDARIES: bool = true;
+}
+impl TrainerParameters for Legal {
+  const ABBREV_LOWER_BOUND: f64 = 0.2;
+  const IGNORE_ABBREV_PENALTY: bool = true;
+  const COLLOCATION_LOWER_BOUND: f64 = 5f64;
+  const INCLUDE_ABBREV_COLLOCATIONS: bool = true;
+}
+
+/// Settings for biomedical text, which is dense with abbreviations
+/// (`et al.`, `i.v.`), abbreviated species names (`E. coli`), and chemical
+/// names with brackets inside of them (`2-(4-chlorophenyl)ethanol`).
+pub struct Biomedical;
+
+impl DefinesInternalPunctuation for Biomedical {}
+impl DefinesMultiChars for Biomedical {}
+impl DefinesNonPrefixCharacters for Biomedical {}
+impl DefinesNonWordCharacters for Biomedical {
+  const NONWORD_CHARS: &'static Set<char> = &phf_set!['?', '!', '"', ';', '}', '*', ':', '@', '\''];
+}
+impl DefinesPunctuation for Biomedical {}
+impl DefinesSentenceEndings for Biomedical {}
+impl DefinesTypeNormalization for Biomedical {}
+impl StructureParameters for Biomedical {}
+impl TokenizerParameters for Biomedical {
+  const REALIGN_BOUNDARIES: bool = true;
+}
+impl TrainerParameters for Biomedical {
+  const ABBREV_LOWER_BOUND: f64 = 0.2;
+  const IGNORE_ABBREV_PENALTY: bool = true;
+  const COLLOCATION_LOWER_BOUND: f64 = 5f64;
+  const INCLUDE_ABBREV_COLLOCATIONS: bool = true;
+}
+
+/// Settings for informal web text. Ellipsis characters (`\u{2026}`), and
+/// interrobangs end sentences, mentions (`@user`), and hashtags (`#tag`) are
+/// words, and quoted dialogue is kept with its attribution.
+pub struct Web;
+
+impl DefinesInternalPunctuation for Web {}
+impl DefinesMultiChars for Web {}
+impl DefinesNonPrefixCharacters for Web {
+  const NONPREFIX_CHARS: &'static Set<char> =
+    &phf_set!['(', '"', '`', '{', '[', ':', ';', '&', '*', ')', '}', ']', '-', ','];
+}
+impl DefinesNonWordCharacters for Web {
+  const NONWORD_CHARS: &'static Set<char> = &phf_set![
+    '?', '!', ')', '"', ';', '}', ']', '*', ':', '\'', '(', '{', '[', '\u{2026}', '\u{203d}'
+  ];
+}
+impl DefinesPunctuation for Web {
+  const PUNCTUATION: &'static Set<char> =
+    &phf_set![';', ':', ',', '.', '!', '?', '\u{2026}', '\u{203d}'];
+}
+impl DefinesSentenceEndings for Web {
+  const SENTENCE_ENDINGS: &'static Set<char> = &phf_set!['.', '?', '!', '\u{2026}', '\u{203d}'];
+}
+impl DefinesTypeNormalization for Web {}
+impl StructureParameters for Web {}
+impl TokenizerParameters for Web {
+  const DIALOGUE_AWARE: bool = true;
+  const REALIGN_BOUNDARIES: bool = true;
 }
+impl TrainerParameters for Web {}
+
+/// Settings for right-to-left scripts, like Arabic, Persian, Urdu, and
+/// Hebrew. The Arabic question mark (`\u{61f}`), and the Urdu full stop
+/// (`\u{6d4}`) end sentences, the Arabic comma (`\u{60c}`), and semicolon
+/// (`\u{61b}`) are punctuation, and directional marks (`\u{200e}`,
+/// `\u{200f}`, `\u{61c}`) are not part of words, so they don't hide the period
+/// before them. These scripts have no case, so the orthographic heuristic never
+/// decides a break, and abbreviations have to be known to the model, like the
+/// ones in `TrainingData::arabic()`.
+pub struct Rtl;
+
+impl DefinesInternalPunctuation for Rtl {
+  const INTERNAL_PUNCTUATION: &'static Set<char> =
+    &phf_set![',', ':', ';', '\u{2014}', '\u{60c}', '\u{61b}'];
+}
+impl DefinesMultiChars for Rtl {}
+impl DefinesNonPrefixCharacters for Rtl {
+  const NONPREFIX_CHARS: &'static Set<char> = &phf_set![
+    '(', '"', '`', '{', '[', ':', ';', '&', '#', '*', '@', ')', '}', ']', '-', ',', '\u{60c}',
+    '\u{61b}'
+  ];
+}
+impl DefinesNonWordCharacters for Rtl {
+  const NONWORD_CHARS: &'static Set<char> = &phf_set![
+    '?', '!', ')', '"', ';', '}', ']', '*', ':', '@', '\'', '(', '{', '[', '\u{61f}', '\u{6d4}',
+    '\u{61b}', '\u{200e}', '\u{200f}', '\u{61c}'
+  ];
+  const TRAILING_SEPARATORS: &'static Set<char> = &phf_set![',', '\u{60c}'];
+}
+impl DefinesPunctuation for Rtl {
+  const PUNCTUATION: &'static Set<char> =
+    &phf_set![';', ':', ',', '.', '!', '?', '\u{60c}', '\u{61b}', '\u{61f}', '\u{6d4}'];
+}
+impl DefinesSentenceEndings for Rtl {
+  const SENTENCE_ENDINGS: &'static Set<char> = &phf_set!['.', '?', '!', '\u{61f}', '\u{6d4}'];
+}
+impl DefinesTypeNormalization for Rtl {}
+impl StructureParameters for Rtl {}
+impl TokenizerParameters for Rtl {
+  const REALIGN_BOUNDARIES: bool = true;
+}
+impl TrainerParameters for Rtl {}
+
+pub type OrthographicContext = u8;
 
 pub const BEG_UC: OrthographicContext = 0b00000010;
 pub const MID_UC: OrthographicContext = 0b00000100;
@@ -156,20 +643,3 @@ pub static ORTHO_MAP: phf::Map<u8, OrthographicContext> = phf_map! {
   b'!' => MID_LC, // 33
   b'a' => UNK_LC  // 97
 };
-
-pub enum LetterCase {
-  Upper,
-  Lower,
-  Unknown,
-}
-
-impl LetterCase {
-  #[inline(always)]
-  pub fn as_byte(&self) -> u8 {
-    match *self {
-      LetterCase::Upper => 0b00000010,
-      LetterCase::Lower => 0b00000001,
-      LetterCase::Unknown => 0b00000011,
-    }
-  }
-}
@@ -0,0 +1,243 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature = "tokenizers")]
+use tokenizers::tokenizer::normalizer::Range;
+#[cfg(feature = "tokenizers")]
+use tokenizers::{PreTokenizedString, PreTokenizer};
+
+use model::Model;
+#[cfg(feature = "tokenizers")]
+use prelude::Standard;
+use prelude::TokenizerParameters;
+use segmenter::{Segmenter, SentenceSegmenter};
+use tokenizer::WordTokenizer;
+#[cfg(any(all(test, feature = "english"), feature = "tokenizers"))]
+use trainer::TrainingData;
+
+impl<P, D> Segmenter<P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Splits text into sentences. Each sentence is returned along with its
+  /// byte offsets in the text, in the shape of the splits made by the
+  /// pre-tokenizers of the Hugging Face `tokenizers` crate.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::{Segmenter, TrainingData};
+  /// #
+  /// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  ///
+  /// assert_eq!(
+  ///   segmenter.pre_tokenize("The cat sat down. It was happy."),
+  ///   vec![("The cat sat down.", (0, 17)), ("It was happy.", (18, 31))]
+  /// );
+  /// # }
+  /// ```
+  ///
+  /// With the `tokenizers` feature, `PunktPreTokenizer` plugs these splits
+  /// into a `tokenizers` pipeline.
+  pub fn pre_tokenize<'a>(&self, text: &'a str) -> Vec<(&'a str, (usize, usize))> {
+    self
+      .segment_spans(text)
+      .into_iter()
+      .map(|r| (&text[r.clone()], (r.start, r.end)))
+      .collect()
+  }
+
+  /// Splits text into the word tokens used by Punkt. Each token is returned
+  /// along with its byte offsets in the text. Whitespace is dropped, and
+  /// punctuation that isn't part of a word is a token of its own.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::{Segmenter, TrainingData};
+  /// #
+  /// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  ///
+  /// assert_eq!(
+  ///   segmenter.pre_tokenize_words("Hi (Mr. Lee)."),
+  ///   vec![
+  ///     ("Hi", (0, 2)),
+  ///     ("(", (3, 4)),
+  ///     ("Mr.", (4, 7)),
+  ///     ("Lee", (8, 11)),
+  ///     (")", (11, 12)),
+  ///     (".", (12, 13)),
+  ///   ]
+  /// );
+  /// # }
+  /// ```
+  pub fn pre_tokenize_words<'a>(&self, text: &'a str) -> Vec<(&'a str, (usize, usize))> {
+    let mut words = WordTokenizer::<P>::new(text);
+    let mut splits = Vec::new();
+
+    while words.next().is_some() {
+      let (start, end) = words.last_span();
+
+      splits.push((&text[start..end], (start, end)));
+    }
+
+    splits
+  }
+}
+
+/// A pre-tokenizer for the Hugging Face `tokenizers` crate, that splits text
+/// into sentences, or into the word tokens used by Punkt. The splits keep
+/// their offsets in the original text, through any normalization that was
+/// done before. Requires the `tokenizers` feature.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate punkt;
+/// # extern crate tokenizers;
+/// # use punkt::{PunktPreTokenizer, Segmenter, TrainingData};
+/// # use tokenizers::{OffsetReferential, OffsetType, PreTokenizedString, PreTokenizer};
+/// #
+/// # fn main() {
+/// # #[cfg(feature = "english")]
+/// # {
+/// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+/// let pre_tokenizer = PunktPreTokenizer::sentences(segmenter);
+/// let mut s = PreTokenizedString::from("The cat sat down. It was happy.");
+///
+/// pre_tokenizer.pre_tokenize(&mut s).unwrap();
+///
+/// let splits: Vec<_> = s
+///   .get_splits(OffsetReferential::Original, OffsetType::Byte)
+///   .into_iter()
+///   .map(|(split, offsets, _)| (split, offsets))
+///   .collect();
+///
+/// assert_eq!(splits, vec![("The cat sat down.", (0, 17)), ("It was happy.", (18, 31))]);
+/// # }
+/// # }
+/// ```
+#[cfg(feature = "tokenizers")]
+pub struct PunktPreTokenizer<P = Standard, D = TrainingData> {
+  segmenter: Segmenter<P, D>,
+  words: bool,
+}
+
+#[cfg(feature = "tokenizers")]
+impl<P, D> PunktPreTokenizer<P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Creates a pre-tokenizer that splits text into sentences. See
+  /// `Segmenter::pre_tokenize`.
+  #[inline]
+  pub fn sentences(segmenter: Segmenter<P, D>) -> PunktPreTokenizer<P, D> {
+    PunktPreTokenizer {
+      segmenter,
+      words: false,
+    }
+  }
+
+  /// Creates a pre-tokenizer that splits text into word tokens. See
+  /// `Segmenter::pre_tokenize_words`.
+  #[inline]
+  pub fn words(segmenter: Segmenter<P, D>) -> PunktPreTokenizer<P, D> {
+    PunktPreTokenizer {
+      segmenter,
+      words: true,
+    }
+  }
+
+  /// The segmenter used to split text.
+  #[inline]
+  pub fn segmenter(&self) -> &Segmenter<P, D> {
+    &self.segmenter
+  }
+}
+
+#[cfg(feature = "tokenizers")]
+impl<P, D> PreTokenizer for PunktPreTokenizer<P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  fn pre_tokenize(&self, pretokenized: &mut PreTokenizedString) -> tokenizers::Result<()> {
+    pretokenized.split(|_, normalized| {
+      let splits = if self.words {
+        self.segmenter.pre_tokenize_words(normalized.get())
+      } else {
+        self.segmenter.pre_tokenize(normalized.get())
+      };
+
+      Ok(
+        splits
+          .into_iter()
+          .filter_map(|(_, (start, end))| normalized.slice(Range::Normalized(start..end)))
+          .collect::<Vec<_>>(),
+      )
+    })
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn pre_tokenize_test() {
+  let doc = "  Dr. Watson  arrived.\n\nHolmes,   as usual, did not…";
+  let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+
+  for &(s, (start, end)) in segmenter.pre_tokenize(doc).iter() {
+    assert_eq!(s, &doc[start..end]);
+  }
+
+  for &(s, (start, end)) in segmenter.pre_tokenize_words(doc).iter() {
+    assert_eq!(s, &doc[start..end]);
+    assert_eq!(s, s.trim());
+  }
+
+  assert_eq!(segmenter.pre_tokenize(doc).len(), 2);
+  assert!(segmenter.pre_tokenize_words("").is_empty());
+}
+
+#[cfg(feature = "tokenizers")]
+#[test]
+fn punkt_pre_tokenizer_test() {
+  use tokenizers::{OffsetReferential, OffsetType};
+
+  let doc = "  Dr. Watson  arrived.\n\nHolmes,   as usual, did not\u{2026}";
+
+  for &words in [false, true].iter() {
+    let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+    let expected = if words {
+      segmenter.pre_tokenize_words(doc)
+    } else {
+      segmenter.pre_tokenize(doc)
+    };
+    let pre_tokenizer = if words {
+      PunktPreTokenizer::words(segmenter)
+    } else {
+      PunktPreTokenizer::sentences(segmenter)
+    };
+    let mut s = PreTokenizedString::from(doc);
+
+    pre_tokenizer.pre_tokenize(&mut s).unwrap();
+
+    let actual: Vec<_> = s
+      .get_splits(OffsetReferential::Original, OffsetType::Byte)
+      .into_iter()
+      .map(|(split, offsets, _)| (split, offsets))
+      .collect();
+
+    assert_eq!(actual, expected);
+  }
+}
@@ -0,0 +1,99 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use regex::{Error, Regex};
+
+/// Protected tokens defined by a set of regular expressions, to implement
+/// `DefinesMultiChars::protected_token` with. Each expression is matched at
+/// the start of the slice it is given, and the longest match is the
+/// protected token. Requires the `regex` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use std::sync::OnceLock;
+/// # use punkt::params::*;
+/// # use punkt::{ProtectedTokens, WordTokenizer};
+/// #
+/// struct Codes;
+///
+/// impl DefinesInternalPunctuation for Codes {}
+/// impl DefinesNonPrefixCharacters for Codes {}
+/// impl DefinesNonWordCharacters for Codes {}
+/// impl DefinesPunctuation for Codes {}
+/// impl DefinesSentenceEndings for Codes {}
+/// impl DefinesTypeNormalization for Codes {}
+/// impl DefinesMultiChars for Codes {
+///   fn protected_token(doc: &str) -> Option<usize> {
+///     static CODES: OnceLock<ProtectedTokens> = OnceLock::new();
+///
+///     CODES
+///       .get_or_init(|| ProtectedTokens::new(&[r"[A-Z]{3}-\d+(\.\d+)*\.[A-Z]", r"v\d+\.\d+"]).unwrap())
+///       .find(doc)
+///   }
+/// }
+///
+/// let words: Vec<_> = WordTokenizer::<Codes>::new("Order ABC-123.456.X today.")
+///   .map(|t| t.tok().to_string())
+///   .collect();
+///
+/// assert_eq!(words, vec!["order", "abc-123.456.x", "today."]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProtectedTokens {
+  regexes: Vec<Regex>,
+}
+
+impl ProtectedTokens {
+  /// Compiles a set of regular expressions. Returns the error of the first
+  /// expression that isn't valid.
+  pub fn new<I, S>(patterns: I) -> Result<ProtectedTokens, Error>
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    let mut regexes = Vec::new();
+
+    for pattern in patterns {
+      // Anchors the expression, so it only matches at the start.
+      regexes.push(Regex::new(&format!("^(?:{})", pattern.as_ref()))?);
+    }
+
+    Ok(ProtectedTokens { regexes: regexes })
+  }
+
+  /// Returns the length in bytes of the longest match at the start of a
+  /// slice, if there is one. Empty matches are ignored.
+  pub fn find(&self, doc: &str) -> Option<usize> {
+    self
+      .regexes
+      .iter()
+      .filter_map(|r| r.find(doc))
+      .map(|m| m.end())
+      .filter(|&end| end > 0)
+      .max()
+  }
+}
+
+#[test]
+fn protected_tokens_test() {
+  let tokens = ProtectedTokens::new(&[r"[A-Z]+-\d+", r"[A-Z]+-\d+\.\d+", r"x*"]).unwrap();
+
+  assert_eq!(tokens.find("AB-12.3 next"), Some(7));
+  assert_eq!(tokens.find("AB-12 next"), Some(5));
+  assert_eq!(tokens.find("xx AB-12"), Some(2));
+  assert_eq!(tokens.find("see AB-12"), None);
+  assert_eq!(tokens.find(""), None);
+  assert!(ProtectedTokens::new(&["("]).is_err());
+  assert_eq!(
+    ProtectedTokens::new(Vec::<String>::new())
+      .unwrap()
+      .find("AB-12"),
+    None
+  );
+}
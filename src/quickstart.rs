@@ -0,0 +1,91 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use prelude::Standard;
+use tokenizer::SentenceByteOffsetTokenizer;
+use trainer::{Trainer, TrainingData};
+
+/// Splits a document into sentences, with the standard parameters, by training
+/// on the document itself first. This is the same as the typical usage in the
+/// crate docs, but the document is only tokenized once (see
+/// `Trainer::train_and_segment`). To reuse a model across documents, see
+/// `split_sentences_with`.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::split_sentences;
+/// #
+/// let doc = "I bought $5.50 worth of apples from the store. I gave them to my dog.";
+///
+/// assert_eq!(
+///   split_sentences(doc),
+///   vec!["I bought $5.50 worth of apples from the store.", "I gave them to my dog."]
+/// );
+/// ```
+pub fn split_sentences<'a>(doc: &'a str) -> Vec<&'a str> {
+  let trainer: Trainer<Standard> = Trainer::new();
+  let mut data = TrainingData::new();
+
+  trainer
+    .train_and_segment(doc, &mut data)
+    .into_iter()
+    .map(|(start, end)| doc.get(start..end).unwrap_or(""))
+    .collect()
+}
+
+/// Splits a document into sentences, with the standard parameters, using
+/// already trained data.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{split_sentences_with, TrainingData};
+/// #
+/// let data = TrainingData::english();
+///
+/// assert_eq!(
+///   split_sentences_with("Mr. Smith left. He was tired.", &data),
+///   vec!["Mr. Smith left.", "He was tired."]
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn split_sentences_with<'a>(doc: &'a str, data: &TrainingData) -> Vec<&'a str> {
+  // The offsets don't borrow the data, so the sentences can outlive it.
+  SentenceByteOffsetTokenizer::<Standard>::new(doc, data)
+    .map(|(start, end)| doc.get(start..end).unwrap_or(""))
+    .collect()
+}
+
+#[test]
+fn split_sentences_test() {
+  let doc = "This is a test. It has two sentences.";
+
+  assert_eq!(
+    split_sentences(doc),
+    vec!["This is a test.", "It has two sentences."]
+  );
+  assert!(split_sentences("").is_empty());
+}
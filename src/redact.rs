@@ -0,0 +1,113 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use metadata::Fnv;
+use token::Token;
+
+/// Formats a token, or a sentence without the text of the document, so it
+/// can be logged. Lengths, flags, and a hash of the text are shown instead.
+///
+/// The hash tells if two redacted values have the same text. It isn't a
+/// cryptographic hash, so short texts, like most tokens, can be recovered by
+/// hashing guesses.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{Redacted, SentenceTokenizer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "Call me at 5. I will wait.";
+/// let data = TrainingData::english();
+///
+/// for s in SentenceTokenizer::<Standard>::new(doc, &data) {
+///   let logged = format!("{:?}", Redacted(s));
+///
+///   assert!(logged.starts_with("Sentence { len: "));
+///   assert!(!logged.contains("wait"));
+/// }
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct Redacted<T>(pub T);
+
+/// Hashes text with FNV-1a, which is stable across versions, and platforms,
+/// so hashes in logs can be compared.
+#[inline]
+fn text_hash(s: &str) -> u64 {
+  let mut h = Fnv::new();
+
+  h.write(s.as_bytes());
+  h.0
+}
+
+impl<'a> fmt::Debug for Redacted<&'a str> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Sentence")
+      .field("len", &self.0.len())
+      .field("chars", &self.0.chars().count())
+      .field("hash", &format_args!("{:016x}", text_hash(self.0)))
+      .finish()
+  }
+}
+
+impl<'a> fmt::Display for Redacted<&'a str> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<{} bytes, {:016x}>", self.0.len(), text_hash(self.0))
+  }
+}
+
+impl<'a> fmt::Debug for Redacted<&'a Token> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Token")
+      .field("len", &self.0.tok().len())
+      .field("chars", &self.0.char_len())
+      .field("kind", &self.0.kind())
+      .field("flags", &self.0.flags())
+      .field("hash", &format_args!("{:016x}", text_hash(self.0.tok())))
+      .finish()
+  }
+}
+
+impl<'a> fmt::Display for Redacted<&'a Token> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    Redacted(self.0.tok()).fmt(f)
+  }
+}
+
+#[test]
+fn redacted_test() {
+  use prelude::Standard;
+  use tokenizer::WordTokenizer;
+
+  let toks: Vec<Token> = WordTokenizer::<Standard>::new("Secret. secret").collect();
+  let shown = format!("{:?}", toks[0]);
+  let redacted = format!("{:?}", Redacted(&toks[0]));
+
+  assert!(shown.contains("secret."));
+  assert!(!redacted.contains("secret"));
+  assert!(redacted.contains("len: 7, chars: 7"));
+  assert!(redacted.contains("IS_NEWLINE_START"));
+  assert_eq!(toks[0].to_string(), "secret.");
+  assert_eq!(
+    Redacted(&toks[1]).to_string(),
+    Redacted("secret").to_string()
+  );
+  assert!(Redacted(&toks[0]).to_string() != Redacted(&toks[1]).to_string());
+  assert_eq!(
+    format!("{:?}", Redacted("")),
+    format!(
+      "Sentence {{ len: 0, chars: 0, hash: {:016x} }}",
+      Fnv::new().0
+    )
+  );
+}
@@ -0,0 +1,156 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::ops::Deref;
+
+/// Text with hard line wraps joined, that remembers where each of its bytes
+/// came from in the original text.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{reflow, SentenceByteOffsetTokenizer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "The data was extrac-\nted from a PDF. It had\nhard wraps.";
+/// let text = reflow(doc);
+/// let data = TrainingData::english();
+///
+/// assert_eq!(&text[..], "The data was extracted from a PDF. It had hard wraps.");
+///
+/// for (start, end) in SentenceByteOffsetTokenizer::<Standard>::new(&text, &data) {
+///   let (start, end) = text.original_range(start, end);
+///   println!("{:?}", &doc[start..end]);
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Reflowed {
+  text: String,
+  offsets: Vec<usize>,
+}
+
+impl Reflowed {
+  /// Maps a byte offset in the reflowed text to the byte offset in the
+  /// original text it came from.
+  #[inline]
+  pub fn original_offset(&self, pos: usize) -> usize {
+    self.offsets[pos]
+  }
+
+  /// Maps a range of bytes in the reflowed text to the range of bytes in the
+  /// original text that it spans.
+  #[inline]
+  pub fn original_range(&self, start: usize, end: usize) -> (usize, usize) {
+    if end > start {
+      (self.offsets[start], self.offsets[end - 1] + 1)
+    } else {
+      (self.offsets[start], self.offsets[start])
+    }
+  }
+
+  /// Consumes the reflowed text, and returns the text.
+  #[inline]
+  pub fn into_string(self) -> String {
+    self.text
+  }
+
+  #[inline]
+  fn push_str(&mut self, s: &str, from: usize) {
+    self.text.push_str(s);
+    self.offsets.extend(from..from + s.len());
+  }
+}
+
+impl Deref for Reflowed {
+  type Target = str;
+
+  #[inline(always)]
+  fn deref(&self) -> &str {
+    &self.text[..]
+  }
+}
+
+/// Joins hard wrapped lines in a document, like the ones in text extracted
+/// from a PDF. Lines separated by a single newline are joined with a space,
+/// and words hyphenated across a line break (`exam-\nple`) are joined back
+/// together. Blank lines are kept as-is, so paragraphs are preserved.
+pub fn reflow(doc: &str) -> Reflowed {
+  let mut out = Reflowed {
+    text: String::with_capacity(doc.len()),
+    offsets: Vec::with_capacity(doc.len() + 1),
+  };
+  let mut pos = 0;
+
+  while pos < doc.len() {
+    let (line, nxt) = match doc[pos..].find('\n') {
+      Some(i) => (&doc[pos..pos + i], pos + i + 1),
+      None => (&doc[pos..], doc.len()),
+    };
+    let next_line = doc[nxt..].split('\n').next().unwrap_or("");
+
+    // Keep the line terminator if this is the last line, or if either line is
+    // blank (a paragraph break).
+    if nxt == doc.len() || line.trim().is_empty() || next_line.trim().is_empty() {
+      out.push_str(&doc[pos..nxt], pos);
+      pos = nxt;
+      continue;
+    }
+
+    let line = line.trim_end();
+    let indent = next_line.len() - next_line.trim_start().len();
+
+    if is_hyphenated(line, next_line.trim_start()) {
+      out.push_str(&line[..line.len() - 1], pos);
+    } else {
+      out.push_str(line, pos);
+      out.push_str(" ", pos + line.len());
+    }
+
+    pos = nxt + indent;
+  }
+
+  out.offsets.push(doc.len());
+  out
+}
+
+/// Checks if a word is broken by a hyphen at the end of a line, and continues
+/// in lowercase on the next line.
+#[inline]
+fn is_hyphenated(line: &str, next_line: &str) -> bool {
+  let mut rev = line.chars().rev();
+
+  match (rev.next(), rev.next(), next_line.chars().next()) {
+    (Some('-'), Some(p), Some(n)) => p.is_alphabetic() && n.is_lowercase(),
+    _ => false,
+  }
+}
+
+#[test]
+fn reflow_joins_lines_and_maps_offsets() {
+  let doc =
+    "This is an exam-\nple of wrapped  \n  text.\r\n\r\nA new para-\ngraph. Well -\nnot quite.";
+  let text = reflow(doc);
+
+  assert_eq!(
+    &text[..],
+    "This is an example of wrapped text.\r\n\r\nA new paragraph. Well - not quite."
+  );
+
+  let start = text.find("text").unwrap();
+  let (s, e) = text.original_range(start, start + 5);
+
+  assert_eq!(&doc[s..e], "text.");
+  assert_eq!(text.original_offset(text.len()), doc.len());
+
+  let (s, e) = text.original_range(text.find("example").unwrap(), text.find(" of").unwrap());
+
+  assert_eq!(&doc[s..e], "exam-\nple");
+}
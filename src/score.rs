@@ -0,0 +1,181 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[cfg(feature = "fixed-scores")]
+use fixed::Fixed;
+
+/// The number type that the likelihood scores of a trainer are computed
+/// with. `f64` by default. The `f32-scores` feature computes them with
+/// `f32`, and the `fixed-scores` feature with `Fixed`, for targets where
+/// floating point math is slow, or missing. `fixed-scores` wins if both are
+/// enabled. Scores are converted to `f64` wherever they leave the trainer,
+/// so the features don't change the public API, but a score close to a
+/// threshold can be decided differently, and the trained model can differ.
+#[cfg(not(any(feature = "f32-scores", feature = "fixed-scores")))]
+pub(crate) type Score = f64;
+
+/// The number type that the likelihood scores of a trainer are computed
+/// with. See the `f64` version.
+#[cfg(all(feature = "f32-scores", not(feature = "fixed-scores")))]
+pub(crate) type Score = f32;
+
+/// The number type that the likelihood scores of a trainer are computed
+/// with. See the `f64` version.
+#[cfg(feature = "fixed-scores")]
+pub(crate) type Score = Fixed;
+
+/// The math the trainer does with scores.
+pub(crate) trait ScoreMath:
+  Copy
+  + PartialOrd
+  + Add<Output = Self>
+  + Sub<Output = Self>
+  + Mul<Output = Self>
+  + Div<Output = Self>
+  + Neg<Output = Self>
+{
+  /// The distance between two neighbouring scores near `n`.
+  fn ulp(n: f64) -> f64;
+
+  /// Converts an `f64`, like a threshold of the parameters, to a score.
+  fn from_f64(n: f64) -> Self;
+
+  /// Converts a count to a score.
+  fn from_count(n: usize) -> Self;
+
+  /// Converts the score to the `f64` that is stored in a model.
+  fn to_f64(self) -> f64;
+
+  // The float types call their inherent methods instead, so these are only
+  // called when `Score` is `Fixed`.
+
+  /// The natural logarithm.
+  #[allow(dead_code)]
+  fn ln(self) -> Self;
+
+  /// The exponential function.
+  #[allow(dead_code)]
+  fn exp(self) -> Self;
+
+  /// Raises the score to an integer power.
+  #[allow(dead_code)]
+  fn powi(self, n: i32) -> Self;
+
+  /// Rounds the score to the nearest multiple of `step`, and returns the
+  /// multiple.
+  fn quantize(self, step: Self) -> i64;
+}
+
+macro_rules! impl_float_score(
+  ($t:ident) => (
+    impl ScoreMath for $t {
+      #[inline(always)]
+      fn ulp(n: f64) -> f64 {
+        n.abs() * ::std::$t::EPSILON as f64
+      }
+
+      #[inline(always)]
+      fn from_f64(n: f64) -> $t {
+        n as $t
+      }
+
+      #[inline(always)]
+      fn from_count(n: usize) -> $t {
+        n as $t
+      }
+
+      #[inline(always)]
+      fn to_f64(self) -> f64 {
+        self as f64
+      }
+
+      #[inline(always)]
+      fn ln(self) -> $t {
+        $t::ln(self)
+      }
+
+      #[inline(always)]
+      fn exp(self) -> $t {
+        $t::exp(self)
+      }
+
+      #[inline(always)]
+      fn powi(self, n: i32) -> $t {
+        $t::powi(self, n)
+      }
+
+      #[inline(always)]
+      fn quantize(self, step: $t) -> i64 {
+        // Casts saturate, and NaN becomes 0.
+        (self / step).round() as i64
+      }
+    }
+  )
+);
+
+impl_float_score!(f64);
+impl_float_score!(f32);
+
+/// The tolerance for a score to tie with a lower bound: `epsilon`, or 16
+/// units in the last place of `Score` at the bound, whichever is larger.
+/// The units cover the rounding of the few operations a score is computed
+/// with, so ties are still ties when `Score` can't represent `epsilon`.
+#[inline]
+pub(crate) fn tie_tolerance(bound: f64, epsilon: f64) -> f64 {
+  epsilon.max(16.0 * Score::ulp(bound.abs().max(1.0)))
+}
+
+/// A lower bound of the parameters. Scores, and the bound are rounded to a
+/// multiple of the tie tolerance before they are compared, so a score that
+/// only differs from the bound by rounding ties with it, and ties are
+/// accepted.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LowerBound {
+  step: Score,
+  bound: i64,
+}
+
+impl LowerBound {
+  /// Creates a lower bound, with the tolerance of `tie_tolerance`.
+  #[inline]
+  pub(crate) fn new(bound: f64, epsilon: f64) -> LowerBound {
+    let step = Score::from_f64(tie_tolerance(bound, epsilon));
+
+    LowerBound {
+      step: step,
+      bound: Score::from_f64(bound).quantize(step),
+    }
+  }
+
+  /// Checks if a score is at, or above the bound.
+  #[inline(always)]
+  pub(crate) fn accepts(&self, score: Score) -> bool {
+    score.quantize(self.step) >= self.bound
+  }
+}
+
+#[test]
+fn score_lower_bound_test() {
+  for &bound in [0.0, 0.3, 7.88, 30.0].iter() {
+    let tolerance = tie_tolerance(bound, 1e-9);
+    let lower = LowerBound::new(bound, 1e-9);
+
+    // The tolerance is never smaller than what `Score` can represent.
+    assert!(tolerance >= 1e-9);
+    assert!(lower.accepts(Score::from_f64(bound)));
+    assert!(lower.accepts(Score::from_f64(bound + tolerance * 2.0)));
+    assert!(!lower.accepts(Score::from_f64(bound - tolerance * 2.0)));
+
+    // Some scores below the bound round to the same multiple, and tie.
+    assert!((1..16)
+      .map(|i| Score::from_f64(bound - tolerance * i as f64 / 16.0))
+      .any(|s| s < Score::from_f64(bound) && lower.accepts(s)));
+  }
+}
@@ -0,0 +1,579 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use model::Model;
+use prelude::{Standard, TokenizerParameters};
+use tokenizer::{SentenceByteOffsetTokenizer, SentenceSpanTokenizer};
+use trainer::TrainingData;
+
+/// Splits text into sentences. Code that only needs sentence boundaries can
+/// accept any `SentenceSegmenter`, instead of depending on the tokenizers of
+/// this crate.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{Segmenter, SentenceSegmenter, TrainingData};
+/// #
+/// fn count_sentences<S: SentenceSegmenter>(segmenter: &S, text: &str) -> usize {
+///   segmenter.segment_spans(text).len()
+/// }
+///
+/// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+///
+/// assert_eq!(count_sentences(&segmenter, "Mr. Smith is here. He is late."), 2);
+/// # }
+/// ```
+pub trait SentenceSegmenter {
+  /// Returns the byte ranges of the sentences in the text, in order. None
+  /// of the ranges are empty.
+  fn segment_spans(&self, text: &str) -> Vec<Range<usize>>;
+}
+
+impl<'a, S> SentenceSegmenter for &'a S
+where
+  S: SentenceSegmenter + ?Sized,
+{
+  #[inline]
+  fn segment_spans(&self, text: &str) -> Vec<Range<usize>> {
+    (**self).segment_spans(text)
+  }
+}
+
+/// A sentence segmenter that owns its model. Unlike the tokenizers, which
+/// borrow a model for a single document, a `Segmenter` can be stored, and
+/// reused for any number of documents.
+///
+/// Segmenting doesn't mutate the segmenter, and it holds no interior
+/// mutability, so a `Segmenter` with a `Sync` model can be shared between
+/// threads without locking. Each thread can keep its own `ScratchSpace` to
+/// reuse allocations across documents.
+pub struct Segmenter<P = Standard, D = TrainingData> {
+  data: D,
+  // The parameters are never stored, so they don't affect `Send`, or `Sync`.
+  params: PhantomData<fn() -> P>,
+}
+
+impl<P, D> Segmenter<P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Creates a new `Segmenter`.
+  #[inline(always)]
+  pub fn new(data: D) -> Segmenter<P, D> {
+    Segmenter {
+      data,
+      params: PhantomData,
+    }
+  }
+
+  /// The model used to segment text.
+  #[inline(always)]
+  pub fn data(&self) -> &D {
+    &self.data
+  }
+
+  /// The model used to segment text, for updating it.
+  #[inline(always)]
+  pub(crate) fn data_mut(&mut self) -> &mut D {
+    &mut self.data
+  }
+
+  /// Consumes the segmenter, and returns its model.
+  #[inline(always)]
+  pub fn into_data(self) -> D {
+    self.data
+  }
+
+  /// The names of the parameters that differ from the parameters the model
+  /// was trained with. See `SentenceByteOffsetTokenizer::param_mismatches`.
+  pub fn param_mismatches(&self) -> Vec<&'static str> {
+    match self.data.params() {
+      Some(params) => params.tokenizer_mismatches::<P>(),
+      None => Vec::new(),
+    }
+  }
+
+  /// Returns an iterator over the sentences in the text, and the whitespace
+  /// between them. Every byte of the text is in exactly one segment, so the
+  /// segments can be joined back into the original text. A gap is always
+  /// followed by a sentence, but two sentences that aren't separated by
+  /// whitespace have no gap between them.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::{Segment, Segmenter, TrainingData};
+  /// #
+  /// let doc = "The cat sat down.  It was happy.";
+  /// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  /// let segments: Vec<Segment> = segmenter.segment_exhaustive(doc).collect();
+  ///
+  /// assert_eq!(
+  ///   segments,
+  ///   vec![Segment::Sentence(0..17), Segment::Gap(17..19), Segment::Sentence(19..32)]
+  /// );
+  /// # }
+  /// ```
+  #[inline]
+  pub fn segment_exhaustive<'a>(&'a self, text: &'a str) -> Segments<'a, P, D> {
+    Segments {
+      iter: SentenceByteOffsetTokenizer::new(text, &self.data),
+      len: text.len(),
+      pos: 0,
+      pending: None,
+    }
+  }
+
+  /// Finds the byte ranges of the sentences in the text, like
+  /// `SentenceSegmenter::segment_spans`, but stores them in a scratch space
+  /// instead of a new vector. The ranges are valid until the scratch space is
+  /// used again.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::{ScratchSpace, Segmenter, TrainingData};
+  /// #
+  /// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  /// let mut scratch = ScratchSpace::new();
+  ///
+  /// for doc in ["One. Two.", "Three. Four. Five."].iter() {
+  ///   for range in segmenter.segment_spans_with(doc, &mut scratch) {
+  ///     println!("{:?}", &doc[range.clone()]);
+  ///   }
+  /// }
+  /// # }
+  /// ```
+  #[inline]
+  pub fn segment_spans_with<'s>(
+    &self,
+    text: &str,
+    scratch: &'s mut ScratchSpace,
+  ) -> &'s [Range<usize>] {
+    scratch.spans.clear();
+    scratch.spans.extend(
+      SentenceByteOffsetTokenizer::<P, D>::new(text, &self.data).map(|(start, end)| start..end),
+    );
+
+    &scratch.spans[..]
+  }
+
+  /// Decides if the sentence ending character at a byte offset in a document
+  /// ends a sentence. Only the text around the character, starting two words
+  /// before it, is segmented, so the decision is cheap to make for a single
+  /// character in a large document.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::{BoundaryDecision, Segmenter, TrainingData};
+  /// #
+  /// let doc = "I met Mr. Smith today. He said hello!";
+  /// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  ///
+  /// assert_eq!(segmenter.is_boundary(doc, 8), BoundaryDecision::NoBreak);
+  /// assert_eq!(segmenter.is_boundary(doc, 21), BoundaryDecision::Break(22));
+  /// assert_eq!(segmenter.is_boundary(doc, 0), BoundaryDecision::NotTerminator);
+  /// # }
+  /// ```
+  pub fn is_boundary(&self, doc: &str, byte_idx: usize) -> BoundaryDecision {
+    let is_terminator = doc
+      .get(byte_idx..)
+      .and_then(|s| s.chars().next())
+      .map(|c| P::is_sentence_ending(&c))
+      .unwrap_or(false);
+
+    if !is_terminator {
+      return BoundaryDecision::NotTerminator;
+    }
+
+    let ctx = context_start(doc, byte_idx);
+
+    for span in SentenceSpanTokenizer::<P, D>::new(&doc[ctx..], &self.data) {
+      if ctx + span.end() <= byte_idx {
+        continue;
+      }
+
+      return match span.terminator() {
+        Some((start, end)) if ctx + start <= byte_idx && byte_idx < ctx + end => {
+          BoundaryDecision::Break(ctx + span.end())
+        }
+        _ => BoundaryDecision::NoBreak,
+      };
+    }
+
+    BoundaryDecision::NoBreak
+  }
+}
+
+/// Finds the start of the word before the word that contains a byte offset.
+/// The second pass of Punkt only looks at pairs of tokens, so that is enough
+/// context to decide a sentence ending at the offset.
+fn context_start(doc: &str, pos: usize) -> usize {
+  let mut start = pos;
+  let mut words = 0;
+  let mut in_word = false;
+
+  for (i, c) in doc[..pos].char_indices().rev() {
+    if !c.is_whitespace() {
+      start = i;
+      in_word = true;
+    } else if in_word {
+      words += 1;
+      in_word = false;
+
+      if words == 2 {
+        break;
+      }
+    }
+  }
+
+  start
+}
+
+/// Buffers that are reused across calls to `Segmenter::segment_spans_with`,
+/// so segmenting many documents doesn't allocate a new result for each one.
+/// A scratch space is cheap to create, and isn't tied to a segmenter, so it
+/// can be kept per thread, or per request.
+#[derive(Debug, Clone, Default)]
+pub struct ScratchSpace {
+  spans: Vec<Range<usize>>,
+}
+
+impl ScratchSpace {
+  /// Creates an empty scratch space.
+  #[inline]
+  pub fn new() -> ScratchSpace {
+    ScratchSpace::default()
+  }
+
+  /// Creates a scratch space with room for the spans of `sentences`
+  /// sentences.
+  #[inline]
+  pub fn with_capacity(sentences: usize) -> ScratchSpace {
+    ScratchSpace {
+      spans: Vec::with_capacity(sentences),
+    }
+  }
+}
+
+/// Whether a sentence ending character in a document ends a sentence. See
+/// `Segmenter::is_boundary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryDecision {
+  /// The character ends a sentence. Holds the byte offset of the end of the
+  /// sentence, which can be after closing characters, like quotes.
+  Break(usize),
+  /// The character doesn't end a sentence, like the period of an
+  /// abbreviation.
+  NoBreak,
+  /// The byte offset isn't at a sentence ending character.
+  NotTerminator,
+}
+
+/// A piece of text returned by `Segmenter::segment_exhaustive`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+  /// The byte range of a sentence.
+  Sentence(Range<usize>),
+  /// The byte range of the whitespace between two sentences.
+  Gap(Range<usize>),
+}
+
+impl Segment {
+  /// The byte range of the segment.
+  #[inline]
+  pub fn range(&self) -> Range<usize> {
+    match *self {
+      Segment::Sentence(ref r) | Segment::Gap(ref r) => r.clone(),
+    }
+  }
+
+  /// Checks if the segment is a sentence.
+  #[inline]
+  pub fn is_sentence(&self) -> bool {
+    match *self {
+      Segment::Sentence(_) => true,
+      Segment::Gap(_) => false,
+    }
+  }
+
+  /// The text of the segment, in the text that was segmented.
+  #[inline]
+  pub fn slice<'a>(&self, text: &'a str) -> &'a str {
+    &text[self.range()]
+  }
+}
+
+/// Joins the text of segments back into a document. The segments of
+/// `Segmenter::segment_exhaustive` cover every byte of a text, so joining
+/// them returns the original text. Sentences can be replaced before they are
+/// joined, to edit some sentences of a document, while keeping the rest of
+/// it, and the whitespace between sentences, as it was.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use std::borrow::Cow;
+/// # use punkt::{reconstruct, Segmenter, TrainingData};
+/// #
+/// let doc = "One fish.\n\nTwo fish.  Red fish.";
+/// let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+///
+/// assert_eq!(reconstruct(segmenter.segment_exhaustive(doc).map(|s| s.slice(doc))), doc);
+///
+/// let edited = reconstruct(segmenter.segment_exhaustive(doc).map(|s| match s.slice(doc) {
+///   "Two fish." => Cow::Owned("Blue fish.".to_string()),
+///   text => Cow::Borrowed(text),
+/// }));
+///
+/// assert_eq!(edited, "One fish.\n\nBlue fish.  Red fish.");
+/// # }
+/// ```
+pub fn reconstruct<I, S>(segments: I) -> String
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<str>,
+{
+  let mut out = String::new();
+
+  for s in segments {
+    out.push_str(s.as_ref());
+  }
+
+  out
+}
+
+/// Iterator over the sentences, and gaps in a text. See
+/// `Segmenter::segment_exhaustive`.
+pub struct Segments<'a, P, D: 'a> {
+  iter: SentenceByteOffsetTokenizer<'a, P, D>,
+  len: usize,
+  pos: usize,
+  pending: Option<Range<usize>>,
+}
+
+impl<'a, P, D> Iterator for Segments<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  type Item = Segment;
+
+  fn next(&mut self) -> Option<Segment> {
+    if let Some(r) = self.pending.take() {
+      return Some(Segment::Sentence(r));
+    }
+
+    match self.iter.next() {
+      Some((start, end)) => {
+        let gap = self.pos..start;
+
+        self.pos = end;
+
+        if gap.start < gap.end {
+          self.pending = Some(start..end);
+          Some(Segment::Gap(gap))
+        } else {
+          Some(Segment::Sentence(start..end))
+        }
+      }
+      // Sentences cover the end of the text, but anything left over is
+      // still returned.
+      None if self.pos < self.len => {
+        let gap = self.pos..self.len;
+
+        self.pos = self.len;
+        Some(Segment::Gap(gap))
+      }
+      None => None,
+    }
+  }
+}
+
+impl<P, D> SentenceSegmenter for Segmenter<P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  #[inline]
+  fn segment_spans(&self, text: &str) -> Vec<Range<usize>> {
+    SentenceByteOffsetTokenizer::<P, D>::new(text, &self.data)
+      .map(|(start, end)| start..end)
+      .collect()
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn segment_exhaustive_test() {
+  let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  let docs = [
+    "",
+    "  ",
+    "  Leading. And trailing.  \n",
+    "One.\n\n  Two!  Three? (Four.) Five",
+    "Hi. ! There",
+  ];
+
+  for doc in docs.iter() {
+    let segments: Vec<Segment> = segmenter.segment_exhaustive(doc).collect();
+    let joined: String = segments.iter().map(|s| &doc[s.range()]).collect();
+
+    assert_eq!(&joined[..], *doc);
+    assert!(segments
+      .windows(2)
+      .all(|w| w[0].is_sentence() || w[1].is_sentence()));
+    assert!(segments.iter().all(|s| !s.range().is_empty()));
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn reconstruct_test() {
+  use corpus::Corpus;
+
+  let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  let corpus = Corpus::load("test/raw", "test/sentence").unwrap();
+
+  for doc in corpus.iter() {
+    let doc = &doc.raw[..];
+
+    assert_eq!(
+      reconstruct(segmenter.segment_exhaustive(doc).map(|s| s.slice(doc))),
+      doc
+    );
+  }
+
+  assert_eq!(reconstruct(Vec::<String>::new()), "");
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn segmenter_test() {
+  let doc = "I met Mr. Smith today. He said hello! Then he left.";
+  let data = TrainingData::english();
+  let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  let expected: Vec<Range<usize>> = SentenceByteOffsetTokenizer::<Standard>::new(doc, &data)
+    .map(|(start, end)| start..end)
+    .collect();
+
+  assert_eq!(segmenter.segment_spans(doc), expected);
+  assert_eq!((&segmenter).segment_spans(doc).len(), 3);
+  assert!(segmenter.segment_spans("").is_empty());
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn segment_spans_with_test() {
+  use std::rc::Rc;
+  use std::sync::Arc;
+  use std::thread;
+
+  use model::CompiledData;
+
+  fn assert_send_sync<T: Send + Sync>() {}
+
+  assert_send_sync::<Segmenter>();
+  assert_send_sync::<Segmenter<Standard, CompiledData>>();
+  assert_send_sync::<Segmenter<Rc<Standard>>>();
+  assert_send_sync::<ScratchSpace>();
+
+  let segmenter: Arc<Segmenter> = Arc::new(Segmenter::new(TrainingData::english()));
+  let docs = ["Mr. Smith is here. He is late.", "", "One. Two. Three."];
+  let handles: Vec<_> = (0..2)
+    .map(|_| {
+      let segmenter = segmenter.clone();
+
+      thread::spawn(move || {
+        let mut scratch = ScratchSpace::with_capacity(4);
+
+        for doc in docs.iter() {
+          let spans = segmenter.segment_spans_with(doc, &mut scratch).to_vec();
+
+          assert_eq!(spans, segmenter.segment_spans(doc));
+        }
+      })
+    })
+    .collect();
+
+  for h in handles {
+    h.join().unwrap();
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn is_boundary_test() {
+  use prelude::DefinesSentenceEndings;
+  use std::fs;
+
+  let data = TrainingData::english();
+  let segmenter: Segmenter = Segmenter::new(TrainingData::english());
+  let doc = "He said \"Go!\" and left. Mr. J. Smith paid 5.5 dollars... Done?!";
+
+  assert_eq!(segmenter.is_boundary(doc, 11), BoundaryDecision::Break(12));
+  assert_eq!(segmenter.is_boundary(doc, 22), BoundaryDecision::Break(23));
+  assert_eq!(segmenter.is_boundary(doc, 26), BoundaryDecision::NoBreak);
+  assert_eq!(segmenter.is_boundary(doc, 29), BoundaryDecision::NoBreak);
+  assert_eq!(segmenter.is_boundary(doc, 43), BoundaryDecision::NoBreak);
+  assert_eq!(segmenter.is_boundary(doc, 61), BoundaryDecision::Break(63));
+  assert_eq!(segmenter.is_boundary(doc, 62), BoundaryDecision::Break(63));
+  assert_eq!(
+    segmenter.is_boundary(doc, 1),
+    BoundaryDecision::NotTerminator
+  );
+  assert_eq!(
+    segmenter.is_boundary(doc, 100),
+    BoundaryDecision::NotTerminator
+  );
+
+  // Decisions made from the context agree with segmenting the whole document.
+  for entry in fs::read_dir("test/raw").unwrap() {
+    let doc = fs::read_to_string(entry.unwrap().path()).unwrap();
+    let spans: Vec<_> = SentenceSpanTokenizer::<Standard>::new(&doc, &data).collect();
+
+    for (i, c) in doc.char_indices() {
+      if !Standard::is_sentence_ending(&c) {
+        continue;
+      }
+
+      let expected = spans
+        .iter()
+        .find(|s| {
+          s.terminator()
+            .map(|(a, b)| a <= i && i < b)
+            .unwrap_or(false)
+        })
+        .map(|s| BoundaryDecision::Break(s.end()))
+        .unwrap_or(BoundaryDecision::NoBreak);
+
+      assert_eq!(
+        segmenter.is_boundary(&doc, i),
+        expected,
+        "{:?}",
+        &doc[i.saturating_sub(30)..i]
+      );
+    }
+  }
+}
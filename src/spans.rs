@@ -0,0 +1,167 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use std::ops::Range;
+
+use model::Model;
+use prelude::{Standard, TokenizerParameters};
+use tokenizer::{SentenceByteOffsetTokenizer, SpannedWords, WordTokenizer};
+use trainer::TrainingData;
+
+/// Iterator over the sentences of a document, and the byte spans of the
+/// word tokens in each. See `segment_with_token_spans`.
+pub struct SentenceTokenSpans<'a, P, D: 'a = TrainingData> {
+  sents: SentenceByteOffsetTokenizer<'a, P, D>,
+  words: SpannedWords<'a, P>,
+  pending: Option<(usize, usize)>,
+}
+
+impl<'a, P, D> Iterator for SentenceTokenSpans<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  type Item = (Range<usize>, Vec<Range<usize>>);
+
+  fn next(&mut self) -> Option<(Range<usize>, Vec<Range<usize>>)> {
+    let (start, end) = self.sents.next()?;
+    let mut spans = Vec::new();
+
+    loop {
+      let (tok_start, tok_end) = match self.pending.take() {
+        Some(span) => span,
+        None => match self.words.next() {
+          Some((_, span)) => span,
+          None => break,
+        },
+      };
+
+      if tok_start >= end {
+        self.pending = Some((tok_start, tok_end));
+        break;
+      }
+
+      // A token can cross the end of a sentence, when punctuation was moved
+      // to the end of the sentence, so it is split at the end.
+      if tok_end > end {
+        spans.push(tok_start..end);
+        self.pending = Some((end, tok_end));
+        break;
+      }
+
+      spans.push(tok_start..tok_end);
+    }
+
+    Some((start..end, spans))
+  }
+}
+
+/// Returns an iterator over the byte ranges of the sentences of a document,
+/// and the byte ranges of the word tokens in each sentence, using the
+/// standard parameters. The document is only tokenized into words once, so
+/// the tokens line up with the sentences without tokenizing each sentence
+/// again.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{segment_with_token_spans, TrainingData};
+/// #
+/// let doc = "Mr. Smith left. He ran.";
+/// let data = TrainingData::english();
+/// let sents: Vec<Vec<&str>> = segment_with_token_spans(doc, &data)
+///   .map(|(_, spans)| spans.into_iter().map(|r| &doc[r]).collect())
+///   .collect();
+///
+/// assert_eq!(sents, vec![vec!["Mr.", "Smith", "left."], vec!["He", "ran."]]);
+/// # }
+/// ```
+#[inline]
+pub fn segment_with_token_spans<'a, D>(
+  doc: &'a str,
+  data: &'a D,
+) -> SentenceTokenSpans<'a, Standard, D>
+where
+  D: Model,
+{
+  segment_with_token_spans_with_params::<Standard, D>(doc, data)
+}
+
+/// Returns an iterator over the byte ranges of the sentences of a document,
+/// and the byte ranges of the word tokens in each sentence.
+#[inline]
+pub fn segment_with_token_spans_with_params<'a, P, D>(
+  doc: &'a str,
+  data: &'a D,
+) -> SentenceTokenSpans<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  SentenceTokenSpans {
+    sents: SentenceByteOffsetTokenizer::new(doc, data),
+    words: WordTokenizer::new(doc).spanned(),
+    pending: None,
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn segment_with_token_spans_test() {
+  use prelude::*;
+
+  struct Realign;
+
+  impl DefinesInternalPunctuation for Realign {}
+  impl DefinesNonPrefixCharacters for Realign {}
+  impl DefinesNonWordCharacters for Realign {}
+  impl DefinesPunctuation for Realign {}
+  impl DefinesSentenceEndings for Realign {}
+  impl DefinesMultiChars for Realign {}
+  impl DefinesTypeNormalization for Realign {}
+  impl TokenizerParameters for Realign {
+    const REALIGN_BOUNDARIES: bool = true;
+  }
+
+  let data = TrainingData::english();
+  let doc = "\"Stop.\" He said (twice).\n\nThen, he left...";
+  let sents: Vec<(Range<usize>, Vec<Range<usize>>)> =
+    segment_with_token_spans_with_params::<Realign, _>(doc, &data).collect();
+  let words: Vec<Vec<&str>> = sents
+    .iter()
+    .map(|&(_, ref spans)| spans.iter().map(|r| &doc[r.clone()]).collect())
+    .collect();
+
+  assert_eq!(sents.len(), 3);
+  assert_eq!(words[0], vec!["\"", "Stop.", "\""]);
+  assert_eq!(words[2], vec!["Then", ",", "he", "left", "..."]);
+
+  for &(ref sent, ref spans) in sents.iter() {
+    assert!(spans
+      .iter()
+      .all(|r| r.start >= sent.start && r.end <= sent.end));
+  }
+
+  assert!(segment_with_token_spans("", &data).next().is_none());
+}
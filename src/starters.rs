@@ -0,0 +1,120 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+
+use model::Model;
+use prelude::TokenizerParameters;
+use token::Token;
+use tokenizer::{SentenceByteOffsetTokenizer, WordTokenizer};
+use trainer::TrainingData;
+
+/// Iterator over the first token of each sentence in a document. See
+/// `sentence_initial_tokens`.
+pub struct SentenceInitialTokens<'a, P, D: 'a = TrainingData> {
+  doc: &'a str,
+  data: &'a D,
+  iter: SentenceByteOffsetTokenizer<'a, P, D>,
+}
+
+impl<'a, P, D> SentenceInitialTokens<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  /// Counts how many sentences start with each token type. Types are
+  /// lowercase, without a final period, so they can be compared to the
+  /// sentence starters of a model. Numbers are counted as `##number##`.
+  pub fn counts(self) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+
+    for t in self {
+      *counts
+        .entry(t.typ_without_break_or_period().to_string())
+        .or_insert(0) += 1;
+    }
+
+    counts
+  }
+}
+
+impl<'a, P, D> Iterator for SentenceInitialTokens<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  type Item = Token;
+
+  fn next(&mut self) -> Option<Token> {
+    while let Some((start, end)) = self.iter.next() {
+      if let Some(mut t) = WordTokenizer::<P>::new(&self.doc[start..end]).next() {
+        ::util::annotate_first_pass::<P, D>(&mut t, self.data);
+        return Some(t);
+      }
+    }
+
+    None
+  }
+}
+
+/// Returns an iterator over the first token of each sentence in a document.
+/// Tokens are annotated like they are by the sentence tokenizer, and can be
+/// punctuation, like an opening quote.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{sentence_initial_tokens, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "The cat sat. The dog barked. A bird sang.";
+/// let data = TrainingData::english();
+/// let counts = sentence_initial_tokens::<Standard, _>(doc, &data).counts();
+///
+/// assert_eq!(counts["the"], 2);
+/// assert_eq!(counts["a"], 1);
+/// # }
+/// ```
+#[inline]
+pub fn sentence_initial_tokens<'a, P, D>(
+  doc: &'a str,
+  data: &'a D,
+) -> SentenceInitialTokens<'a, P, D>
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  SentenceInitialTokens {
+    doc,
+    data,
+    iter: SentenceByteOffsetTokenizer::new(doc, data),
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn sentence_initial_tokens_test() {
+  use prelude::Standard;
+
+  let data = TrainingData::english();
+  let doc = "  \"Stop,\" I said. 42 people agreed.\n\nYes. I left. Then I went home.";
+  let tokens: Vec<String> = sentence_initial_tokens::<Standard, _>(doc, &data)
+    .map(|t| t.tok().to_string())
+    .collect();
+  let counts = sentence_initial_tokens::<Standard, _>(doc, &data).counts();
+
+  assert_eq!(tokens, vec!["\"", "42", "yes.", "i", "then"]);
+  assert_eq!(counts["##number##"], 1);
+  assert_eq!(counts["yes"], 1);
+  assert_eq!(counts.values().sum::<usize>(), tokens.len());
+  assert!(sentence_initial_tokens::<Standard, _>("", &data)
+    .next()
+    .is_none());
+}
@@ -0,0 +1,302 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use model::Model;
+use prelude::StructureParameters;
+use tokenizer::SentenceByteOffsetTokenizer;
+use trainer::TrainingData;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+  Blank,
+  Heading,
+  ListItem,
+  Prose,
+}
+
+/// Iterator over the byte offsets of the sentences in a document, that
+/// emits headings and list items as standalone segments.
+///
+/// Headings and list items often lack terminal punctuation, and would
+/// otherwise be glued onto the sentence that follows them. The document
+/// is first split into blocks using the line-based heuristics defined in
+/// `StructureParameters`. Headings are returned whole, while list items and
+/// the remaining prose are tokenized into sentences.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{StructuredByteOffsetTokenizer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let doc = "# Setup\n\n- Install the crate\n- Run the tests\n\nThat's all.";
+/// let data = TrainingData::english();
+///
+/// for (start, end) in StructuredByteOffsetTokenizer::<Standard>::new(doc, &data) {
+///   println!("{:?}", &doc[start..end]);
+/// }
+/// # }
+/// ```
+pub struct StructuredByteOffsetTokenizer<'a, P, D: 'a = TrainingData> {
+  doc: &'a str,
+  data: &'a D,
+  pos: usize,
+  prev_blank: bool,
+  inner: Option<(usize, SentenceByteOffsetTokenizer<'a, P, D>)>,
+}
+
+impl<'a, P, D> StructuredByteOffsetTokenizer<'a, P, D>
+where
+  P: StructureParameters,
+  D: Model,
+{
+  /// Creates a new `StructuredByteOffsetTokenizer`.
+  #[inline(always)]
+  pub fn new(doc: &'a str, data: &'a D) -> StructuredByteOffsetTokenizer<'a, P, D> {
+    StructuredByteOffsetTokenizer {
+      doc,
+      data,
+      pos: 0,
+      prev_blank: true,
+      inner: None,
+    }
+  }
+
+  /// Finds the next block of lines in the document, skipping blank lines.
+  /// Returns the kind of the block, and its trimmed byte offsets.
+  fn next_block(&mut self) -> Option<(LineKind, usize, usize)> {
+    loop {
+      if self.pos >= self.doc.len() {
+        return None;
+      }
+
+      let (line, nxt) = line_at(self.doc, self.pos);
+
+      if !line.trim().is_empty() {
+        break;
+      }
+
+      self.prev_blank = true;
+      self.pos = nxt;
+    }
+
+    let start = self.pos;
+    let (line, mut nxt) = line_at(self.doc, start);
+    let kind = classify::<P>(self.doc, line, self.prev_blank, nxt);
+    let mut end = start + line.len();
+    let mut prev_blank = false;
+
+    match kind {
+      // Indented lines following a list item continue the item.
+      LineKind::ListItem => {
+        while nxt < self.doc.len() {
+          let (line, after) = line_at(self.doc, nxt);
+
+          if line.trim().is_empty()
+            || !line.starts_with(char::is_whitespace)
+            || is_list_item::<P>(line.trim())
+          {
+            break;
+          }
+
+          end = nxt + line.len();
+          nxt = after;
+        }
+      }
+      // Prose continues until a heading or list item is encountered.
+      LineKind::Prose => {
+        while nxt < self.doc.len() {
+          let (line, after) = line_at(self.doc, nxt);
+
+          match classify::<P>(self.doc, line, prev_blank, after) {
+            LineKind::Heading | LineKind::ListItem => break,
+            LineKind::Blank => prev_blank = true,
+            LineKind::Prose => {
+              prev_blank = false;
+              end = nxt + line.len();
+            }
+          }
+
+          nxt = after;
+        }
+      }
+      _ => (),
+    }
+
+    self.pos = nxt;
+    self.prev_blank = prev_blank;
+
+    let block = &self.doc[start..end];
+
+    Some((
+      kind,
+      start + (block.len() - block.trim_start().len()),
+      start + block.trim_end().len(),
+    ))
+  }
+}
+
+impl<'a, P, D> Iterator for StructuredByteOffsetTokenizer<'a, P, D>
+where
+  P: StructureParameters,
+  D: Model,
+{
+  type Item = (usize, usize);
+
+  fn next(&mut self) -> Option<(usize, usize)> {
+    loop {
+      if let Some((offset, ref mut iter)) = self.inner {
+        if let Some((start, end)) = iter.next() {
+          return Some((offset + start, offset + end));
+        }
+      }
+
+      self.inner = None;
+
+      match self.next_block() {
+        Some((LineKind::Heading, start, end)) => return Some((start, end)),
+        Some((_, start, end)) => {
+          self.inner = Some((
+            start,
+            SentenceByteOffsetTokenizer::new(&self.doc[start..end], self.data),
+          ))
+        }
+        None => return None,
+      }
+    }
+  }
+}
+
+/// Returns the line starting at `pos` without its line terminator, and
+/// the position of the line after it.
+#[inline]
+fn line_at(doc: &str, pos: usize) -> (&str, usize) {
+  match doc[pos..].find('\n') {
+    Some(i) => (&doc[pos..pos + i], pos + i + 1),
+    None => (&doc[pos..], doc.len()),
+  }
+}
+
+/// Classifies a line, given whether the line before it was blank, and the
+/// position of the line after it.
+fn classify<P>(doc: &str, line: &str, prev_blank: bool, nxt: usize) -> LineKind
+where
+  P: StructureParameters,
+{
+  let line = line.trim();
+
+  if line.is_empty() {
+    LineKind::Blank
+  } else if P::DETECT_HEADINGS && is_heading::<P>(doc, line, prev_blank, nxt) {
+    LineKind::Heading
+  } else if is_list_item::<P>(line) {
+    LineKind::ListItem
+  } else {
+    LineKind::Prose
+  }
+}
+
+/// Checks if a trimmed line is a heading.
+fn is_heading<P>(doc: &str, line: &str, prev_blank: bool, nxt: usize) -> bool
+where
+  P: StructureParameters,
+{
+  if line.starts_with('#') {
+    let text = line.trim_start_matches('#');
+
+    return text.is_empty() || text.starts_with(char::is_whitespace);
+  }
+
+  let is_short = line.chars().count() <= P::MAX_HEADING_LEN;
+  let is_unterminated = line
+    .chars()
+    .next_back()
+    .map(|c| !P::is_punctuation(&c) && !P::is_closing_quote(&c))
+    .unwrap_or(false);
+
+  // A sentence ending followed by whitespace means the line is prose.
+  let has_sentence_ending = line.char_indices().any(|(i, c)| {
+    P::is_sentence_ending(&c) && line[i + c.len_utf8()..].starts_with(char::is_whitespace)
+  });
+
+  if !prev_blank || !is_short || !is_unterminated || has_sentence_ending {
+    return false;
+  }
+
+  // The next line must be blank, or look like the start of a new sentence.
+  let next = if nxt < doc.len() {
+    line_at(doc, nxt).0.trim()
+  } else {
+    ""
+  };
+
+  next
+    .chars()
+    .next()
+    .map(|c| c.is_uppercase() || c.is_numeric())
+    .unwrap_or(true)
+}
+
+/// Checks if a trimmed line starts with a list marker followed by whitespace.
+fn is_list_item<P>(line: &str) -> bool
+where
+  P: StructureParameters,
+{
+  let marker = line.split(char::is_whitespace).next().unwrap_or("");
+
+  if marker.len() == line.len() {
+    return false;
+  }
+
+  let mut chars = marker.chars();
+
+  match (chars.next(), chars.next_back()) {
+    (Some(c), None) => P::is_list_bullet(&c),
+    (Some(_), Some(t)) if P::DETECT_NUMBERED_LISTS && (t == '.' || t == ')') => {
+      let body = &marker[..marker.len() - 1];
+
+      if body.len() <= 3 && body.chars().all(|c| c.is_ascii_digit()) {
+        true
+      } else {
+        t == ')' && body.chars().count() == 1 && body.chars().all(char::is_alphabetic)
+      }
+    }
+    _ => false,
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn structured_tokenizer_headings_and_lists_test() {
+  use prelude::Standard;
+
+  let data = TrainingData::english();
+  let doc = "# Overview\n\nThis crate splits text. It is fast\n\n- First item\n- Second item. \
+             With two sentences.\n  It continues here.\n\n1) Numbered\n\nResults\nThe end.";
+  let sents: Vec<&str> = StructuredByteOffsetTokenizer::<Standard>::new(doc, &data)
+    .map(|(start, end)| &doc[start..end])
+    .collect();
+
+  assert_eq!(
+    sents,
+    vec![
+      "# Overview",
+      "This crate splits text.",
+      "It is fast",
+      "- First item",
+      "- Second item.",
+      "With two sentences.",
+      "It continues here.",
+      "1) Numbered",
+      "Results",
+      "The end.",
+    ]
+  );
+}
@@ -6,46 +6,364 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::ops::Deref;
+//! Word tokens, and the properties Punkt records about them.
+//!
+//! Tokens are returned by `WordTokenizer`. Each token keeps a normalized
+//! (lowercase) copy of the text it was created from, along with flags
+//! describing it.
+//!
+//! # Examples
+//!
+//! ```
+//! # use punkt::WordTokenizer;
+//! # use punkt::params::Standard;
+//! # use punkt::token::{LetterCase, Token, TokenKind};
+//! #
+//! let tokens: Vec<Token> = WordTokenizer::<Standard>::new("Dr. Watson arrived.").collect();
+//!
+//! assert_eq!(tokens[0].tok(), "dr.");
+//! assert!(tokens[0].has_final_period());
+//! assert_eq!(tokens[1].first_case(), LetterCase::Upper);
+//! assert_eq!(tokens[2].kind(), TokenKind::Word);
+//! ```
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use std::borrow::Cow;
+use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::ops::{BitAnd, BitOr, Deref};
+
+use prelude::{DefinesTypeNormalization, Standard};
+
+// These flags only use the lower 8 bits.
+const HAS_FINAL_PERIOD: u32 = 0b0000000000000001;
+const IS_ELLIPSIS: u32 = 0b0000000000000010;
+const IS_ABBREV: u32 = 0b0000000000000100;
+const IS_SENTENCE_BREAK: u32 = 0b0000000000001000;
+const IS_PARAGRAPH_START: u32 = 0b0000000000010000;
+const IS_NEWLINE_START: u32 = 0b0000000000100000;
+const IS_UPPERCASE: u32 = 0b0000000001000000;
+const IS_LOWERCASE: u32 = 0b0000000010000000;
+
+// These flags only use the upper 8 bits of the lower 16. The upper 16 bits
+// are free for new flags.
+const IS_INITIAL: u32 = 0b1000000000000000;
+const IS_NUMERIC: u32 = 0b0100000000000000;
+const IS_NON_PUNCT: u32 = 0b0010000000000000;
+const IS_ALPHABETIC: u32 = 0b0000010000000000;
+
+const IS_PROTECTED: u32 = 0b00000000000000010000000000000000;
+
+const FLAG_NAMES: [(&'static str, u32); 13] = [
+  ("HAS_FINAL_PERIOD", HAS_FINAL_PERIOD),
+  ("IS_ELLIPSIS", IS_ELLIPSIS),
+  ("IS_ABBREV", IS_ABBREV),
+  ("IS_SENTENCE_BREAK", IS_SENTENCE_BREAK),
+  ("IS_PARAGRAPH_START", IS_PARAGRAPH_START),
+  ("IS_NEWLINE_START", IS_NEWLINE_START),
+  ("IS_UPPERCASE", IS_UPPERCASE),
+  ("IS_LOWERCASE", IS_LOWERCASE),
+  ("IS_ALPHABETIC", IS_ALPHABETIC),
+  ("IS_NON_PUNCT", IS_NON_PUNCT),
+  ("IS_NUMERIC", IS_NUMERIC),
+  ("IS_INITIAL", IS_INITIAL),
+  ("IS_PROTECTED", IS_PROTECTED),
+];
+
+/// The flags set on a token, by the word tokenizer, and by the annotation
+/// passes of the sentence tokenizer. Returned by `Token::flags`.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::WordTokenizer;
+/// # use punkt::params::Standard;
+/// # use punkt::token::TokenFlags;
+/// #
+/// let tok = WordTokenizer::<Standard>::new("Hello.").next().unwrap();
+/// let flags = tok.flags();
+///
+/// assert!(flags.contains(TokenFlags::HAS_FINAL_PERIOD | TokenFlags::IS_UPPERCASE));
+/// assert_eq!(flags.iter().count(), 4);
+/// assert_eq!(
+///   format!("{:?}", flags),
+///   "TokenFlags(HAS_FINAL_PERIOD | IS_NEWLINE_START | IS_UPPERCASE | IS_NON_PUNCT)"
+/// );
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TokenFlags(u32);
+
+impl TokenFlags {
+  /// The token ends with a period.
+  pub const HAS_FINAL_PERIOD: TokenFlags = TokenFlags(HAS_FINAL_PERIOD);
+  /// The token is an ellipsis.
+  pub const IS_ELLIPSIS: TokenFlags = TokenFlags(IS_ELLIPSIS);
+  /// The token was annotated as an abbreviation.
+  pub const IS_ABBREV: TokenFlags = TokenFlags(IS_ABBREV);
+  /// The token was annotated as the end of a sentence.
+  pub const IS_SENTENCE_BREAK: TokenFlags = TokenFlags(IS_SENTENCE_BREAK);
+  /// The token is the first one of a paragraph.
+  pub const IS_PARAGRAPH_START: TokenFlags = TokenFlags(IS_PARAGRAPH_START);
+  /// The token is the first one on a line.
+  pub const IS_NEWLINE_START: TokenFlags = TokenFlags(IS_NEWLINE_START);
+  /// The first character of the token is uppercase.
+  pub const IS_UPPERCASE: TokenFlags = TokenFlags(IS_UPPERCASE);
+  /// The first character of the token is lowercase.
+  pub const IS_LOWERCASE: TokenFlags = TokenFlags(IS_LOWERCASE);
+  /// The token has no punctuation, or symbols.
+  pub const IS_ALPHABETIC: TokenFlags = TokenFlags(IS_ALPHABETIC);
+  /// The token has a letter, or an underscore.
+  pub const IS_NON_PUNCT: TokenFlags = TokenFlags(IS_NON_PUNCT);
+  /// The token is a number.
+  pub const IS_NUMERIC: TokenFlags = TokenFlags(IS_NUMERIC);
+  /// The token is an initial.
+  pub const IS_INITIAL: TokenFlags = TokenFlags(IS_INITIAL);
+  /// The token is protected. See `DefinesMultiChars::protected_token`.
+  pub const IS_PROTECTED: TokenFlags = TokenFlags(IS_PROTECTED);
+
+  /// No flags set.
+  #[inline(always)]
+  pub fn empty() -> TokenFlags {
+    TokenFlags(0)
+  }
+
+  /// Creates flags from their raw representation.
+  #[inline(always)]
+  pub fn from_bits(bits: u32) -> TokenFlags {
+    TokenFlags(bits)
+  }
+
+  /// The raw representation of the flags.
+  #[inline(always)]
+  pub fn bits(self) -> u32 {
+    self.0
+  }
+
+  /// Checks if no flags are set.
+  #[inline(always)]
+  pub fn is_empty(self) -> bool {
+    self.0 == 0
+  }
 
-use prelude::LetterCase;
-
-// These 6 flags only use the lower 8 bits.
-const HAS_FINAL_PERIOD: u16 = 0b0000000000000001;
-const IS_ELLIPSIS: u16 = 0b0000000000000010;
-const IS_ABBREV: u16 = 0b0000000000000100;
-const IS_SENTENCE_BREAK: u16 = 0b0000000000001000;
-const IS_PARAGRAPH_START: u16 = 0b0000000000010000;
-const IS_NEWLINE_START: u16 = 0b0000000000100000;
-const IS_UPPERCASE: u16 = 0b0000000001000000;
-const IS_LOWERCASE: u16 = 0b0000000010000000;
-
-// These flags only use the upper 8 bits.
-const IS_INITIAL: u16 = 0b1000000000000000;
-const IS_NUMERIC: u16 = 0b0100000000000000;
-const IS_NON_PUNCT: u16 = 0b0010000000000000;
-const IS_ALPHABETIC: u16 = 0b0000010000000000;
-
-#[derive(Eq)]
+  /// Checks if all of the flags in `other` are set.
+  #[inline(always)]
+  pub fn contains(self, other: TokenFlags) -> bool {
+    self.0 & other.0 == other.0
+  }
+
+  /// Checks if any of the flags in `other` are set.
+  #[inline(always)]
+  pub fn intersects(self, other: TokenFlags) -> bool {
+    self.0 & other.0 != 0
+  }
+
+  /// Iterates over each flag that is set, from the lowest bit to the
+  /// highest.
+  #[inline]
+  pub fn iter(self) -> impl Iterator<Item = TokenFlags> {
+    (0..32)
+      .map(|i| 1u32 << i)
+      .filter(move |bit| self.0 & bit != 0)
+      .map(TokenFlags)
+  }
+}
+
+impl BitOr for TokenFlags {
+  type Output = TokenFlags;
+
+  #[inline(always)]
+  fn bitor(self, other: TokenFlags) -> TokenFlags {
+    TokenFlags(self.0 | other.0)
+  }
+}
+
+impl BitAnd for TokenFlags {
+  type Output = TokenFlags;
+
+  #[inline(always)]
+  fn bitand(self, other: TokenFlags) -> TokenFlags {
+    TokenFlags(self.0 & other.0)
+  }
+}
+
+/// Lists the names of the flags that are set, like `IS_ABBREV | IS_NUMERIC`.
+impl fmt::Debug for TokenFlags {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut first = true;
+
+    f.write_str("TokenFlags(")?;
+
+    for flag in self.iter() {
+      if !first {
+        f.write_str(" | ")?;
+      }
+
+      match FLAG_NAMES.iter().find(|&&(_, bit)| bit == flag.0) {
+        Some(&(name, _)) => f.write_str(name)?,
+        None => write!(f, "{:#x}", flag.0)?,
+      }
+
+      first = false;
+    }
+
+    if first {
+      f.write_str("empty")?;
+    }
+
+    f.write_str(")")
+  }
+}
+
+/// Serializes the flags as their raw representation. Requires the `serde`
+/// feature.
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for TokenFlags {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: ::serde::Serializer,
+  {
+    serializer.serialize_u32(self.0)
+  }
+}
+
+/// Deserializes the flags from their raw representation, like
+/// `TokenFlags::from_bits`. Requires the `serde` feature.
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for TokenFlags {
+  fn deserialize<D>(deserializer: D) -> Result<TokenFlags, D::Error>
+  where
+    D: ::serde::Deserializer<'de>,
+  {
+    <u32 as ::serde::Deserialize>::deserialize(deserializer).map(TokenFlags)
+  }
+}
+
+/// The case of the first letter of a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LetterCase {
+  /// The token starts with an uppercase letter.
+  Upper,
+  /// The token starts with a lowercase letter.
+  Lower,
+  /// The token doesn't start with a cased letter.
+  Unknown,
+}
+
+impl LetterCase {
+  #[inline(always)]
+  pub(crate) fn as_byte(&self) -> u8 {
+    match *self {
+      LetterCase::Upper => 0b00000010,
+      LetterCase::Lower => 0b00000001,
+      LetterCase::Unknown => 0b00000011,
+    }
+  }
+}
+
+/// The position of a token in a sentence, as far as it is known while
+/// training.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrthographyPosition {
+  /// The token begins a sentence.
+  Initial,
+  /// The token is in the middle of a sentence.
+  Internal,
+  /// The position of the token isn't known.
+  Unknown,
+}
+
+impl OrthographyPosition {
+  #[inline(always)]
+  pub(crate) fn as_byte(&self) -> u8 {
+    match *self {
+      OrthographyPosition::Initial => 0b01000000,
+      OrthographyPosition::Internal => 0b00100000,
+      OrthographyPosition::Unknown => 0b01100000,
+    }
+  }
+}
+
+/// The category of a word token, decided when the token is scanned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+  /// A word. Contains at least one letter or digit, and isn't a number,
+  /// or an initial.
+  Word,
+  /// A number, like `-1,000.5`.
+  Number,
+  /// A single letter followed by a period, like `J.`.
+  Initial,
+  /// An ellipsis, like `...` or `. . .`.
+  Ellipsis,
+  /// Punctuation that isn't part of a word, like `(`, `;`, or `--`.
+  Punctuation,
+  /// Any other character that isn't part of a word, like `$` or `%`.
+  Symbol,
+  /// A line break. Only returned by `WordTokenizer::with_newlines`.
+  Newline,
+}
+
+/// A word token. Tokens are equal, and hash the same, if they have the same
+/// type (see `Token::typ`).
+///
+/// Dereferences to the normalized token, with a period appended to it if it
+/// doesn't already end with one.
+///
+/// The text accessors return slices of the stored token, or of its special
+/// type, so they take constant time, and never allocate:
+///
+/// | Token   | `tok`   | `typ`        | `typ_with_period` | `typ_without_period` |
+/// |---------|---------|--------------|-------------------|----------------------|
+/// | `Mr.`   | `mr.`   | `mr.`        | `mr.`             | `mr`                 |
+/// | `word`  | `word`  | `word`       | `word.`           | `word`               |
+/// | `1999.` | `1999.` | `##number##` | `##number##.`     | `##number##`         |
+/// | `42`    | `42`    | `##number##` | `##number##.`     | `##number##`         |
+/// | `.`     | `.`     | `.`          | `.`               | `.`                  |
+///
+/// A special type never keeps the final period of the token, so numbers
+/// with, and without one have the same type.
+#[derive(Clone, Eq)]
 pub struct Token {
   inner: String,
-  flags: u16,
+  flags: u32,
+  char_len: usize,
+  kind: TokenKind,
+  special: Option<&'static str>,
 }
 
 impl Token {
-  pub fn new(slice: &str, is_el: bool, is_pg: bool, is_nl: bool) -> Token {
-    debug_assert!(slice.len() > 0);
-
-    let first = slice.chars().nth(0).unwrap();
+  pub(crate) fn new<P>(slice: &str, is_el: bool, is_pg: bool, is_nl: bool) -> Token
+  where
+    P: DefinesTypeNormalization,
+  {
+    let stripped = strip_ignorable::<P>(slice);
+    let slice: &str = &stripped;
+    let first = slice.chars().next().unwrap_or('\0');
     let mut has_punct = false;
+    let mut has_alnum = false;
 
     // Add a period to any tokens without a period. This is an optimization
     // to avoid creating an entirely new token when using as a key.
-    let mut tok = if slice.as_bytes()[slice.len() - 1] == b'.' {
+    let mut tok = if slice.ends_with('.') {
       let mut tok = Token {
         inner: String::with_capacity(slice.len()),
         flags: 0x00,
+        char_len: 0,
+        kind: TokenKind::Word,
+        special: None,
       };
 
       tok.set_has_final_period(true);
@@ -54,6 +372,9 @@ impl Token {
       Token {
         inner: String::with_capacity(slice.len() + 1),
         flags: 0x00,
+        char_len: 0,
+        kind: TokenKind::Word,
+        special: None,
       }
     };
 
@@ -63,16 +384,30 @@ impl Token {
       tok.set_is_initial(true);
     }
 
-    for c in slice.chars() {
-      for c0 in c.to_lowercase() {
-        tok.inner.push(c0);
-      }
+    // Parameters that return a special type without a period, or fold away
+    // the final period, can't break the representation of the token.
+    tok.special = P::special_type_with_period(slice).filter(|s| s.ends_with('.'));
+
+    P::fold_case(slice, &mut tok.inner);
+
+    if tok.has_final_period() && !tok.inner.ends_with('.') {
+      tok.inner.push('.');
+    }
+
+    // Folding the case can change the number of characters, so the folded
+    // token is counted, rather than the slice.
+    tok.char_len = tok.inner.chars().count();
 
-      if c.is_alphabetic() || c == '_' {
+    for c in slice.chars() {
+      // Letterlike symbols (`№`, `℃`) stand for words, so they can be
+      // learned as abbreviations.
+      if c.is_alphabetic() || c == '_' || ('\u{2100}' <= c && c <= '\u{214f}') {
         tok.set_is_non_punct(true);
       } else if !c.is_digit(10) {
         has_punct = true;
       }
+
+      has_alnum |= c.is_alphanumeric();
     }
 
     if !tok.has_final_period() {
@@ -90,67 +425,113 @@ impl Token {
     tok.set_is_paragraph_start(is_pg);
     tok.set_is_newline_start(is_nl);
 
+    tok.kind = if is_el {
+      TokenKind::Ellipsis
+    } else if tok.is_numeric() {
+      TokenKind::Number
+    } else if tok.is_initial() {
+      TokenKind::Initial
+    } else if has_alnum || tok.is_non_punct() {
+      TokenKind::Word
+    } else if first == '\n' {
+      TokenKind::Newline
+    } else if is_punctuation_char(first) {
+      TokenKind::Punctuation
+    } else {
+      TokenKind::Symbol
+    };
+
     tok
   }
 
+  /// Returns the category of the token.
+  #[inline(always)]
+  pub fn kind(&self) -> TokenKind {
+    self.kind
+  }
+
+  /// Returns the flags set on the token.
+  #[inline(always)]
+  pub fn flags(&self) -> TokenFlags {
+    TokenFlags(self.flags)
+  }
+
   /// Returns the normalized original token (which can be reconstructed from
   /// the inner representation of the token, and the flags on the token).
   #[inline(always)]
   pub fn tok(&self) -> &str {
     if self.has_final_period() {
-      &self.inner[..]
+      self.inner.as_str()
     } else {
-      &self.inner[..self.inner.len() - 1]
+      without_period(&self.inner)
     }
   }
 
-  /// Returns the token with any ending period truncated.
+  /// Returns the normalized token without its final period, if it has one.
+  /// Returns an empty string for a token that is only a period.
   #[inline(always)]
   pub fn tok_without_period(&self) -> &str {
-    if self.has_final_period() {
-      &self.tok()[..self.len() - 1]
-    } else {
-      self.tok()
-    }
+    without_period(&self.inner)
+  }
+
+  /// Returns the number of characters in the normalized token. Counted once,
+  /// when the token is created.
+  #[inline(always)]
+  pub fn char_len(&self) -> usize {
+    self.char_len
+  }
+
+  /// Returns the number of characters in the normalized token, without any
+  /// ending period.
+  #[inline(always)]
+  pub fn char_len_without_period(&self) -> usize {
+    self.char_len - self.has_final_period() as usize
   }
 
-  /// Returns the type of the token. If the token is numeric (determined by flags),
-  /// returns `##number##`, otherwise returns the normalized token.
+  /// Checks if the type of the token is a special type, rather than the
+  /// normalized token.
+  #[inline(always)]
+  pub(crate) fn has_special_type(&self) -> bool {
+    self.special.is_some()
+  }
+
+  /// Returns the type of the token. If the token has a special type (like
+  /// `##number##` for numbers), returns it, otherwise returns the normalized
+  /// token. See `DefinesTypeNormalization`.
   #[inline(always)]
   pub fn typ(&self) -> &str {
-    if self.is_numeric() {
-      "##number##"
-    } else {
-      self.tok()
+    match self.special {
+      Some(s) => without_period(s),
+      None => self.tok(),
     }
   }
 
-  /// Returns the type of the token with a period appended to it. Returns
-  /// `##number##.` if the token is numeric (determined by flags), otherwise
-  /// returns the original token with a period appended to it.
+  /// Returns the type of the token with a period appended to it. Returns the
+  /// special type of the token (like `##number##.` for numbers) if it has one,
+  /// otherwise returns the original token with a period appended to it.
   #[inline(always)]
   pub fn typ_with_period(&self) -> &str {
-    if self.is_numeric() {
-      "##number##."
-    } else {
-      &self.inner[..]
+    match self.special {
+      Some(s) => s,
+      None => self.inner.as_str(),
     }
   }
 
-  /// Returns the type of the token without a period appended to it. Will return
-  /// `.`, if it is the only character in the string; otherwise, will slice type
-  /// to exclude the final period.
+  /// Returns the type of the token without its final period. A special type
+  /// never has one, so it is returned as it is, like `typ`. A token that is
+  /// only a period keeps it, so the type is never empty.
   #[inline(always)]
   pub fn typ_without_period(&self) -> &str {
-    if self.tok().len() > 1 && self.has_final_period() {
-      &self.typ_with_period()[..self.typ_with_period().len() - 1]
-    } else {
-      self.typ()
+    match self.special {
+      Some(s) => without_period(s),
+      None if self.inner.len() > 1 => without_period(&self.inner),
+      None => self.inner.as_str(),
     }
   }
 
-  /// Returns the type of the token without a break or period if it had one originally
-  /// at the end.
+  /// Returns `typ_without_period` if the token was annotated as the end of a
+  /// sentence, otherwise returns `typ`. The period of an abbreviation is part
+  /// of its type, but the period that ends a sentence isn't.
   #[inline(always)]
   pub fn typ_without_break_or_period(&self) -> &str {
     if self.is_sentence_break() {
@@ -160,6 +541,7 @@ impl Token {
     }
   }
 
+  /// Returns the case of the first letter of the token.
   #[inline(always)]
   pub fn first_case(&self) -> LetterCase {
     if self.is_uppercase() {
@@ -171,56 +553,67 @@ impl Token {
     }
   }
 
+  /// Checks if the token starts with an uppercase letter.
   #[inline(always)]
   pub fn is_uppercase(&self) -> bool {
     self.flags & IS_UPPERCASE != 0
   }
 
+  /// Checks if the token starts with a lowercase letter.
   #[inline(always)]
   pub fn is_lowercase(&self) -> bool {
     self.flags & IS_LOWERCASE != 0
   }
 
+  /// Checks if the token is an ellipsis.
   #[inline(always)]
   pub fn is_ellipsis(&self) -> bool {
     self.flags & IS_ELLIPSIS != 0
   }
 
+  /// Checks if the token was annotated as an abbreviation.
   #[inline(always)]
   pub fn is_abbrev(&self) -> bool {
     self.flags & IS_ABBREV != 0
   }
 
+  /// Checks if the token was annotated as ending a sentence.
   #[inline(always)]
   pub fn is_sentence_break(&self) -> bool {
     self.flags & IS_SENTENCE_BREAK != 0
   }
 
+  /// Checks if the token ends with a period.
   #[inline(always)]
   pub fn has_final_period(&self) -> bool {
     self.flags & HAS_FINAL_PERIOD != 0
   }
 
+  /// Checks if the token is the first on a line, after an empty line.
   #[inline(always)]
   pub fn is_paragraph_start(&self) -> bool {
     self.flags & IS_PARAGRAPH_START != 0
   }
 
+  /// Checks if the token is the first on a line.
   #[inline(always)]
   pub fn is_newline_start(&self) -> bool {
     self.flags & IS_NEWLINE_START != 0
   }
 
+  /// Checks if the token is a number.
   #[inline(always)]
   pub fn is_numeric(&self) -> bool {
     self.flags & IS_NUMERIC != 0
   }
 
+  /// Checks if the token is an initial, like `J.`.
   #[inline(always)]
   pub fn is_initial(&self) -> bool {
     self.flags & IS_INITIAL != 0
   }
 
+  /// Checks if the token contains a letter. Numbers are included.
   // The NLTK docs note that all numeric tokens are considered to be contain
   // only punctuation, because they are converted to `##number##`, which clearly
   // has alphabetic characters.
@@ -229,13 +622,23 @@ impl Token {
     (self.flags & IS_NON_PUNCT != 0) || self.is_numeric()
   }
 
+  /// Checks if the token only contains letters, and digits (and a final
+  /// period).
   #[inline(always)]
   pub fn is_alphabetic(&self) -> bool {
     self.flags & IS_ALPHABETIC != 0
   }
 
+  /// True if the token is protected. A protected token is never split, and
+  /// only ends a sentence with its last character. See
+  /// `DefinesMultiChars::protected_token`.
   #[inline(always)]
-  pub fn set_is_ellipsis(&mut self, b: bool) {
+  pub fn is_protected(&self) -> bool {
+    self.flags & IS_PROTECTED != 0
+  }
+
+  #[inline(always)]
+  pub(crate) fn set_is_ellipsis(&mut self, b: bool) {
     if b {
       self.flags |= IS_ELLIPSIS;
     } else if self.is_ellipsis() {
@@ -244,7 +647,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_abbrev(&mut self, b: bool) {
+  pub(crate) fn set_is_abbrev(&mut self, b: bool) {
     if b {
       self.flags |= IS_ABBREV;
     } else if self.is_abbrev() {
@@ -253,7 +656,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_sentence_break(&mut self, b: bool) {
+  pub(crate) fn set_is_sentence_break(&mut self, b: bool) {
     if b {
       self.flags |= IS_SENTENCE_BREAK;
     } else if self.is_sentence_break() {
@@ -262,7 +665,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_has_final_period(&mut self, b: bool) {
+  pub(crate) fn set_has_final_period(&mut self, b: bool) {
     if b {
       self.flags |= HAS_FINAL_PERIOD;
     } else if self.has_final_period() {
@@ -271,7 +674,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_paragraph_start(&mut self, b: bool) {
+  pub(crate) fn set_is_paragraph_start(&mut self, b: bool) {
     if b {
       self.flags |= IS_PARAGRAPH_START;
     } else if self.is_paragraph_start() {
@@ -280,7 +683,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_newline_start(&mut self, b: bool) {
+  pub(crate) fn set_is_newline_start(&mut self, b: bool) {
     if b {
       self.flags |= IS_NEWLINE_START;
     } else if self.is_newline_start() {
@@ -289,7 +692,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_uppercase(&mut self, b: bool) {
+  pub(crate) fn set_is_uppercase(&mut self, b: bool) {
     if b {
       self.flags |= IS_UPPERCASE;
     } else if self.is_uppercase() {
@@ -298,7 +701,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_lowercase(&mut self, b: bool) {
+  pub(crate) fn set_is_lowercase(&mut self, b: bool) {
     if b {
       self.flags |= IS_LOWERCASE;
     } else if self.is_lowercase() {
@@ -307,7 +710,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_numeric(&mut self, b: bool) {
+  pub(crate) fn set_is_numeric(&mut self, b: bool) {
     if b {
       self.flags |= IS_NUMERIC;
     } else if self.is_numeric() {
@@ -316,7 +719,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_initial(&mut self, b: bool) {
+  pub(crate) fn set_is_initial(&mut self, b: bool) {
     if b {
       self.flags |= IS_INITIAL;
     } else if self.is_initial() {
@@ -325,7 +728,7 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_non_punct(&mut self, b: bool) {
+  pub(crate) fn set_is_non_punct(&mut self, b: bool) {
     if b {
       self.flags |= IS_NON_PUNCT;
     } else if self.is_non_punct() {
@@ -334,13 +737,22 @@ impl Token {
   }
 
   #[inline(always)]
-  pub fn set_is_alphabetic(&mut self, b: bool) {
+  pub(crate) fn set_is_alphabetic(&mut self, b: bool) {
     if b {
       self.flags |= IS_ALPHABETIC;
     } else if self.is_alphabetic() {
       self.flags ^= IS_ALPHABETIC;
     }
   }
+
+  #[inline(always)]
+  pub(crate) fn set_is_protected(&mut self, b: bool) {
+    if b {
+      self.flags |= IS_PROTECTED;
+    } else if self.is_protected() {
+      self.flags ^= IS_PROTECTED;
+    }
+  }
 }
 
 impl Deref for Token {
@@ -348,7 +760,28 @@ impl Deref for Token {
 
   #[inline(always)]
   fn deref(&self) -> &str {
-    &self.inner[..]
+    self.inner.as_str()
+  }
+}
+
+/// Shows the token, its type, and what is known about it. The text of the
+/// document is included, so wrap tokens in `Redacted` before logging them.
+impl fmt::Debug for Token {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Token")
+      .field("tok", &self.tok())
+      .field("typ", &self.typ())
+      .field("kind", &self.kind)
+      .field("flags", &self.flags())
+      .finish()
+  }
+}
+
+/// Writes the normalized token. See `Token::tok`.
+impl fmt::Display for Token {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(self.tok())
   }
 }
 
@@ -369,6 +802,74 @@ impl Hash for Token {
   }
 }
 
+/// Returns the type that a word is stored as in a model, using the standard
+/// parameters. Matches `Token::typ` for a token created from the word.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::token::normalize_type;
+/// #
+/// assert_eq!(normalize_type("Dr."), "dr.");
+/// assert_eq!(normalize_type("1,000"), "##number##");
+/// assert_eq!(normalize_type("word"), "word");
+/// ```
+#[inline]
+pub fn normalize_type(word: &str) -> Cow<'_, str> {
+  normalize_type_with_params::<Standard>(word)
+}
+
+/// Returns the type that a word is stored as in a model. Matches `Token::typ`
+/// for a token created from the word, including its case folding. Only
+/// returns an owned string if folding changed the word.
+pub fn normalize_type_with_params<P>(word: &str) -> Cow<'_, str>
+where
+  P: DefinesTypeNormalization,
+{
+  let stripped = strip_ignorable::<P>(word);
+
+  if let Some(s) = P::special_type_with_period(&stripped) {
+    return Cow::Borrowed(without_period(s));
+  }
+
+  let mut folded = String::with_capacity(word.len());
+
+  P::fold_case(&stripped, &mut folded);
+
+  if folded != word {
+    Cow::Owned(folded)
+  } else {
+    Cow::Borrowed(word)
+  }
+}
+
+/// Returns a string without its final period. Stored tokens, and special
+/// types always end with one.
+#[inline(always)]
+fn without_period(s: &str) -> &str {
+  s.strip_suffix('.').unwrap_or(s)
+}
+
+/// Removes the characters that are left out of types from a word. A word that
+/// only has such characters is kept as it is, so tokens are never empty.
+#[inline]
+fn strip_ignorable<P>(word: &str) -> Cow<'_, str>
+where
+  P: DefinesTypeNormalization,
+{
+  if P::IGNORABLE_CHARS.is_empty() || !word.chars().any(|c| P::is_ignorable_char(&c)) {
+    return Cow::Borrowed(word);
+  }
+
+  let stripped: String = word.chars().filter(|c| !P::is_ignorable_char(c)).collect();
+
+  if stripped.is_empty() {
+    Cow::Borrowed(word)
+  } else {
+    Cow::Owned(stripped)
+  }
+}
+
 /// A number can start with a negative sign ('-'), and be followed by digits
 /// or isolated periods, commas, or dashes.
 /// Note: It's assumed that multi-chars are taken out of the input when creating word
@@ -376,7 +877,7 @@ impl Hash for Token {
 /// its received. This assumption should be fulfilled by the parser generating
 /// these word tokens. If it isn't some weird outputs are possible (such as "5.4--5").
 #[inline]
-fn is_str_numeric(tok: &str) -> bool {
+pub(crate) fn is_str_numeric(tok: &str) -> bool {
   let mut digit_found = false;
   let mut pos = 0;
 
@@ -404,6 +905,22 @@ fn is_str_numeric(tok: &str) -> bool {
   digit_found
 }
 
+/// Unicode punctuation that can appear in a token on its own. Mathematical,
+/// currency, and other symbols aren't included.
+#[inline]
+fn is_punctuation_char(c: char) -> bool {
+  match c {
+    '#' | '$' | '%' | '&' | '*' | '+' | '<' | '=' | '>' | '@' | '\\' | '^' | '`' | '|' | '~' => {
+      false
+    }
+    _ if c.is_ascii_punctuation() => true,
+    '\u{a1}' | '\u{a7}' | '\u{ab}' | '\u{b6}' | '\u{b7}' | '\u{bb}' | '\u{bf}' => true,
+    '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205e}' => true,
+    '\u{3001}'..='\u{3003}' | '\u{3008}'..='\u{3011}' => true,
+    _ => false,
+  }
+}
+
 /// Tests if the token is an initial. An initial is a 2 character grouping
 /// where the first character is a letter (non-digit, non-symbol), and the
 /// next is a period.
@@ -430,13 +947,14 @@ fn test_token_flags() {
     )
   );
 
-  let mut tok = Token::new("test", false, false, false);
+  let mut tok = Token::new::<::prelude::Standard>("test", false, false, false);
 
   tok.set_is_non_punct(false);
   tok.set_is_lowercase(false);
   tok.set_is_alphabetic(false);
 
   assert_eq!(tok.flags, 0);
+  assert_eq!(format!("{:?}", tok.flags()), "TokenFlags(empty)");
 
   perform_flag_test!(tok, set_is_ellipsis, is_ellipsis);
   perform_flag_test!(tok, set_is_abbrev, is_abbrev);
@@ -449,4 +967,215 @@ fn test_token_flags() {
   perform_flag_test!(tok, set_is_initial, is_initial);
   perform_flag_test!(tok, set_is_non_punct, is_non_punct);
   perform_flag_test!(tok, set_is_alphabetic, is_alphabetic);
+  perform_flag_test!(tok, set_is_protected, is_protected);
+
+  tok.set_is_abbrev(true);
+  tok.set_is_numeric(true);
+
+  assert!(tok
+    .flags()
+    .contains(TokenFlags::IS_ABBREV | TokenFlags::IS_NUMERIC));
+  assert!(!tok.flags().intersects(TokenFlags::IS_INITIAL));
+  assert_eq!(
+    tok.flags().iter().collect::<Vec<_>>(),
+    vec![TokenFlags::IS_ABBREV, TokenFlags::IS_NUMERIC]
+  );
+  assert_eq!(
+    format!("{:?}", TokenFlags::from_bits(IS_ABBREV | 1 << 20)),
+    "TokenFlags(IS_ABBREV | 0x100000)"
+  );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_token_flags_serde() {
+  use serde_json;
+
+  let flags = TokenFlags::IS_ABBREV | TokenFlags::IS_NUMERIC;
+  let json = serde_json::to_string(&flags).unwrap();
+
+  assert_eq!(json, (IS_ABBREV | IS_NUMERIC).to_string());
+  assert_eq!(serde_json::from_str::<TokenFlags>(&json).unwrap(), flags);
+  assert_eq!(
+    serde_json::from_str::<Vec<TokenFlags>>("[0, 1048576]").unwrap(),
+    vec![TokenFlags::empty(), TokenFlags::from_bits(1 << 20)]
+  );
+  assert!(serde_json::from_str::<TokenFlags>("\"IS_ABBREV\"").is_err());
+}
+
+#[test]
+fn test_token_char_len() {
+  use prelude::Standard;
+
+  for &(slice, len, without) in [
+    ("Hello.", 6, 5),
+    ("Über", 4, 4),
+    ("naïve.", 6, 5),
+    (".", 1, 0),
+    ("\u{130}stanbul", 9, 9),
+  ]
+  .iter()
+  {
+    let tok = Token::new::<Standard>(slice, false, false, false);
+
+    assert_eq!(tok.char_len(), tok.tok().chars().count(), "{}", slice);
+    assert_eq!(tok.char_len(), len, "{}", slice);
+    assert_eq!(tok.char_len_without_period(), without, "{}", slice);
+  }
+}
+
+#[test]
+fn test_token_special_type() {
+  use prelude::Standard;
+
+  struct Custom;
+
+  impl DefinesTypeNormalization for Custom {
+    fn special_type_with_period(word: &str) -> Option<&'static str> {
+      let word = word.trim_end_matches('.');
+
+      if word.starts_with("http://") || word.starts_with("https://") {
+        Some("##url##.")
+      } else if word.len() == 4 && word.chars().all(|c| c.is_digit(10)) {
+        Some("##year##.")
+      } else {
+        Standard::special_type_with_period(word)
+      }
+    }
+  }
+
+  let year = Token::new::<Custom>("1999.", false, false, false);
+  let url = Token::new::<Custom>("https://example.com", false, false, false);
+  let number = Token::new::<Custom>("42", false, false, false);
+
+  assert_eq!(year.typ(), "##year##");
+  assert_eq!(year.typ_without_period(), "##year##");
+  assert_eq!(year.tok(), "1999.");
+  assert!(year.is_numeric());
+  assert_eq!(url.typ(), "##url##");
+  assert_eq!(url.typ_with_period(), "##url##.");
+  assert_eq!(number.typ(), "##number##");
+  assert_eq!(
+    Token::new::<Standard>("1999.", false, false, false).typ_with_period(),
+    "##number##."
+  );
+  assert_eq!(
+    Token::new::<Standard>("word", false, false, false).typ(),
+    "word"
+  );
+}
+
+#[test]
+fn test_token_type_accessors() {
+  use prelude::Standard;
+
+  for &(slice, tok, tok_np, typ, typ_wp, typ_np) in [
+    ("Mr.", "mr.", "mr", "mr.", "mr.", "mr"),
+    ("word", "word", "word", "word", "word.", "word"),
+    (
+      "1999.",
+      "1999.",
+      "1999",
+      "##number##",
+      "##number##.",
+      "##number##",
+    ),
+    ("42", "42", "42", "##number##", "##number##.", "##number##"),
+    (
+      "-3.5",
+      "-3.5",
+      "-3.5",
+      "##number##",
+      "##number##.",
+      "##number##",
+    ),
+    (".", ".", "", ".", ".", "."),
+    ("?", "?", "?", "?", "?.", "?"),
+    ("U.S.", "u.s.", "u.s", "u.s.", "u.s.", "u.s"),
+    (
+      "\u{130}.",
+      "i\u{307}.",
+      "i\u{307}",
+      "i\u{307}.",
+      "i\u{307}.",
+      "i\u{307}",
+    ),
+  ]
+  .iter()
+  {
+    let mut t = Token::new::<Standard>(slice, false, false, false);
+
+    assert_eq!(t.tok(), tok, "{}", slice);
+    assert_eq!(t.tok_without_period(), tok_np, "{}", slice);
+    assert_eq!(t.typ(), typ, "{}", slice);
+    assert_eq!(t.typ_with_period(), typ_wp, "{}", slice);
+    assert_eq!(t.typ_without_period(), typ_np, "{}", slice);
+    assert_eq!(t.typ_without_break_or_period(), typ, "{}", slice);
+
+    t.set_is_sentence_break(true);
+
+    assert_eq!(t.typ_without_break_or_period(), typ_np, "{}", slice);
+  }
+}
+
+#[test]
+fn test_normalize_type() {
+  use prelude::Standard;
+  use tokenizer::WordTokenizer;
+
+  let doc = include_str!("../test/raw/ny-times-article-02.txt");
+  let mut words = WordTokenizer::<Standard>::new(doc);
+
+  while let Some(t) = words.next() {
+    let (start, end) = words.last_span();
+
+    assert_eq!(normalize_type(&doc[start..end]), t.typ());
+  }
+
+  assert_eq!(
+    normalize_type("\u{39f}\u{394}\u{39f}\u{3a3}"),
+    "\u{3bf}\u{3b4}\u{3bf}\u{3c3}"
+  );
+  assert!(match normalize_type("lower") {
+    Cow::Borrowed(_) => true,
+    Cow::Owned(_) => false,
+  });
+}
+
+#[test]
+fn test_token_fold_case() {
+  use prelude::Standard;
+
+  struct Turkish;
+
+  impl DefinesTypeNormalization for Turkish {
+    fn fold_case(word: &str, buf: &mut String) {
+      for c in word.chars() {
+        match c {
+          'I' => buf.push('\u{131}'),
+          '\u{130}' => buf.push('i'),
+          _ => buf.extend(c.to_lowercase()),
+        }
+      }
+    }
+  }
+
+  let tok = Token::new::<Turkish>("ISTANBUL.", false, false, false);
+
+  assert_eq!(tok.tok(), "\u{131}stanbul.");
+  assert!(tok.has_final_period());
+  assert!(tok.is_uppercase());
+  assert_eq!(
+    Token::new::<Turkish>("\u{130}zmir", false, false, false).typ(),
+    "izmir"
+  );
+  assert_eq!(
+    Token::new::<Standard>("\u{130}zmir", false, false, false).typ(),
+    "i\u{307}zmir"
+  );
+  assert_eq!(
+    normalize_type_with_params::<Turkish>("Izmir"),
+    "\u{131}zmir"
+  );
+  assert_eq!(normalize_type("Izmir"), "izmir");
 }
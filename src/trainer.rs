@@ -6,23 +6,32 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::borrow::Cow;
 use std::cmp::min;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::default::Default;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::str::FromStr;
 
 use freqdist::FrequencyDistribution;
-use rustc_serialize::json::Json;
 
+use count::{ExactCounter, TypeCounter};
+use filter::{self, LookupFilter};
+use format::{LoadError, MODEL_VERSION};
+use json::Json;
+use memory::{self, MemoryBudget, MemoryReport};
+use metadata::{Metadata, ModelEntries, ParamsSnapshot, CRATE_VERSION};
+use ortho::OrthoContext;
 use prelude::{
-  DefinesNonPrefixCharacters, DefinesNonWordCharacters, OrthographicContext, OrthographyPosition,
-  TrainerParameters,
+  DefinesMultiChars, DefinesNonPrefixCharacters, DefinesNonWordCharacters,
+  DefinesTypeNormalization, OrthographicContext, Standard, TokenizerParameters, TrainerParameters,
 };
-use token::Token;
-use tokenizer::WordTokenizer;
+use score::{LowerBound, Score, ScoreMath};
+use token::{normalize_type, OrthographyPosition, Token};
+use tokenizer::{SentenceByteOffsetTokenizer, WordTokenizer};
 use util;
 
 /// A collocation is any pair of words that has a high likelihood of appearing
@@ -81,6 +90,24 @@ where
   }
 }
 
+/// Why a model considers a token to be an abbreviation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provenance {
+  /// Learned by a trainer. The score is the one the abbreviation had, if it
+  /// was scored. Rare abbreviations are learned without a score.
+  Trained {
+    /// The abbreviation score, compared with `ABBREV_LOWER_BOUND`.
+    score: Option<f64>,
+  },
+  /// Loaded with the model, like the bundled models, or a model file. The
+  /// provenance of abbreviations isn't stored in model files, so a trained
+  /// model that was saved, and loaded again reports this.
+  Seeded,
+  /// Inserted by a user, through an abbreviation list, an overlay, or a
+  /// correction to a `Segmenter`.
+  Inserted,
+}
+
 /// Stores data that was obtained during training.
 ///
 /// # Examples
@@ -88,20 +115,92 @@ where
 /// Precompiled data can be loaded via a language specific constructor.
 ///
 /// ```
+/// # #[cfg(feature = "english")]
+/// # {
 /// # use punkt::TrainingData;
 /// #
 /// let eng_data = TrainingData::english();
-/// let ger_data = TrainingData::german();
 ///
 /// assert!(eng_data.contains_abbrev("va"));
+/// # #[cfg(feature = "german")]
+/// # {
+/// let ger_data = TrainingData::german();
+///
 /// assert!(ger_data.contains_abbrev("crz"));
+/// # }
+/// # }
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone)]
 pub struct TrainingData {
   abbrevs: HashSet<String>,
+  abbrev_provenance: HashMap<String, Provenance>,
   collocations: HashMap<String, HashSet<String>>,
+  abbrev_contexts: HashMap<String, HashSet<String>>,
   sentence_starters: HashSet<String>,
   orthographic_context: HashMap<String, OrthographicContext>,
+  filter: Option<LookupFilter>,
+  metadata: Metadata,
+  pub(crate) version: u32,
+}
+
+impl Default for TrainingData {
+  #[inline]
+  fn default() -> TrainingData {
+    TrainingData {
+      abbrevs: HashSet::new(),
+      abbrev_provenance: HashMap::new(),
+      collocations: HashMap::new(),
+      abbrev_contexts: HashMap::new(),
+      sentence_starters: HashSet::new(),
+      orthographic_context: HashMap::new(),
+      filter: None,
+      metadata: Metadata::default(),
+      version: MODEL_VERSION,
+    }
+  }
+}
+
+/// Compares what the models know. Metadata, the version, and whether a
+/// lookup filter is enabled aren't compared, and neither is the order that
+/// things were learned in.
+impl PartialEq for TrainingData {
+  fn eq(&self, other: &TrainingData) -> bool {
+    let is_set = |&(_, c): &(&str, OrthoContext)| !c.is_empty();
+
+    self.abbrevs == other.abbrevs
+      && self.sentence_starters == other.sentence_starters
+      && self.collocations().count() == other.collocations().count()
+      && self
+        .collocations()
+        .all(|(l, r)| other.contains_collocation(l, r))
+      && self.abbrev_contexts().count() == other.abbrev_contexts().count()
+      && self
+        .abbrev_contexts()
+        .all(|(a, r)| other.contains_abbrev_context(a, r))
+      && self.orthographic_contexts().filter(&is_set).count()
+        == other.orthographic_contexts().filter(&is_set).count()
+      && self
+        .orthographic_contexts()
+        .filter(&is_set)
+        .all(|(tok, c)| other.get_orthographic_context(tok) == c)
+  }
+}
+
+impl Eq for TrainingData {}
+
+/// Summarizes the sizes of the model, instead of listing its contents.
+impl fmt::Debug for TrainingData {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("TrainingData")
+      .field("abbrevs", &self.abbrevs.len())
+      .field("collocations", &self.collocations().count())
+      .field("abbrev_contexts", &self.abbrev_contexts().count())
+      .field("sentence_starters", &self.sentence_starters.len())
+      .field("orthographic_contexts", &self.orthographic_context.len())
+      .field("version", &self.version)
+      .field("lookup_filter", &self.filter.is_some())
+      .finish()
+  }
 }
 
 impl TrainingData {
@@ -113,17 +212,235 @@ impl TrainingData {
     }
   }
 
+  /// Creates new data, trained on one or more documents with the standard
+  /// parameters. The counts of the documents are merged, so this is the same
+  /// as training once on all of them, with each document starting a new
+  /// paragraph.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use punkt::TrainingData;
+  /// #
+  /// let docs = ["The U.S. economy grew.", "The U.S. team won. It rained."];
+  /// let data = TrainingData::trained_on(&docs);
+  ///
+  /// assert!(data.contains_abbrev("u.s"));
+  /// ```
+  pub fn trained_on<I>(docs: I) -> TrainingData
+  where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+  {
+    let mut counts = CorpusCounts::new();
+
+    for doc in docs {
+      counts.merge(corpus_counts::<Standard>(doc.as_ref()));
+    }
+
+    Trainer::<Standard>::new().finalize_counts_owned(counts, TrainingData::new())
+  }
+
+  /// The provenance of the data.
+  #[inline(always)]
+  pub fn metadata(&self) -> &Metadata {
+    &self.metadata
+  }
+
+  /// The provenance of the data, which can be modified.
+  #[inline(always)]
+  pub fn metadata_mut(&mut self) -> &mut Metadata {
+    &mut self.metadata
+  }
+
+  /// The version of the model format the data was loaded from. Data that
+  /// was created in memory is always the current `MODEL_VERSION`.
+  #[inline(always)]
+  pub fn version(&self) -> u32 {
+    self.version
+  }
+
+  /// Upgrades data loaded from an older version of the model format to the
+  /// current `MODEL_VERSION`. Returns false if the data was already
+  /// current.
+  ///
+  /// Version 0 models (the JSON files converted from NLTK) may contain
+  /// collocations with uppercase characters, and orthographic contexts with
+  /// unknown flags set, which are never matched by the tokenizers.
+  pub fn migrate(&mut self) -> bool {
+    use prelude::{ORT_LC, ORT_UC};
+
+    if self.version >= MODEL_VERSION {
+      return false;
+    }
+
+    if self.version < 1 {
+      let collocations: Vec<(String, String)> = self
+        .collocations()
+        .map(|(l, r)| (l.to_lowercase(), r.to_lowercase()))
+        .collect();
+      let orthographic_context: Vec<(String, OrthographicContext)> = self
+        .orthographic_contexts()
+        .map(|(tok, ctxt)| (tok.to_string(), ctxt.bits() & (ORT_UC | ORT_LC)))
+        .collect();
+
+      self.collocations.clear();
+      self.orthographic_context.clear();
+
+      for (l, r) in collocations {
+        self.insert_collocation(&l, &r);
+      }
+
+      for (tok, ctxt) in orthographic_context {
+        self.insert_orthographic_context(&tok, ctxt);
+      }
+    }
+
+    self.version = MODEL_VERSION;
+
+    if self.filter.is_some() {
+      self.enable_lookup_filter();
+    }
+
+    true
+  }
+
+  /// Builds a compact filter over all of the data, that is consulted before
+  /// doing any lookups. Most tokens in a document are not abbreviations,
+  /// collocations, or sentence starters, so this skips most of the hashing
+  /// when tokenizing large documents with large models. The filter is kept up
+  /// to date when data is inserted.
+  pub fn enable_lookup_filter(&mut self) {
+    let len = self.abbrevs.len()
+      + self.collocations.values().map(|s| s.len()).sum::<usize>()
+      + self.sentence_starters.len()
+      + self.orthographic_context.len();
+    let mut lookup = LookupFilter::with_capacity(len * 2);
+
+    for tok in self.abbrevs.iter() {
+      lookup.insert(filter::ABBREV, &[tok]);
+    }
+
+    for (l, rs) in self.collocations.iter() {
+      for r in rs.iter() {
+        lookup.insert(filter::COLLOCATION, &[l, r]);
+      }
+    }
+
+    for tok in self.sentence_starters.iter() {
+      lookup.insert(filter::SENTENCE_STARTER, &[tok]);
+    }
+
+    for tok in self.orthographic_context.keys() {
+      lookup.insert(filter::ORTHOGRAPHIC_CONTEXT, &[tok]);
+    }
+
+    self.filter = Some(lookup);
+  }
+
+  /// Removes the lookup filter.
+  #[inline]
+  pub fn disable_lookup_filter(&mut self) {
+    self.filter = None;
+  }
+
+  /// Checks if a key might be in the data. Always true if there is no filter.
+  #[inline(always)]
+  fn may_contain(&self, tag: u8, parts: &[&str]) -> bool {
+    match self.filter {
+      Some(ref lookup) => lookup.may_contain(tag, parts),
+      None => true,
+    }
+  }
+
+  /// Adds a key to the lookup filter, if there is one. Rebuilds the filter
+  /// from the sets if it has grown too large, so the key has to be inserted
+  /// into its set first.
+  #[inline]
+  fn update_filter(&mut self, tag: u8, parts: &[&str]) {
+    let is_full = match self.filter {
+      Some(ref mut lookup) => {
+        lookup.insert(tag, parts);
+        lookup.is_full()
+      }
+      None => false,
+    };
+
+    if is_full {
+      self.enable_lookup_filter();
+    }
+  }
+
   /// Check if a token is considered to be an abbreviation.
   #[inline(always)]
   pub fn contains_abbrev(&self, tok: &str) -> bool {
-    self.abbrevs.contains(tok)
+    self.may_contain(filter::ABBREV, &[tok]) && self.abbrevs.contains(tok)
+  }
+
+  /// Returns why a token is considered to be an abbreviation, or `None` if
+  /// it isn't one.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::{Provenance, TrainingData};
+  /// #
+  /// let mut data = TrainingData::english();
+  ///
+  /// data.import_abbrev_list("acct.".as_bytes()).unwrap();
+  ///
+  /// assert_eq!(data.abbrev_provenance("jan"), Some(Provenance::Seeded));
+  /// assert_eq!(data.abbrev_provenance("acct"), Some(Provenance::Inserted));
+  /// assert_eq!(data.abbrev_provenance("the"), None);
+  /// # }
+  /// ```
+  #[inline]
+  pub fn abbrev_provenance(&self, tok: &str) -> Option<Provenance> {
+    if self.contains_abbrev(tok) {
+      Some(
+        self
+          .abbrev_provenance
+          .get(tok)
+          .cloned()
+          .unwrap_or(Provenance::Seeded),
+      )
+    } else {
+      None
+    }
+  }
+
+  /// Insert an abbreviation that was loaded with the model.
+  #[inline(always)]
+  pub(crate) fn insert_abbrev(&mut self, tok: &str) -> bool {
+    self.insert_abbrev_with_provenance(tok, Provenance::Seeded)
   }
 
-  /// Insert a newly learned abbreviation.
+  /// Insert a newly learned abbreviation, and records why it was learned.
+  /// Only allocates if the abbreviation is actually inserted, or has to be
+  /// lowercased.
   #[inline]
-  fn insert_abbrev(&mut self, tok: &str) -> bool {
-    if !self.contains_abbrev(tok) {
-      self.abbrevs.insert(tok.to_lowercase())
+  pub(crate) fn insert_abbrev_with_provenance(
+    &mut self,
+    tok: &str,
+    provenance: Provenance,
+  ) -> bool {
+    let tok: Cow<str> = if tok.chars().any(char::is_uppercase) {
+      Cow::Owned(tok.to_lowercase())
+    } else {
+      Cow::Borrowed(tok)
+    };
+
+    if !self.contains_abbrev(&tok) {
+      if provenance != Provenance::Seeded {
+        self.abbrev_provenance.insert(tok.to_string(), provenance);
+      }
+
+      let inserted = self.abbrevs.insert(tok.to_string());
+
+      self.update_filter(filter::ABBREV, &[&tok]);
+      inserted
     } else {
       false
     }
@@ -131,7 +448,8 @@ impl TrainingData {
 
   /// Removes a learned abbreviation.
   #[inline]
-  fn remove_abbrev(&mut self, tok: &str) -> bool {
+  pub(crate) fn remove_abbrev(&mut self, tok: &str) -> bool {
+    self.abbrev_provenance.remove(tok);
     self.abbrevs.remove(tok)
   }
 
@@ -139,14 +457,17 @@ impl TrainingData {
   /// sentence.
   #[inline(always)]
   pub fn contains_sentence_starter(&self, tok: &str) -> bool {
-    self.sentence_starters.contains(tok)
+    self.may_contain(filter::SENTENCE_STARTER, &[tok]) && self.sentence_starters.contains(tok)
   }
 
   /// Insert a newly learned word that signifies the start of a sentence.
   #[inline]
-  fn insert_sentence_starter(&mut self, tok: &str) -> bool {
+  pub(crate) fn insert_sentence_starter(&mut self, tok: &str) -> bool {
     if !self.contains_sentence_starter(tok) {
-      self.sentence_starters.insert(tok.to_string())
+      let inserted = self.sentence_starters.insert(tok.to_string());
+
+      self.update_filter(filter::SENTENCE_STARTER, &[tok]);
+      inserted
     } else {
       false
     }
@@ -155,66 +476,352 @@ impl TrainingData {
   /// Checks if a pair of words are commonly known to appear together.
   #[inline]
   pub fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    self.may_contain(filter::COLLOCATION, &[left, right])
+      && self
+        .collocations
+        .get(left)
+        .map(|s| s.contains(right))
+        .unwrap_or(false)
+  }
+
+  /// Insert a newly learned pair of words that frequently appear together.
+  /// Only allocates if the pair is actually inserted.
+  pub(crate) fn insert_collocation(&mut self, left: &str, right: &str) -> bool {
+    if self.contains_collocation(left, right) {
+      return false;
+    }
+
+    let inserted = match self.collocations.get_mut(left) {
+      Some(rs) => rs.insert(right.to_string()),
+      None => {
+        let mut rs = HashSet::new();
+
+        rs.insert(right.to_string());
+        self.collocations.insert(left.to_string(), rs);
+        true
+      }
+    };
+
+    self.update_filter(filter::COLLOCATION, &[left, right]);
+    inserted
+  }
+
+  /// Removes a pair of words that were known to appear together.
+  #[inline]
+  pub(crate) fn remove_collocation(&mut self, left: &str, right: &str) -> bool {
     self
       .collocations
-      .get(left)
+      .get_mut(left)
+      .map(|rs| rs.remove(right))
+      .unwrap_or(false)
+  }
+
+  /// Checks if the next word is in the right contexts of an abbreviation.
+  #[inline]
+  pub fn contains_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    self
+      .abbrev_contexts
+      .get(abbrev)
       .map(|s| s.contains(right))
       .unwrap_or(false)
   }
 
-  /// Insert a newly learned pair of words that frequently appear together.
-  fn insert_collocation(&mut self, left: &str, right: &str) -> bool {
-    if !self.collocations.contains_key(left) {
-      self.collocations.insert(left.to_string(), HashSet::new());
-    }
+  /// Checks if an abbreviation can be followed by a word without ending the
+  /// sentence. True for abbreviations without right contexts, which never end
+  /// a sentence by themselves.
+  #[inline]
+  pub fn allows_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    self
+      .abbrev_contexts
+      .get(abbrev)
+      .map(|s| s.contains(right))
+      .unwrap_or(true)
+  }
 
-    if !self.collocations.get(left).unwrap().contains(right) {
-      self
-        .collocations
-        .get_mut(left)
-        .unwrap()
-        .insert(right.to_string());
-      true
-    } else {
-      false
+  /// Restricts an abbreviation to only continue a sentence before the
+  /// words it was given as right contexts. Before any other word, the period
+  /// of the abbreviation ends the sentence. Returns false if the word was
+  /// already a right context of the abbreviation.
+  ///
+  /// Words are stored as their type, so numbers are matched by any number.
+  /// The contexts are only used while the abbreviation is known, and aren't
+  /// compiled by `codegen::emit_rust`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::{SentenceTokenizer, TrainingData};
+  /// # use punkt::params::Standard;
+  /// #
+  /// let mut data = TrainingData::english();
+  ///
+  /// data.import_abbrev_list("no.".as_bytes()).unwrap();
+  /// data.insert_abbrev_context("no", "5");
+  ///
+  /// let doc = "It is no. 5 on the list. I said no. then I left.";
+  /// let sents: Vec<&str> = SentenceTokenizer::<Standard, _>::new(doc, &data).collect();
+  ///
+  /// assert_eq!(sents, vec!["It is no. 5 on the list.", "I said no.", "then I left."]);
+  /// # }
+  /// ```
+  pub fn insert_abbrev_context(&mut self, abbrev: &str, right: &str) -> bool {
+    let right = normalize_type(&right.to_lowercase()).into_owned();
+
+    self
+      .abbrev_contexts
+      .entry(abbrev.to_lowercase())
+      .or_insert_with(HashSet::new)
+      .insert(right)
+  }
+
+  /// Removes a right context of an abbreviation. The abbreviation continues
+  /// sentences before any word once it has no right contexts left.
+  pub fn remove_abbrev_context(&mut self, abbrev: &str, right: &str) -> bool {
+    let abbrev = abbrev.to_lowercase();
+    let right = normalize_type(&right.to_lowercase()).into_owned();
+    let removed = match self.abbrev_contexts.get_mut(&abbrev) {
+      Some(rs) => rs.remove(&right[..]),
+      None => false,
+    };
+
+    if self
+      .abbrev_contexts
+      .get(&abbrev)
+      .map(|rs| rs.is_empty())
+      .unwrap_or(false)
+    {
+      self.abbrev_contexts.remove(&abbrev);
     }
+
+    removed
   }
 
   /// Insert or update the known orthographic context that a word commonly
-  /// appears in.
-  #[inline]
-  fn insert_orthographic_context(&mut self, tok: &str, ctxt: OrthographicContext) -> bool {
-    // `get_mut` isn't allowed here, without adding an unnecessary lifetime
-    // qualifier to `tok`.
-    match self.orthographic_context.get_mut(tok) {
-      Some(c) => {
-        *c |= ctxt;
-        return false;
-      }
-      None => (),
+  /// appears in. Only allocates if the word is actually inserted.
+  #[inline]
+  pub(crate) fn insert_orthographic_context(
+    &mut self,
+    tok: &str,
+    ctxt: OrthographicContext,
+  ) -> bool {
+    if let Some(c) = self.orthographic_context.get_mut(tok) {
+      *c |= ctxt;
+      return false;
     }
 
     self.orthographic_context.insert(tok.to_string(), ctxt);
+    self.update_filter(filter::ORTHOGRAPHIC_CONTEXT, &[tok]);
     true
   }
 
-  /// Gets the orthographic context for a token. Returns 0 if the token
-  /// was not yet encountered.
+  /// Gets the orthographic context for a token. Returns an empty context if
+  /// the token was not yet encountered.
   #[inline(always)]
-  pub fn get_orthographic_context(&self, tok: &str) -> u8 {
-    *self.orthographic_context.get(tok).unwrap_or(&0)
+  pub fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    if self.may_contain(filter::ORTHOGRAPHIC_CONTEXT, &[tok]) {
+      OrthoContext::from_bits(*self.orthographic_context.get(tok).unwrap_or(&0))
+    } else {
+      OrthoContext::empty()
+    }
+  }
+
+  /// Estimates the heap memory used by each part of the data, to decide what
+  /// to prune, or how much memory a model needs.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "english")]
+  /// # {
+  /// # use punkt::TrainingData;
+  /// #
+  /// let report = TrainingData::english().memory_footprint();
+  ///
+  /// assert!(report.orthographic_contexts > report.abbrevs);
+  /// assert_eq!(report.lookup_filter, 0);
+  /// # }
+  /// ```
+  pub fn memory_footprint(&self) -> MemoryReport {
+    let nested = |map: &HashMap<String, HashSet<String>>| {
+      memory::map_size(map) + map.values().map(memory::set_size).sum::<usize>()
+    };
+
+    MemoryReport {
+      abbrevs: memory::set_size(&self.abbrevs) + memory::map_size(&self.abbrev_provenance),
+      collocations: nested(&self.collocations),
+      abbrev_contexts: nested(&self.abbrev_contexts),
+      sentence_starters: memory::set_size(&self.sentence_starters),
+      orthographic_contexts: memory::map_size(&self.orthographic_context),
+      lookup_filter: self
+        .filter
+        .as_ref()
+        .map(LookupFilter::heap_size)
+        .unwrap_or(0),
+    }
+  }
+
+  /// Returns an iterator over the learned abbreviations.
+  #[inline]
+  pub fn abbrevs(&self) -> impl Iterator<Item = &str> {
+    self.abbrevs.iter().map(|s| &s[..])
+  }
+
+  /// Returns an iterator over the learned collocations.
+  #[inline]
+  pub fn collocations(&self) -> impl Iterator<Item = (&str, &str)> {
+    self
+      .collocations
+      .iter()
+      .flat_map(|(l, rs)| rs.iter().map(move |r| (&l[..], &r[..])))
+  }
+
+  /// Returns an iterator over the abbreviations with right contexts, and
+  /// their contexts. See `insert_abbrev_context`.
+  #[inline]
+  pub fn abbrev_contexts(&self) -> impl Iterator<Item = (&str, &str)> {
+    self
+      .abbrev_contexts
+      .iter()
+      .flat_map(|(a, rs)| rs.iter().map(move |r| (&a[..], &r[..])))
+  }
+
+  /// Returns an iterator over the learned sentence starters.
+  #[inline]
+  pub fn sentence_starters(&self) -> impl Iterator<Item = &str> {
+    self.sentence_starters.iter().map(|s| &s[..])
+  }
+
+  /// Returns an iterator over the tokens with a learned orthographic context,
+  /// and their context.
+  #[inline]
+  pub fn orthographic_contexts(&self) -> impl Iterator<Item = (&str, OrthoContext)> {
+    self
+      .orthographic_context
+      .iter()
+      .map(|(s, c)| (&s[..], OrthoContext::from_bits(*c)))
+  }
+
+  /// Takes the abbreviations, and sentence starters out of the data, leaving
+  /// both sets empty. Used to freeze the data.
+  #[cfg(feature = "fst")]
+  #[inline]
+  pub(crate) fn take_abbrevs_and_starters(&mut self) -> (HashSet<String>, HashSet<String>) {
+    (
+      ::std::mem::replace(&mut self.abbrevs, HashSet::new()),
+      ::std::mem::replace(&mut self.sentence_starters, HashSet::new()),
+    )
+  }
+
+  /// Puts back the abbreviations, and sentence starters that were taken out
+  /// of the data. Used to thaw frozen data.
+  #[cfg(feature = "fst")]
+  #[inline]
+  pub(crate) fn restore_abbrevs_and_starters(
+    &mut self,
+    abbrevs: HashSet<String>,
+    sentence_starters: HashSet<String>,
+  ) {
+    self.abbrevs = abbrevs;
+    self.sentence_starters = sentence_starters;
+  }
+
+  /// Takes the provenance of the abbreviations out of the data. Used to
+  /// freeze the data.
+  #[cfg(feature = "fst")]
+  #[inline]
+  pub(crate) fn take_abbrev_provenance(&mut self) -> HashMap<String, Provenance> {
+    ::std::mem::replace(&mut self.abbrev_provenance, HashMap::new())
+  }
+
+  /// Puts back the provenance of the abbreviations that was taken out of the
+  /// data. Used to thaw frozen data.
+  #[cfg(feature = "fst")]
+  #[inline]
+  pub(crate) fn restore_abbrev_provenance(&mut self, provenance: HashMap<String, Provenance>) {
+    self.abbrev_provenance = provenance;
   }
 }
 
-impl FromStr for TrainingData {
-  type Err = &'static str;
+impl TrainingData {
+  /// Inserts the entries of an exported model, that were read by a loader.
+  pub(crate) fn insert_entries(&mut self, entries: &ModelEntries) {
+    for s in entries.abbrevs.iter() {
+      self.insert_abbrev(s);
+    }
+
+    for &(l, r) in entries.collocations.iter() {
+      self.insert_collocation(l, r);
+    }
 
+    for s in entries.sentence_starters.iter() {
+      self.insert_sentence_starter(s);
+    }
+
+    for &(s, c) in entries.orthographic_contexts.iter() {
+      self.insert_orthographic_context(s, c);
+    }
+
+    for &(a, r) in entries.abbrev_contexts.iter() {
+      self.insert_abbrev_context(a, r);
+    }
+  }
+}
+
+impl TrainingData {
   /// Deserializes JSON and loads the data into a new TrainingData object.
-  fn from_str(s: &str) -> Result<TrainingData, &'static str> {
+  #[inline]
+  pub(crate) fn from_json(s: &str) -> Result<TrainingData, LoadError> {
     match Json::from_str(s) {
-      Ok(Json::Object(mut obj)) => {
+      Ok(json) => TrainingData::from_json_value(json),
+      Err(_) => Err(LoadError::Json(
+        "no json object found containing training data",
+      )),
+    }
+  }
+
+  /// Loads the data from a parsed JSON object.
+  pub(crate) fn from_json_value(json: Json) -> Result<TrainingData, LoadError> {
+    match json {
+      Json::Object(mut obj) => {
         let mut data: TrainingData = Default::default();
 
+        // Models without a version predate versioning.
+        data.version = match obj.remove("version") {
+          Some(Json::U64(v)) if v <= MODEL_VERSION as u64 => v as u32,
+          Some(Json::U64(v)) => return Err(LoadError::UnsupportedVersion(v as u32)),
+          Some(_) => return Err(LoadError::Json("failed to parse version")),
+          None => 0,
+        };
+
+        let mut checksum = None;
+
+        match obj.remove("metadata") {
+          Some(Json::Object(meta)) => {
+            for (k, v) in meta.into_iter() {
+              match (&k[..], v) {
+                ("language", Json::String(st)) => data.metadata.set_language(st),
+                ("corpus", Json::String(st)) => data.metadata.set_corpus(st),
+                ("crate_version", Json::String(st)) => data.metadata.set_crate_version(st),
+                ("params", params) => match ParamsSnapshot::from_json(params) {
+                  Some(params) => data.metadata.set_params(params),
+                  None => return Err(LoadError::Json("failed to parse params")),
+                },
+                ("checksum", Json::String(st)) => match u64::from_str_radix(&st, 16) {
+                  Ok(h) => checksum = Some(h),
+                  Err(_) => return Err(LoadError::Json("failed to parse checksum")),
+                },
+                // Unknown keys are skipped, so metadata can be added without
+                // a new version.
+                _ => (),
+              }
+            }
+          }
+          Some(_) => return Err(LoadError::Json("failed to parse metadata section")),
+          None => (),
+        }
+
         // Macro that gets a Json array by a path on the object. Then does a
         // pattern match on a specified pattern, and runs a specified action.
         macro_rules! read_json_array_data(
@@ -228,50 +835,461 @@ impl FromStr for TrainingData {
                   }
                 }
               }
-              _ => return Err("failed to parse expected path")
+              _ => return Err(LoadError::Json("failed to parse expected path"))
             }
           );
         );
 
-        read_json_array_data!(
-          "abbrev_types",
-          Json::String(st),
-          data.insert_abbrev(&st[..])
-        );
+        // The entries are collected as they were stored, to check the
+        // checksum against, before they are inserted.
+        let mut abbrevs = Vec::new();
+        let mut sentence_starters = Vec::new();
+        let mut collocations = Vec::new();
+        let mut orthographic_contexts = Vec::new();
+        let mut abbrev_contexts = Vec::new();
+
+        read_json_array_data!("abbrev_types", Json::String(st), abbrevs.push(st));
+
+        read_json_array_data!(
+          "sentence_starters",
+          Json::String(st),
+          sentence_starters.push(st)
+        );
+
+        // Load collocations, these come as an array with 2 members in them (or they should).
+        // Pop them in reverse order.
+        read_json_array_data!("collocations", Json::Array(mut ar), {
+          match (ar.pop(), ar.pop()) {
+            (Some(Json::String(r)), Some(Json::String(l))) => collocations.push((l, r)),
+            _ => return Err(LoadError::Json("failed to parse collocations section")),
+          };
+        });
+
+        match obj.remove("ortho_context") {
+          Some(Json::Object(obj)) => {
+            for (k, ctxt) in obj.into_iter() {
+              if let Some(c) = ctxt.as_u64() {
+                orthographic_contexts.push((k, c as u8));
+              }
+            }
+          }
+          _ => {
+            return Err(LoadError::Json(
+              "failed to parse orthographic context section",
+            ))
+          }
+        }
+
+        // Only written if there are any.
+        match obj.remove("abbrev_contexts") {
+          Some(Json::Array(arr)) => {
+            for x in arr.into_iter() {
+              match x {
+                Json::Array(mut ar) => match (ar.pop(), ar.pop()) {
+                  (Some(Json::String(r)), Some(Json::String(a))) => {
+                    abbrev_contexts.push((a, r));
+                  }
+                  _ => return Err(LoadError::Json("failed to parse abbreviation contexts")),
+                },
+                _ => return Err(LoadError::Json("failed to parse abbreviation contexts")),
+              }
+            }
+          }
+          Some(_) => return Err(LoadError::Json("failed to parse abbreviation contexts")),
+          None => (),
+        }
+
+        let mut entries = ModelEntries {
+          abbrevs: abbrevs.iter().map(|s| &s[..]).collect(),
+          collocations: collocations
+            .iter()
+            .map(|&(ref l, ref r)| (&l[..], &r[..]))
+            .collect(),
+          sentence_starters: sentence_starters.iter().map(|s| &s[..]).collect(),
+          orthographic_contexts: orthographic_contexts
+            .iter()
+            .map(|&(ref s, c)| (&s[..], c))
+            .collect(),
+          abbrev_contexts: abbrev_contexts
+            .iter()
+            .map(|&(ref a, ref r)| (&a[..], &r[..]))
+            .collect(),
+        };
+
+        match checksum {
+          Some(h) if h != entries.hash() => Err(LoadError::ChecksumMismatch),
+          _ => {
+            data.insert_entries(&entries);
+            Ok(data)
+          }
+        }
+      }
+      _ => Err(LoadError::Json(
+        "no json object found containing training data",
+      )),
+    }
+  }
+
+  /// Serializes the data into JSON, in the same format as the pretrained
+  /// models, with the current `MODEL_VERSION`, the metadata, and a checksum.
+  /// Entries are written in sorted order, so the output is deterministic.
+  #[inline]
+  pub fn to_json(&self) -> String {
+    self.to_json_value().to_string()
+  }
+
+  /// Serializes the data into a JSON object.
+  pub(crate) fn to_json_value(&self) -> Json {
+    let mut obj = BTreeMap::new();
+    let mut meta = BTreeMap::new();
+    let mut collocations: Vec<(&str, &str)> = self.collocations().collect();
+
+    collocations.sort();
+
+    for &(k, v) in [
+      ("language", self.metadata.language()),
+      ("corpus", self.metadata.corpus()),
+      ("crate_version", self.metadata.crate_version()),
+    ]
+    .iter()
+    {
+      if let Some(v) = v {
+        meta.insert(k.to_string(), Json::String(v.to_string()));
+      }
+    }
+
+    if let Some(params) = self.metadata.params() {
+      meta.insert("params".to_string(), params.to_json());
+    }
+
+    meta.insert(
+      "checksum".to_string(),
+      Json::String(format!("{:016x}", self.content_hash())),
+    );
+
+    obj.insert("version".to_string(), Json::U64(MODEL_VERSION as u64));
+    obj.insert("metadata".to_string(), Json::Object(meta));
+    obj.insert(
+      "abbrev_types".to_string(),
+      sorted_json_array(self.abbrevs()),
+    );
+    obj.insert(
+      "sentence_starters".to_string(),
+      sorted_json_array(self.sentence_starters()),
+    );
+    obj.insert(
+      "collocations".to_string(),
+      Json::Array(
+        collocations
+          .into_iter()
+          .map(|(l, r)| {
+            Json::Array(vec![
+              Json::String(l.to_string()),
+              Json::String(r.to_string()),
+            ])
+          })
+          .collect(),
+      ),
+    );
+    obj.insert(
+      "ortho_context".to_string(),
+      Json::Object(
+        self
+          .orthographic_contexts()
+          .map(|(tok, ctxt)| (tok.to_string(), Json::U64(ctxt.bits() as u64)))
+          .collect(),
+      ),
+    );
+
+    let mut abbrev_contexts: Vec<(&str, &str)> = self.abbrev_contexts().collect();
+
+    if !abbrev_contexts.is_empty() {
+      abbrev_contexts.sort();
+      obj.insert(
+        "abbrev_contexts".to_string(),
+        Json::Array(
+          abbrev_contexts
+            .into_iter()
+            .map(|(a, r)| {
+              Json::Array(vec![
+                Json::String(a.to_string()),
+                Json::String(r.to_string()),
+              ])
+            })
+            .collect(),
+        ),
+      );
+    }
+
+    Json::Object(obj)
+  }
+}
+
+#[inline]
+fn sorted_json_array<'a, I>(iter: I) -> Json
+where
+  I: Iterator<Item = &'a str>,
+{
+  let mut v: Vec<&str> = iter.collect();
+
+  v.sort();
+  Json::Array(v.into_iter().map(|s| Json::String(s.to_string())).collect())
+}
+
+impl FromStr for TrainingData {
+  type Err = &'static str;
+
+  /// Deserializes JSON and loads the data into a new TrainingData object.
+  #[inline]
+  fn from_str(s: &str) -> Result<TrainingData, &'static str> {
+    TrainingData::from_json(s).map_err(|e| e.message())
+  }
+}
+
+/// The tokens of a corpus, and the counts over them that do not depend on
+/// any `TrainingData`. Counting is the part of training that doesn't
+/// depend on the data learned so far, so the counts of separate documents
+/// can be gathered in parallel with `corpus_counts`, combined with `merge`,
+/// then passed to `Trainer::finalize_counts`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::thread;
+/// # use punkt::{corpus_counts, CorpusCounts, Trainer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let docs = vec!["This is a doc. It has sentences.", "This is another one."];
+/// let handles: Vec<_> = docs
+///   .into_iter()
+///   .map(|doc| thread::spawn(move || corpus_counts::<Standard>(doc)))
+///   .collect();
+/// let mut counts = CorpusCounts::new();
+///
+/// for handle in handles {
+///   counts.merge(handle.join().unwrap());
+/// }
+///
+/// let mut data = TrainingData::new();
+/// let trainer: Trainer<Standard> = Trainer::new();
+///
+/// trainer.finalize_counts(counts, &mut data);
+/// ```
+#[derive(Clone, Default)]
+pub struct CorpusCounts {
+  tokens: Vec<Token>,
+  period_token_count: usize,
+  // The index of the first token of each run of tokens with the same weight,
+  // and the weight.
+  weights: Vec<(usize, usize)>,
+}
+
+impl CorpusCounts {
+  /// Creates empty counts.
+  #[inline(always)]
+  pub fn new() -> CorpusCounts {
+    Default::default()
+  }
+
+  /// Appends the counts of another document, which starts a new paragraph.
+  /// Merging is associative, but the order of the documents is kept, since
+  /// the trainer looks at consecutive tokens. Splitting a document on
+  /// paragraph boundaries, and merging the counts of each part in order
+  /// yields the same counts as the entire document.
+  #[inline]
+  pub fn merge(&mut self, mut other: CorpusCounts) {
+    if !self.tokens.is_empty() {
+      if let Some(t) = other.tokens.first_mut() {
+        t.set_is_paragraph_start(true);
+      }
+    }
+
+    let offset = self.tokens.len();
+
+    self
+      .weights
+      .extend(other.weights.into_iter().map(|(i, w)| (i + offset, w)));
+    self.tokens.extend(other.tokens);
+    self.period_token_count += other.period_token_count;
+  }
+
+  /// Scales the counts by a weight, as if the documents were seen `weight`
+  /// times as often. Weighting lets a small corpus count for more when it
+  /// is merged with a larger one. Orthographic contexts don't depend on
+  /// counts, so they are learned from every document regardless of weight.
+  #[inline]
+  pub fn weighted(mut self, weight: usize) -> CorpusCounts {
+    for run in self.weights.iter_mut() {
+      run.1 *= weight;
+    }
+
+    self.period_token_count *= weight;
+    self
+  }
+
+  /// The weight of each token.
+  fn token_weights(&self) -> Vec<usize> {
+    let mut weights = Vec::with_capacity(self.tokens.len());
+
+    for (i, &(start, w)) in self.weights.iter().enumerate() {
+      let end = self
+        .weights
+        .get(i + 1)
+        .map(|&(next, _)| next)
+        .unwrap_or(self.tokens.len());
+
+      weights.extend((start..end).map(|_| w));
+    }
+
+    weights
+  }
+
+  /// The number of tokens that were counted.
+  #[inline(always)]
+  pub fn token_count(&self) -> usize {
+    self.tokens.len()
+  }
+}
+
+impl fmt::Debug for CorpusCounts {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("CorpusCounts")
+      .field("token_count", &self.tokens.len())
+      .field("period_token_count", &self.period_token_count)
+      .finish()
+  }
+}
+
+/// Tokenizes a document, and counts what the trainer needs to know about
+/// it. This is a pure function, so it can be run on any executor.
+pub fn corpus_counts<P>(doc: &str) -> CorpusCounts
+where
+  P: DefinesMultiChars
+    + DefinesNonPrefixCharacters
+    + DefinesNonWordCharacters
+    + DefinesTypeNormalization,
+{
+  let tokens: Vec<Token> = WordTokenizer::<P>::new(doc).collect();
+  let period_token_count = tokens.iter().filter(|t| t.has_final_period()).count();
+
+  CorpusCounts {
+    tokens,
+    period_token_count,
+    weights: vec![(0, 1)],
+  }
+}
 
-        read_json_array_data!(
-          "sentence_starters",
-          Json::String(st),
-          data.insert_sentence_starter(&st[..])
-        );
+/// Values that replace the thresholds of a trainer's parameters at runtime.
+/// A threshold that is `None` is taken from the parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Overrides {
+  /// Replaces `TrainerParameters::ABBREV_LOWER_BOUND`.
+  pub abbrev_lower_bound: Option<f64>,
+  /// Replaces `TrainerParameters::COLLOCATION_LOWER_BOUND`.
+  pub collocation_lower_bound: Option<f64>,
+  /// Replaces `TrainerParameters::SENTENCE_STARTER_LOWER_BOUND`.
+  pub sentence_starter_lower_bound: Option<f64>,
+}
 
-        // Load collocations, these come as an array with 2 members in them (or they should).
-        // Pop them in reverse order, then insert into the proper bucket.
-        read_json_array_data!("collocations", Json::Array(mut ar), {
-          match (ar.pop(), ar.pop()) {
-            (Some(Json::String(r)), Some(Json::String(l))) => data
-              .collocations
-              .entry(l)
-              .or_insert(HashSet::new())
-              .insert(r),
-            _ => return Err("failed to parse collocations section"),
-          };
-        });
+/// The changes that training on a document would make to a model. See
+/// `Trainer::dry_run`. Every list is sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrainDelta {
+  /// Abbreviations that would be learned.
+  pub added_abbrevs: Vec<String>,
+  /// Abbreviations that would be forgotten.
+  pub removed_abbrevs: Vec<String>,
+  /// Collocations that would be learned.
+  pub added_collocations: Vec<(String, String)>,
+  /// Sentence starters that would be learned.
+  pub added_sentence_starters: Vec<String>,
+}
 
-        match obj.remove("ortho_context") {
-          Some(Json::Object(obj)) => {
-            for (k, ctxt) in obj.into_iter() {
-              ctxt
-                .as_u64()
-                .map(|c| data.orthographic_context.insert(k, c as u8));
-            }
-          }
-          _ => return Err("failed to parse orthographic context section"),
-        }
+impl TrainDelta {
+  /// Finds the changes between a model, and the same model after training.
+  fn between(before: &TrainingData, after: &TrainingData) -> TrainDelta {
+    fn sorted<'a, I: Iterator<Item = &'a str>>(iter: I) -> Vec<String> {
+      let mut v: Vec<String> = iter.map(String::from).collect();
 
-        Ok(data)
-      }
-      _ => Err("no json object found containing training data"),
+      v.sort();
+      v
+    }
+
+    let mut added_collocations: Vec<(String, String)> = after
+      .collocations()
+      .filter(|&(l, r)| !before.contains_collocation(l, r))
+      .map(|(l, r)| (l.to_string(), r.to_string()))
+      .collect();
+
+    added_collocations.sort();
+
+    TrainDelta {
+      added_abbrevs: sorted(after.abbrevs().filter(|a| !before.contains_abbrev(a))),
+      removed_abbrevs: sorted(before.abbrevs().filter(|a| !after.contains_abbrev(a))),
+      added_collocations: added_collocations,
+      added_sentence_starters: sorted(
+        after
+          .sentence_starters()
+          .filter(|s| !before.contains_sentence_starter(s)),
+      ),
+    }
+  }
+
+  /// Checks if training would not change any abbreviations, collocations,
+  /// or sentence starters.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.added_abbrevs.is_empty()
+      && self.removed_abbrevs.is_empty()
+      && self.added_collocations.is_empty()
+      && self.added_sentence_starters.is_empty()
+  }
+}
+
+/// What a trainer counted in the documents it was trained on. Returned by
+/// `Trainer::train`, and `Trainer::finalize_counts`. Counts are weighted,
+/// like the counts the trainer uses. Comparing reports across the documents
+/// of a corpus shows anomalies, like a run of OCR output where nearly every
+/// token ends with a period.
+///
+/// # Examples
+///
+/// ```
+/// # use punkt::{Trainer, TrainingData};
+/// # use punkt::params::Standard;
+/// #
+/// let mut data = TrainingData::new();
+/// let trainer: Trainer<Standard> = Trainer::new();
+/// let report = trainer.train("The U.S. economy grew. The U.S. team won. It rained.", &mut data);
+///
+/// assert_eq!(report.token_count, 10);
+/// assert_eq!(report.period_token_count, 5);
+/// assert_eq!(report.sentence_break_count, 3);
+/// assert_eq!(report.period_ratio(), 0.5);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrainReport {
+  /// The number of tokens.
+  pub token_count: usize,
+  /// The number of tokens that end with a period.
+  pub period_token_count: usize,
+  /// The number of tokens that were annotated as the end of a sentence,
+  /// after the abbreviations were learned.
+  pub sentence_break_count: usize,
+  /// The number of entries that were dropped from the frequency tables, to
+  /// stay within the memory budget of the trainer.
+  pub pruned_count: usize,
+}
+
+impl TrainReport {
+  /// The fraction of tokens that end with a period. Returns 0 if there were
+  /// no tokens.
+  #[inline]
+  pub fn period_ratio(&self) -> f64 {
+    if self.token_count == 0 {
+      0f64
+    } else {
+      self.period_token_count as f64 / self.token_count as f64
     }
   }
 }
@@ -282,72 +1300,252 @@ impl FromStr for TrainingData {
 /// part of an abbreviation, or actually marks the termination of a sentence.
 pub struct Trainer<P> {
   params: PhantomData<P>,
+  overrides: Overrides,
+  budget: Option<MemoryBudget>,
 }
 
 impl<P> Trainer<P>
 where
-  P: TrainerParameters + DefinesNonPrefixCharacters + DefinesNonWordCharacters,
+  P: TrainerParameters
+    + DefinesMultiChars
+    + DefinesNonPrefixCharacters
+    + DefinesNonWordCharacters
+    + DefinesTypeNormalization,
 {
   /// Creates a new Trainer.
   #[inline(always)]
   pub fn new() -> Trainer<P> {
+    Trainer::with_overrides(Default::default())
+  }
+
+  /// Creates a new Trainer, that uses the thresholds in `overrides` instead
+  /// of the ones in its parameters.
+  #[inline(always)]
+  pub fn with_overrides(overrides: Overrides) -> Trainer<P> {
     Trainer {
       params: PhantomData,
+      overrides: overrides,
+      budget: None,
     }
   }
 
+  /// Limits the estimated heap usage of the frequency tables that the
+  /// trainer counts in, so an unattended training job can't run out of
+  /// memory on a pathological corpus. See `MemoryBudget`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use punkt::{MemoryBudget, Trainer, TrainingData};
+  /// # use punkt::params::Standard;
+  /// #
+  /// let doc = "The U.S. economy grew. The U.S. team won. It rained.";
+  /// let trainer = Trainer::<Standard>::new().with_memory_budget(MemoryBudget::new(1 << 20));
+  /// let mut data = TrainingData::new();
+  /// let report = trainer.train(doc, &mut data);
+  ///
+  /// assert_eq!(report.pruned_count, 0);
+  /// assert!(data.contains_abbrev("u.s"));
+  /// ```
+  #[inline(always)]
+  pub fn with_memory_budget(mut self, budget: MemoryBudget) -> Trainer<P> {
+    self.budget = Some(budget);
+    self
+  }
+
+  /// The thresholds that replace the ones in the parameters of the trainer.
+  #[inline(always)]
+  pub fn overrides(&self) -> &Overrides {
+    &self.overrides
+  }
+
+  /// The memory budget of the trainer, if it has one.
+  #[inline(always)]
+  pub fn memory_budget(&self) -> Option<MemoryBudget> {
+    self.budget
+  }
+
   /// Train on a document. Does tokenization using a WordTokenizer.
-  pub fn train(&self, doc: &str, data: &mut TrainingData) {
-    let mut period_token_count: usize = 0;
+  ///
+  /// This is equivalent to calling `finalize_counts` with the
+  /// `corpus_counts` of the document.
+  #[inline]
+  pub fn train(&self, doc: &str, data: &mut TrainingData) -> TrainReport {
+    self.finalize_counts(corpus_counts::<P>(doc), data)
+  }
+
+  /// Train on a document, taking ownership of the data, and returning it
+  /// once it is trained.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use punkt::{Trainer, TrainingData};
+  /// # use punkt::params::Standard;
+  /// #
+  /// let trainer: Trainer<Standard> = Trainer::new();
+  /// let doc = "The U.S. economy grew. The U.S. team won. It rained.";
+  /// let data = trainer.train_owned(doc, TrainingData::new());
+  ///
+  /// assert!(data.contains_abbrev("u.s"));
+  /// ```
+  #[inline]
+  pub fn train_owned(&self, doc: &str, mut data: TrainingData) -> TrainingData {
+    self.train(doc, &mut data);
+    data
+  }
+
+  /// Reports the changes that training on a document would make to a model,
+  /// without changing the model. Lets a retrained model be reviewed before
+  /// it replaces the one in use.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use punkt::{Trainer, TrainingData};
+  /// # use punkt::params::Standard;
+  /// #
+  /// let data = TrainingData::new();
+  /// let trainer: Trainer<Standard> = Trainer::new();
+  /// let delta = trainer.dry_run("The U.S. economy grew. The U.S. team won. It rained.", &data);
+  ///
+  /// assert_eq!(delta.added_abbrevs, vec!["u.s"]);
+  /// assert!(!data.contains_abbrev("u.s"));
+  /// ```
+  pub fn dry_run(&self, doc: &str, data: &TrainingData) -> TrainDelta {
+    let mut after = data.clone();
+
+    self.train(doc, &mut after);
+
+    TrainDelta::between(data, &after)
+  }
+
+  /// Train on a document, with its counts scaled by a weight. See
+  /// `CorpusCounts::weighted`.
+  #[inline]
+  pub fn train_weighted(&self, doc: &str, weight: usize, data: &mut TrainingData) -> TrainReport {
+    self.finalize_counts(corpus_counts::<P>(doc).weighted(weight), data)
+  }
+
+  /// Train on the counts of one or more documents, that were gathered with
+  /// `corpus_counts`, and combined with `CorpusCounts::merge`.
+  pub fn finalize_counts(&self, counts: CorpusCounts, data: &mut TrainingData) -> TrainReport {
+    self.finalize_counts_with(counts, ExactCounter::new(), data)
+  }
+
+  /// Like `finalize_counts`, but takes ownership of the data, and returns it
+  /// once it is trained.
+  #[inline]
+  pub fn finalize_counts_owned(
+    &self,
+    counts: CorpusCounts,
+    mut data: TrainingData,
+  ) -> TrainingData {
+    self.finalize_counts(counts, &mut data);
+    data
+  }
+
+  /// Like `finalize_counts`, but counts types with `type_fdist`, which must
+  /// be empty. Only the type counts can be approximate. The tokens of
+  /// `counts` are kept until training is done, and collocations, and
+  /// sentence starters are still counted exactly, since every candidate has
+  /// to be scored.
+  pub fn finalize_counts_with<C>(
+    &self,
+    mut counts: CorpusCounts,
+    type_fdist: C,
+    data: &mut TrainingData,
+  ) -> TrainReport
+  where
+    C: TypeCounter,
+  {
+    let weights = counts.token_weights();
+
+    self.finalize_tokens(
+      &mut counts.tokens,
+      &weights,
+      counts.period_token_count,
+      type_fdist,
+      data,
+    )
+  }
+
+  /// Trains on the tokens of one or more documents, and the weight of each
+  /// token. The tokens are annotated with the first pass, on the data as it
+  /// was before rare abbreviations were found.
+  fn finalize_tokens<C>(
+    &self,
+    tokens: &mut [Token],
+    weights: &[usize],
+    period_token_count: usize,
+    mut type_fdist: C,
+    data: &mut TrainingData,
+  ) -> TrainReport
+  where
+    C: TypeCounter,
+  {
     let mut sentence_break_count: usize = 0;
-    let tokens: Vec<Token> = WordTokenizer::<P>::new(doc).collect();
-    let mut type_fdist: FrequencyDistribution<&str> = FrequencyDistribution::new();
+    let mut pruned_count: usize = 0;
     let mut collocation_fdist = FrequencyDistribution::new();
     let mut sentence_starter_fdist = FrequencyDistribution::new();
-
-    for t in tokens.iter() {
-      if t.has_final_period() {
-        period_token_count += 1
-      }
-      type_fdist.insert(t.typ());
+    // Scores that round to the same multiple of `SCORE_EPSILON` as a bound
+    // are ties with the bound, which are accepted, so rounding can't decide
+    // what is learned.
+    let abbrev_lower_bound = LowerBound::new(
+      self
+        .overrides
+        .abbrev_lower_bound
+        .unwrap_or(P::ABBREV_LOWER_BOUND),
+      P::SCORE_EPSILON,
+    );
+
+    data.metadata.set_crate_version(CRATE_VERSION);
+    data
+      .metadata
+      .set_params(ParamsSnapshot::of::<P>().with_overrides(&self.overrides));
+
+    for (t, &w) in tokens.iter().zip(weights.iter()) {
+      type_fdist.add(t.typ(), w);
+      pruned_count += self.prune_to_budget(
+        &mut type_fdist,
+        &mut collocation_fdist,
+        &mut sentence_starter_fdist,
+      );
     }
 
     // Iterate through to see if any tokens need to be reclassified as an
-    // abbreviation or removed as an abbreviation.
+    // abbreviation or removed as an abbreviation. The tokens are scored on
+    // the data as it was before any of them are reclassified.
     {
-      let reclassify_iter: ReclassifyIterator<_, P> = ReclassifyIterator {
+      let reclassified: Vec<(&Token, Score)> = ReclassifyIterator::<_, P, _> {
         iter: tokens.iter(),
         data: data,
         period_token_count: period_token_count,
-        type_fdist: &mut type_fdist,
+        type_fdist: &type_fdist,
         params: PhantomData,
-      };
+      }
+      .collect();
 
-      for (t, score) in reclassify_iter {
-        if score >= P::ABBREV_LOWER_BOUND {
+      for (t, score) in reclassified {
+        if abbrev_lower_bound.accepts(score) {
           if t.has_final_period() {
-            unsafe {
-              (&mut *(data as *const TrainingData as *mut TrainingData))
-                .insert_abbrev(t.typ_without_period());
-            }
+            data.insert_abbrev_with_provenance(
+              t.typ_without_period(),
+              Provenance::Trained {
+                score: Some(score.to_f64()),
+              },
+            );
           }
         } else {
           if !t.has_final_period() {
-            unsafe {
-              (&mut *(data as *const TrainingData as *mut TrainingData))
-                .remove_abbrev(t.typ_without_period());
-            }
+            data.remove_abbrev(t.typ_without_period());
           }
         }
       }
     }
 
-    // Annotating the tokens requires an unsafe block, but it won't modify any pointers,
-    // just will modify some flags on the tokens.
-    for t in tokens.iter() {
-      unsafe {
-        util::annotate_first_pass::<P>(&mut *(t as *const Token as *mut Token), data);
-      }
+    for t in tokens.iter_mut() {
+      util::annotate_first_pass::<P, _>(t, data);
     }
 
     // Update or insert the orthographic context of all tokens in the document.
@@ -366,9 +1564,9 @@ where
 
     // Order matters! Sentence break checks are dependent on whether or not
     // the token is an abbreviation. Must come after the first pass annotation!
-    for t in tokens.iter() {
+    for (t, w) in tokens.iter().zip(weights.iter()) {
       if t.is_sentence_break() {
-        sentence_break_count += 1;
+        sentence_break_count += w;
       }
     }
 
@@ -380,20 +1578,31 @@ where
         last: None,
       };
 
-      for (lt, rt) in consecutive_token_iter {
+      for (i, (lt, rt)) in consecutive_token_iter.enumerate() {
         match rt {
           Some(cur) if lt.has_final_period() => {
-            if is_rare_abbrev_type::<P>(&data, &type_fdist, lt, cur) {
-              data.insert_abbrev(lt.typ_without_period());
+            let w = weights[i + 1];
+
+            if is_rare_abbrev_type::<P, _>(&data, &type_fdist, lt, cur) {
+              data.insert_abbrev_with_provenance(
+                lt.typ_without_period(),
+                Provenance::Trained { score: None },
+              );
             }
 
             if is_potential_sentence_starter(cur, lt) {
-              sentence_starter_fdist.insert(cur);
+              sentence_starter_fdist.extend(Some((cur, w)));
             }
 
             if is_potential_collocation::<P>(lt, cur) {
-              collocation_fdist.insert(Collocation::new(lt, cur));
+              collocation_fdist.extend(Some((Collocation::new(lt, cur), w)));
             }
+
+            pruned_count += self.prune_to_budget(
+              &mut type_fdist,
+              &mut collocation_fdist,
+              &mut sentence_starter_fdist,
+            );
           }
           _ => (),
         }
@@ -401,12 +1610,18 @@ where
     }
 
     {
-      let ss_iter: PotentialSentenceStartersIterator<_, P> = PotentialSentenceStartersIterator {
+      let ss_iter = PotentialSentenceStartersIterator {
         iter: sentence_starter_fdist.keys(),
         sentence_break_count: sentence_break_count,
         type_fdist: &type_fdist,
         sentence_starter_fdist: &sentence_starter_fdist,
-        params: PhantomData,
+        lower_bound: LowerBound::new(
+          self
+            .overrides
+            .sentence_starter_lower_bound
+            .unwrap_or(P::SENTENCE_STARTER_LOWER_BOUND),
+          P::SCORE_EPSILON,
+        ),
       };
 
       for (tok, _) in ss_iter {
@@ -415,42 +1630,175 @@ where
     }
 
     {
-      let clc_iter: PotentialCollocationsIterator<_, P> = PotentialCollocationsIterator {
+      let clc_iter: PotentialCollocationsIterator<_, P, _> = PotentialCollocationsIterator {
         iter: collocation_fdist.keys(),
         data: &data,
         type_fdist: &type_fdist,
         collocation_fdist: &collocation_fdist,
+        lower_bound: LowerBound::new(
+          self
+            .overrides
+            .collocation_lower_bound
+            .unwrap_or(P::COLLOCATION_LOWER_BOUND),
+          P::SCORE_EPSILON,
+        ),
         params: PhantomData,
       };
 
-      for (col, _) in clc_iter {
-        unsafe {
-          (&mut *(data as *const TrainingData as *mut TrainingData)).insert_collocation(
+      let collocations: Vec<(&str, &str)> = clc_iter
+        .map(|(col, _)| {
+          (
             col.left().typ_without_period(),
             col.right().typ_without_break_or_period(),
-          );
-        }
+          )
+        })
+        .collect();
+
+      for (left, right) in collocations {
+        data.insert_collocation(left, right);
       }
     }
+
+    TrainReport {
+      token_count: type_fdist.total(),
+      period_token_count: period_token_count,
+      sentence_break_count: sentence_break_count,
+      pruned_count: pruned_count,
+    }
+  }
+
+  /// Drops the entries with the lowest counts from the frequency tables,
+  /// while their estimated size is over the memory budget. The threshold
+  /// doubles with each round, so a table of large counts is pruned in a few
+  /// rounds. Returns the number of entries that were dropped.
+  fn prune_to_budget<'a, C>(
+    &self,
+    type_fdist: &mut C,
+    collocation_fdist: &mut FrequencyDistribution<Collocation<&'a Token>>,
+    sentence_starter_fdist: &mut FrequencyDistribution<&'a Token>,
+  ) -> usize
+  where
+    C: TypeCounter,
+  {
+    let budget = match self.budget {
+      Some(budget) => budget.bytes(),
+      None => return 0,
+    };
+    let mut pruned = 0;
+    let mut threshold: usize = 1;
+
+    while type_fdist.memory_usage()
+      + memory::fdist_size(collocation_fdist)
+      + memory::fdist_size(sentence_starter_fdist)
+      > budget
+    {
+      pruned += type_fdist.prune(threshold);
+      pruned += prune_fdist(collocation_fdist, threshold, |col| {
+        Collocation::new(*col.left(), *col.right())
+      });
+      pruned += prune_fdist(sentence_starter_fdist, threshold, |&tok| tok);
+
+      if threshold == usize::max_value() {
+        break;
+      }
+
+      threshold = threshold.saturating_mul(2);
+    }
+
+    pruned
+  }
+}
+
+/// Drops the entries of a frequency distribution that were counted
+/// `threshold` times or less. The kept keys are copied with `copy`. Returns
+/// the number of entries that were dropped.
+fn prune_fdist<K, F>(fdist: &mut FrequencyDistribution<K>, threshold: usize, copy: F) -> usize
+where
+  K: Eq + Hash,
+  F: Fn(&K) -> K,
+{
+  let before = fdist.keys().len();
+  let mut kept = FrequencyDistribution::new();
+
+  kept.extend(
+    fdist
+      .keys()
+      .map(|k| (copy(k), fdist.get(k)))
+      .filter(|&(_, n)| n > threshold),
+  );
+  *fdist = kept;
+
+  before - fdist.keys().len()
+}
+
+impl<P> Trainer<P>
+where
+  P: TrainerParameters + TokenizerParameters,
+{
+  /// Trains on a document, and returns the byte offsets of its sentences,
+  /// found with the trained data. The sentences are the same as the ones of
+  /// a `SentenceByteOffsetTokenizer` after calling `train`, because they are
+  /// found by one, but the word tokens that were counted for training are
+  /// reused for the tokens around each sentence ending, instead of
+  /// tokenizing them again.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # use punkt::{Trainer, TrainingData};
+  /// # use punkt::params::Standard;
+  /// #
+  /// let doc = "The U.S. economy grew. The U.S. team won. It rained.";
+  /// let trainer: Trainer<Standard> = Trainer::new();
+  /// let mut data = TrainingData::new();
+  /// let sents: Vec<&str> = trainer
+  ///   .train_and_segment(doc, &mut data)
+  ///   .into_iter()
+  ///   .map(|(start, end)| &doc[start..end])
+  ///   .collect();
+  ///
+  /// assert_eq!(sents, vec!["The U.S. economy grew.", "The U.S. team won.", "It rained."]);
+  /// ```
+  pub fn train_and_segment(&self, doc: &str, data: &mut TrainingData) -> Vec<(usize, usize)> {
+    let (mut tokens, spans): (Vec<Token>, Vec<(usize, usize)>) =
+      WordTokenizer::<P>::new(doc).spanned().unzip();
+    let period_token_count = tokens.iter().filter(|t| t.has_final_period()).count();
+    let weights = vec![1; tokens.len()];
+
+    self.finalize_tokens(
+      &mut tokens,
+      &weights,
+      period_token_count,
+      ExactCounter::new(),
+      data,
+    );
+
+    SentenceByteOffsetTokenizer::<P, TrainingData>::with_tokens(doc, data, &tokens, &spans)
+      .collect()
   }
 }
 
-fn is_rare_abbrev_type<P>(
+fn is_rare_abbrev_type<P, C>(
   data: &TrainingData,
-  type_fdist: &FrequencyDistribution<&str>,
+  type_fdist: &C,
   tok0: &Token,
   tok1: &Token,
 ) -> bool
 where
   P: TrainerParameters,
+  C: TypeCounter,
 {
-  use prelude::{BEG_UC, MID_UC};
-
   if tok0.is_abbrev() || !tok0.is_sentence_break() {
     false
   } else {
     let key = tok0.typ_without_break_or_period();
-    let count = (type_fdist[key] + type_fdist[&key[..key.len() - 1]]) as f64;
+    // The last character might be more than 1 byte long.
+    let key_without_last = key
+      .char_indices()
+      .next_back()
+      .map(|(i, _)| &key[..i])
+      .unwrap_or("");
+    let count = (type_fdist.count(key) + type_fdist.count(key_without_last)) as f64;
 
     // Already an abbreviation...
     if data.contains_abbrev(tok0.typ()) || count >= P::ABBREV_UPPER_BOUND {
@@ -460,11 +1808,7 @@ where
     } else if tok1.is_lowercase() {
       let ctxt = data.get_orthographic_context(tok1.typ_without_break_or_period());
 
-      if (ctxt & BEG_UC > 0) && !(ctxt & MID_UC > 0) {
-        true
-      } else {
-        false
-      }
+      ctxt.seen_sentence_initial_upper() && !ctxt.seen_sentence_internal_upper()
     } else {
       false
     }
@@ -491,20 +1835,21 @@ where
 /// Iterates over every token from the supplied iterator. Only returns
 /// the ones that are 'not obviously' abbreviations. Also returns the associated
 /// score of that token.
-struct ReclassifyIterator<'b, I, P> {
+struct ReclassifyIterator<'c, I, P, C: 'c> {
   iter: I,
-  data: &'b TrainingData,
+  data: &'c TrainingData,
   period_token_count: usize,
-  type_fdist: &'b FrequencyDistribution<&'b str>,
+  type_fdist: &'c C,
   params: PhantomData<P>,
 }
 
-impl<'b, I, P> Iterator for ReclassifyIterator<'b, I, P>
+impl<'b, 'c, I, P, C> Iterator for ReclassifyIterator<'c, I, P, C>
 where
   I: Iterator<Item = &'b Token>,
   P: TrainerParameters,
+  C: TypeCounter,
 {
-  type Item = (&'b Token, f64);
+  type Item = (&'b Token, Score);
 
   #[inline]
   fn next(&mut self) -> Option<Self::Item> {
@@ -528,26 +1873,41 @@ where
           .chars()
           .fold(0, |acc, c| if c == '.' { acc + 1 } else { acc })
           + 1;
-      let num_nonperiods = t.typ_without_period().chars().count() - num_periods + 1;
+      let num_chars = if t.has_special_type() {
+        t.typ_without_period().chars().count()
+      } else if t.char_len() > 1 {
+        t.char_len_without_period()
+      } else {
+        t.char_len()
+      };
+      let num_nonperiods = num_chars - num_periods + 1;
+
+      // Only tokens that could be learned as an abbreviation are limited by
+      // length. Known abbreviations can still be removed.
+      if t.has_final_period()
+        && (num_nonperiods < P::MIN_ABBREV_LEN || num_nonperiods > P::MAX_ABBREV_LEN)
+      {
+        continue;
+      }
 
-      let count_with_period = self.type_fdist.get(t.typ_with_period());
-      let count_without_period = self.type_fdist.get(t.typ_without_period());
+      let count_with_period = self.type_fdist.count(t.typ_with_period());
+      let count_without_period = self.type_fdist.count(t.typ_without_period());
 
       let likelihood = util::dunning_log_likelihood(
-        (count_with_period + count_without_period) as f64,
-        self.period_token_count as f64,
-        count_with_period as f64,
-        self.type_fdist.sum_counts() as f64,
+        Score::from_count(count_with_period + count_without_period),
+        Score::from_count(self.period_token_count),
+        Score::from_count(count_with_period),
+        Score::from_count(self.type_fdist.total()),
       );
 
-      let f_length = (-(num_nonperiods as f64)).exp();
+      let f_length = (-Score::from_count(num_nonperiods)).exp();
       let f_penalty = if P::IGNORE_ABBREV_PENALTY {
-        0f64
+        Score::from_count(0)
       } else {
-        (num_nonperiods as f64).powi(-(count_without_period as i32))
+        Score::from_count(num_nonperiods).powi(-(count_without_period as i32))
       };
 
-      let score = likelihood * f_length * f_penalty * (num_periods as f64);
+      let score = likelihood * f_length * f_penalty * Score::from_count(num_periods);
 
       return Some((t, score));
     }
@@ -602,23 +1962,25 @@ where
   }
 }
 
-struct PotentialCollocationsIterator<'b, I, P> {
+struct PotentialCollocationsIterator<'b, I, P, C: 'b> {
   iter: I,
   data: &'b TrainingData,
-  type_fdist: &'b FrequencyDistribution<&'b str>,
+  type_fdist: &'b C,
   collocation_fdist: &'b FrequencyDistribution<Collocation<&'b Token>>,
+  lower_bound: LowerBound,
   params: PhantomData<P>,
 }
 
-impl<'a, 'b, I, P> Iterator for PotentialCollocationsIterator<'b, I, P>
+impl<'a, 'b, I, P, C> Iterator for PotentialCollocationsIterator<'b, I, P, C>
 where
   I: Iterator<Item = &'a Collocation<&'a Token>>,
   P: TrainerParameters,
+  C: TypeCounter,
 {
-  type Item = (&'a Collocation<&'a Token>, f64);
+  type Item = (&'a Collocation<&'a Token>, Score);
 
   #[inline]
-  fn next(&mut self) -> Option<(&'a Collocation<&'a Token>, f64)> {
+  fn next(&mut self) -> Option<(&'a Collocation<&'a Token>, Score)> {
     while let Some(col) = self.iter.next() {
       if self
         .data
@@ -629,26 +1991,26 @@ where
 
       let count = self.collocation_fdist.get(col);
 
-      let left_count = self.type_fdist.get(col.left().typ_without_period())
-        + self.type_fdist.get(col.left().typ_with_period());
-      let right_count = self.type_fdist.get(col.right().typ_without_period())
-        + self.type_fdist.get(col.right().typ_with_period());
+      let left_count = self.type_fdist.count(col.left().typ_without_period())
+        + self.type_fdist.count(col.left().typ_with_period());
+      let right_count = self.type_fdist.count(col.right().typ_without_period())
+        + self.type_fdist.count(col.right().typ_with_period());
 
       if left_count > 1
         && right_count > 1
-        && P::COLLOCATION_FREQUENCY_LOWER_BOUND < count as f64
+        && Score::from_f64(P::COLLOCATION_FREQUENCY_LOWER_BOUND) < Score::from_count(count)
         && count <= min(left_count, right_count)
       {
         let likelihood = util::col_log_likelihood(
-          left_count as f64,
-          right_count as f64,
-          count as f64,
-          self.type_fdist.sum_counts() as f64,
+          Score::from_count(left_count),
+          Score::from_count(right_count),
+          Score::from_count(count),
+          Score::from_count(self.type_fdist.total()),
         );
 
-        if likelihood >= P::COLLOCATION_LOWER_BOUND
-          && (self.type_fdist.sum_counts() as f64 / left_count as f64)
-            > (right_count as f64 / count as f64)
+        if self.lower_bound.accepts(likelihood)
+          && (Score::from_count(self.type_fdist.total()) / Score::from_count(left_count))
+            > (Score::from_count(right_count) / Score::from_count(count))
         {
           return Some((col, likelihood));
         }
@@ -659,43 +2021,44 @@ where
   }
 }
 
-struct PotentialSentenceStartersIterator<'b, I, P> {
+struct PotentialSentenceStartersIterator<'b, I, C: 'b> {
   iter: I,
   sentence_break_count: usize,
-  type_fdist: &'b FrequencyDistribution<&'b str>,
+  type_fdist: &'b C,
   sentence_starter_fdist: &'b FrequencyDistribution<&'b Token>,
-  params: PhantomData<P>,
+  lower_bound: LowerBound,
 }
 
-impl<'a, 'b, I, P> Iterator for PotentialSentenceStartersIterator<'b, I, P>
+impl<'a, 'b, I, C> Iterator for PotentialSentenceStartersIterator<'b, I, C>
 where
   I: Iterator<Item = &'a &'a Token>,
-  P: TrainerParameters,
+  C: TypeCounter,
 {
-  type Item = (&'a Token, f64);
+  type Item = (&'a Token, Score);
 
   #[inline]
-  fn next(&mut self) -> Option<(&'a Token, f64)> {
+  fn next(&mut self) -> Option<(&'a Token, Score)> {
     while let Some(tok) = self.iter.next() {
       let ss_count = self.sentence_starter_fdist.get(tok);
-      let typ_count =
-        self.type_fdist.get(tok.typ_with_period()) + self.type_fdist.get(tok.typ_without_period());
+      let typ_count = self.type_fdist.count(tok.typ_with_period())
+        + self.type_fdist.count(tok.typ_without_period());
 
       if typ_count < ss_count {
         continue;
       }
 
       let likelihood = util::col_log_likelihood(
-        self.sentence_break_count as f64,
-        typ_count as f64,
-        ss_count as f64,
-        self.type_fdist.sum_counts() as f64,
+        Score::from_count(self.sentence_break_count),
+        Score::from_count(typ_count),
+        Score::from_count(ss_count),
+        Score::from_count(self.type_fdist.total()),
       );
 
-      let ratio = self.type_fdist.sum_counts() as f64 / self.sentence_break_count as f64;
+      let ratio =
+        Score::from_count(self.type_fdist.total()) / Score::from_count(self.sentence_break_count);
 
-      if likelihood >= P::SENTENCE_STARTER_LOWER_BOUND
-        && ratio > (typ_count as f64 / ss_count as f64)
+      if self.lower_bound.accepts(likelihood)
+        && ratio > (Score::from_count(typ_count) / Score::from_count(ss_count))
       {
         return Some((*tok, likelihood));
       }
@@ -737,35 +2100,40 @@ where
   }
 }
 
-// Macro for generating functions to load precompiled data.
+// Macro for generating functions to load precompiled data. Each language is
+// in its own crate, behind a feature of the same name, so programs only embed
+// the languages they use.
 macro_rules! preloaded_data(
-  ($lang:ident, $file:expr) => (
+  ($lang:ident, $feature:tt, $krate:ident) => (
+    #[cfg(feature = $feature)]
     impl TrainingData {
       #[inline] #[allow(missing_docs)] pub fn $lang() -> TrainingData {
-        FromStr::from_str(include_str!($file)).unwrap()
+        FromStr::from_str(::$krate::MODEL).unwrap()
       }
     }
   )
 );
 
-preloaded_data!(czech, "data/czech.json");
-preloaded_data!(danish, "data/danish.json");
-preloaded_data!(dutch, "data/dutch.json");
-preloaded_data!(english, "data/english.json");
-preloaded_data!(estonian, "data/estonian.json");
-preloaded_data!(finnish, "data/finnish.json");
-preloaded_data!(french, "data/french.json");
-preloaded_data!(german, "data/german.json");
-preloaded_data!(greek, "data/greek.json");
-preloaded_data!(italian, "data/italian.json");
-preloaded_data!(norwegian, "data/norwegian.json");
-preloaded_data!(polish, "data/polish.json");
-preloaded_data!(portuguese, "data/portuguese.json");
-preloaded_data!(slovene, "data/slovene.json");
-preloaded_data!(spanish, "data/spanish.json");
-preloaded_data!(swedish, "data/swedish.json");
-preloaded_data!(turkish, "data/turkish.json");
-
+preloaded_data!(arabic, "arabic", punkt_data_arabic);
+preloaded_data!(czech, "czech", punkt_data_czech);
+preloaded_data!(danish, "danish", punkt_data_danish);
+preloaded_data!(dutch, "dutch", punkt_data_dutch);
+preloaded_data!(english, "english", punkt_data_english);
+preloaded_data!(estonian, "estonian", punkt_data_estonian);
+preloaded_data!(finnish, "finnish", punkt_data_finnish);
+preloaded_data!(french, "french", punkt_data_french);
+preloaded_data!(german, "german", punkt_data_german);
+preloaded_data!(greek, "greek", punkt_data_greek);
+preloaded_data!(italian, "italian", punkt_data_italian);
+preloaded_data!(norwegian, "norwegian", punkt_data_norwegian);
+preloaded_data!(polish, "polish", punkt_data_polish);
+preloaded_data!(portuguese, "portuguese", punkt_data_portuguese);
+preloaded_data!(slovene, "slovene", punkt_data_slovene);
+preloaded_data!(spanish, "spanish", punkt_data_spanish);
+preloaded_data!(swedish, "swedish", punkt_data_swedish);
+preloaded_data!(turkish, "turkish", punkt_data_turkish);
+
+#[cfg(feature = "english")]
 #[test]
 fn test_data_load_from_json_test() {
   let data: TrainingData = TrainingData::english();
@@ -779,40 +2147,514 @@ fn test_data_load_from_json_test() {
   assert!(data.contains_collocation("##number##", "corrections"));
 }
 
-#[cfg(test)]
-macro_rules! bench_trainer(
-  ($name:ident, $doc:expr) => (
-    #[bench] fn $name(b: &mut ::test::Bencher) {
-      b.iter(|| {
-        let mut data = TrainingData::new();
-        let trainer: Trainer<::prelude::Standard> = Trainer::new();
+#[test]
+fn test_data_json_round_trip_test() {
+  type Lang = (fn() -> TrainingData, &'static str);
+
+  let langs: &[Lang] = &[
+    #[cfg(feature = "arabic")]
+    (TrainingData::arabic, ::punkt_data_arabic::MODEL),
+    #[cfg(feature = "czech")]
+    (TrainingData::czech, ::punkt_data_czech::MODEL),
+    #[cfg(feature = "danish")]
+    (TrainingData::danish, ::punkt_data_danish::MODEL),
+    #[cfg(feature = "dutch")]
+    (TrainingData::dutch, ::punkt_data_dutch::MODEL),
+    #[cfg(feature = "english")]
+    (TrainingData::english, ::punkt_data_english::MODEL),
+    #[cfg(feature = "estonian")]
+    (TrainingData::estonian, ::punkt_data_estonian::MODEL),
+    #[cfg(feature = "finnish")]
+    (TrainingData::finnish, ::punkt_data_finnish::MODEL),
+    #[cfg(feature = "french")]
+    (TrainingData::french, ::punkt_data_french::MODEL),
+    #[cfg(feature = "german")]
+    (TrainingData::german, ::punkt_data_german::MODEL),
+    #[cfg(feature = "greek")]
+    (TrainingData::greek, ::punkt_data_greek::MODEL),
+    #[cfg(feature = "italian")]
+    (TrainingData::italian, ::punkt_data_italian::MODEL),
+    #[cfg(feature = "norwegian")]
+    (TrainingData::norwegian, ::punkt_data_norwegian::MODEL),
+    #[cfg(feature = "polish")]
+    (TrainingData::polish, ::punkt_data_polish::MODEL),
+    #[cfg(feature = "portuguese")]
+    (TrainingData::portuguese, ::punkt_data_portuguese::MODEL),
+    #[cfg(feature = "slovene")]
+    (TrainingData::slovene, ::punkt_data_slovene::MODEL),
+    #[cfg(feature = "spanish")]
+    (TrainingData::spanish, ::punkt_data_spanish::MODEL),
+    #[cfg(feature = "swedish")]
+    (TrainingData::swedish, ::punkt_data_swedish::MODEL),
+    #[cfg(feature = "turkish")]
+    (TrainingData::turkish, ::punkt_data_turkish::MODEL),
+  ];
+
+  for &(lang, raw) in langs.iter() {
+    let data = lang();
+    let json = data.to_json();
+    let loaded: TrainingData = json.parse().unwrap();
+    let raw: Json = raw.parse().unwrap();
+
+    assert_eq!(loaded, data);
+    assert_eq!(loaded.to_json(), json);
+    assert_eq!(raw.to_string().parse::<Json>().unwrap(), raw);
+  }
 
-        trainer.train($doc, &mut data);
-      })
-    }
-  )
-);
+  for bad in ["", "[]", "{", "{\"abbrev_types\": []"].iter() {
+    assert_eq!(
+      bad.parse::<TrainingData>().err(),
+      Some("no json object found containing training data")
+    );
+  }
 
-#[cfg(test)]
-bench_trainer!(
-  bench_trainer_short,
-  include_str!("../test/raw/sigma-wiki.txt")
-);
+  assert_eq!(
+    "{\"version\": \"1\"}".parse::<TrainingData>().err(),
+    Some("failed to parse version")
+  );
+}
 
-#[cfg(test)]
-bench_trainer!(
-  bench_trainer_medium,
-  include_str!("../test/raw/npr-article-01.txt")
-);
+#[test]
+fn test_data_insert_test() {
+  let mut data = TrainingData::new();
+
+  assert!(data.insert_abbrev("Etc"));
+  assert!(!data.insert_abbrev("etc"));
+  assert!(data.contains_abbrev("etc"));
+  assert!(data.insert_collocation("a", "b"));
+  assert!(data.insert_collocation("a", "c"));
+  assert!(!data.insert_collocation("a", "b"));
+  assert!(data.insert_orthographic_context("a", 0b10));
+  assert!(!data.insert_orthographic_context("a", 0b100));
+  assert_eq!(data.get_orthographic_context("a").bits(), 0b110);
+}
 
-#[cfg(test)]
-bench_trainer!(
-  bench_trainer_long,
-  include_str!("../test/raw/the-sayings-of-confucius.txt")
-);
+#[test]
+fn trainer_finalize_merged_counts_test() {
+  use prelude::Standard;
 
-#[cfg(test)]
-bench_trainer!(
-  bench_trainer_very_long,
-  include_str!("../test/raw/pride-and-prejudice.txt")
-);
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let trainer: Trainer<Standard> = Trainer::new();
+  let mut whole = TrainingData::new();
+  let mut merged = TrainingData::new();
+  let mut counts = CorpusCounts::new();
+
+  let whole_report = trainer.train(doc, &mut whole);
+
+  for part in doc.split("\n\n") {
+    counts.merge(corpus_counts::<Standard>(part));
+  }
+
+  let merged_report = trainer.finalize_counts(counts, &mut merged);
+
+  assert_eq!(whole_report, merged_report);
+  assert!(whole_report.sentence_break_count > 0);
+  assert!(whole_report.period_token_count >= whole_report.sentence_break_count);
+
+  fn sorted<T: Ord, I: Iterator<Item = T>>(iter: I) -> Vec<T> {
+    let mut v: Vec<T> = iter.collect();
+    v.sort();
+    v
+  }
+
+  assert_eq!(sorted(whole.abbrevs()), sorted(merged.abbrevs()));
+  assert_eq!(
+    sorted(whole.sentence_starters()),
+    sorted(merged.sentence_starters())
+  );
+  assert_eq!(sorted(whole.collocations()), sorted(merged.collocations()));
+  assert_eq!(
+    sorted(whole.orthographic_contexts()),
+    sorted(merged.orthographic_contexts())
+  );
+}
+
+#[test]
+fn trainer_weighted_test() {
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let trainer: Trainer<::prelude::Standard> = Trainer::new();
+  let mut data = TrainingData::new();
+  let mut weighted = TrainingData::new();
+
+  let report = trainer.train(doc, &mut data);
+
+  assert_eq!(trainer.train_weighted(doc, 1, &mut weighted), report);
+  assert_eq!(data, weighted);
+
+  let mut counts = CorpusCounts::new();
+
+  counts.merge(corpus_counts::<::prelude::Standard>(doc).weighted(4));
+  assert_eq!(counts.token_weights().len(), counts.token_count());
+  assert!(counts.token_weights().iter().all(|&w| w == 4));
+
+  let mut weighted = TrainingData::new();
+  let weighted_report = trainer.finalize_counts(counts, &mut weighted);
+
+  assert!(data != weighted);
+  assert_eq!(weighted_report.token_count, report.token_count * 4);
+  assert_eq!(
+    weighted_report.period_token_count,
+    report.period_token_count * 4
+  );
+  assert_eq!(weighted_report.period_ratio(), report.period_ratio());
+  assert_eq!(TrainReport::default().period_ratio(), 0f64);
+}
+
+#[test]
+fn trainer_memory_budget_test() {
+  use count::ExactCounter;
+  use prelude::Standard;
+
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let trainer: Trainer<Standard> = Trainer::new();
+  let mut expected = TrainingData::new();
+  let report = trainer.train(doc, &mut expected);
+
+  assert_eq!(trainer.memory_budget(), None);
+  assert_eq!(report.pruned_count, 0);
+
+  // Tables that fit in the budget are left alone.
+  let roomy = Trainer::<Standard>::new().with_memory_budget(MemoryBudget::new(usize::max_value()));
+  let mut data = TrainingData::new();
+
+  assert_eq!(roomy.train(doc, &mut data), report);
+  assert_eq!(data, expected);
+
+  // The rarest entries are dropped, until the tables fit. The total count of
+  // the types stays the same.
+  let budget = MemoryBudget::new(1 << 13);
+  let tight = Trainer::<Standard>::new().with_memory_budget(budget);
+  let tokens: Vec<Token> = WordTokenizer::<Standard>::new(doc).collect();
+  let mut types = ExactCounter::new();
+  let mut collocations = FrequencyDistribution::new();
+  let mut starters = FrequencyDistribution::new();
+
+  for (l, r) in tokens.iter().zip(tokens.iter().skip(1)) {
+    types.add(l.typ(), 1);
+    collocations.extend(Some((Collocation::new(l, r), 1)));
+    starters.extend(Some((r, 1)));
+  }
+
+  fn size<'a>(
+    types: &ExactCounter,
+    collocations: &FrequencyDistribution<Collocation<&'a Token>>,
+    starters: &FrequencyDistribution<&'a Token>,
+  ) -> usize {
+    types.memory_usage() + memory::fdist_size(collocations) + memory::fdist_size(starters)
+  }
+
+  let total = types.total();
+
+  assert!(size(&types, &collocations, &starters) > budget.bytes());
+  assert!(tight.prune_to_budget(&mut types, &mut collocations, &mut starters) > 0);
+  assert!(size(&types, &collocations, &starters) <= budget.bytes());
+  assert!(types.count("the") > 0);
+  assert_eq!(types.count("aioli"), 0);
+  assert_eq!(types.total(), total);
+
+  let mut data = TrainingData::new();
+  let pruned = tight.train(doc, &mut data);
+
+  assert!(pruned.pruned_count > 0);
+  assert_eq!(pruned.token_count, report.token_count);
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn test_data_lookup_filter_test() {
+  let mut data: TrainingData = TrainingData::english();
+
+  data.enable_lookup_filter();
+
+  assert!(data.contains_sentence_starter("among"));
+  assert!(data.contains_abbrev("w.va"));
+  assert!(data.contains_collocation("##number##", "corrections"));
+  assert!(!data.contains_abbrev("among"));
+  assert!(!data.contains_collocation("corrections", "##number##"));
+  assert!(data.get_orthographic_context("zzzz").is_empty());
+
+  for i in 0..10_000 {
+    data.insert_abbrev(&format!("abbrev{}", i));
+  }
+
+  assert!((0..10_000).all(|i| data.contains_abbrev(&format!("abbrev{}", i))));
+  assert!(data.contains_abbrev("w.va"));
+}
+
+#[test]
+fn test_data_lookup_filter_rebuild_test() {
+  let mut data = TrainingData::new();
+
+  data.enable_lookup_filter();
+
+  // An empty model gets a filter for 64 keys, which is rebuilt by the
+  // insert of the 64th key, and again as it keeps growing. The key that
+  // triggers a rebuild has to be in the rebuilt filter.
+  for i in 0..1_000 {
+    let tok = format!("key{}", i);
+
+    data.insert_abbrev(&tok);
+    data.insert_sentence_starter(&tok);
+    data.insert_collocation(&tok, "right");
+    data.insert_orthographic_context(&tok, 1);
+
+    assert!(data.contains_abbrev(&tok), "{}", tok);
+    assert!(data.contains_sentence_starter(&tok), "{}", tok);
+    assert!(data.contains_collocation(&tok, "right"), "{}", tok);
+    assert!(!data.get_orthographic_context(&tok).is_empty(), "{}", tok);
+  }
+}
+
+#[test]
+fn trainer_dry_run_test() {
+  let trainer: Trainer<::prelude::Standard> = Trainer::new();
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let mut data = TrainingData::new();
+
+  data.insert_abbrev("the");
+
+  let before = data.clone();
+  let delta = trainer.dry_run(doc, &data);
+
+  assert_eq!(data, before);
+  assert_eq!(delta.removed_abbrevs, vec!["the"]);
+  assert!(delta.added_abbrevs.contains(&"u.s".to_string()));
+  assert!(delta.added_abbrevs.windows(2).all(|w| w[0] < w[1]));
+
+  trainer.train(doc, &mut data);
+
+  assert!(!data.contains_abbrev("the"));
+  assert!(delta.added_abbrevs.iter().all(|a| data.contains_abbrev(a)));
+  assert!(trainer.dry_run(doc, &data).removed_abbrevs.is_empty());
+  assert!(TrainDelta::default().is_empty());
+  assert!(!delta.is_empty());
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn test_data_clone_eq_test() {
+  let data = TrainingData::english();
+  let mut copy = data.clone();
+
+  assert_eq!(data, copy);
+
+  copy.enable_lookup_filter();
+  copy.insert_orthographic_context("zzzz", 0);
+  assert_eq!(data, copy);
+
+  copy.insert_collocation("zzzz", "yyyy");
+  assert!(data != copy);
+  copy.remove_collocation("zzzz", "yyyy");
+  assert_eq!(data, copy);
+
+  copy.insert_abbrev("zzzz");
+  assert!(data != copy);
+  assert_eq!(TrainingData::new(), TrainingData::default());
+  assert!(format!("{:?}", data).starts_with("TrainingData { abbrevs: "));
+}
+
+#[test]
+fn trainer_score_epsilon_test() {
+  use prelude::Standard;
+  use score;
+
+  let doc = "The U.S. economy grew. The U.S. team won. It rained.";
+  let mut data = TrainingData::new();
+
+  Trainer::<Standard>::new().train(doc, &mut data);
+
+  let score = match data.abbrev_provenance("u.s") {
+    Some(Provenance::Trained { score: Some(score) }) => score,
+    _ => panic!("expected a trained abbreviation"),
+  };
+  let learned = |bound: f64| {
+    let mut data = TrainingData::new();
+    let overrides = Overrides {
+      abbrev_lower_bound: Some(bound),
+      ..Default::default()
+    };
+
+    Trainer::<Standard>::with_overrides(overrides).train(doc, &mut data);
+    data.contains_abbrev("u.s")
+  };
+
+  // The tolerance is `SCORE_EPSILON`, unless `Score` is too coarse for it.
+  let tolerance = score::tie_tolerance(score, Standard::SCORE_EPSILON);
+
+  assert!(learned(score));
+  assert!(!learned(score + tolerance * 2.0));
+  assert!(!learned(score * 1.01));
+
+  // Some bounds above the score round to the same multiple of the tolerance,
+  // and the score ties with them.
+  assert!((1..16).any(|i| learned(score + tolerance * i as f64 / 16.0)));
+}
+
+#[test]
+fn training_data_abbrev_provenance_test() {
+  use prelude::Standard;
+
+  let doc = "The U.S. economy grew. The U.S. team won. It rained.";
+  let mut data = TrainingData::new();
+
+  data.insert_abbrev("jan");
+  Trainer::<Standard>::new().train(doc, &mut data);
+
+  assert_eq!(data.abbrev_provenance("jan"), Some(Provenance::Seeded));
+  assert!(match data.abbrev_provenance("u.s") {
+    Some(Provenance::Trained { score: Some(score) }) => score >= Standard::ABBREV_LOWER_BOUND,
+    _ => false,
+  });
+  assert_eq!(data.abbrev_provenance("economy"), None);
+
+  data.import_abbrev_list("Acct.\njan.".as_bytes()).unwrap();
+
+  assert_eq!(data.abbrev_provenance("acct"), Some(Provenance::Inserted));
+  assert_eq!(data.abbrev_provenance("jan"), Some(Provenance::Seeded));
+
+  data.remove_abbrev("acct");
+  data.insert_abbrev("acct");
+
+  assert_eq!(data.abbrev_provenance("acct"), Some(Provenance::Seeded));
+
+  // Provenance isn't stored in model files.
+  let loaded: TrainingData = data.to_json().parse().unwrap();
+
+  assert_eq!(loaded.abbrev_provenance("u.s"), Some(Provenance::Seeded));
+}
+
+#[test]
+fn trainer_finalize_counts_with_test() {
+  use count::CountMinSketch;
+  use prelude::Standard;
+
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let trainer: Trainer<Standard> = Trainer::new();
+  let mut exact = TrainingData::new();
+  let mut sketched = TrainingData::new();
+  let mut tiny = TrainingData::new();
+
+  trainer.finalize_counts(corpus_counts::<Standard>(doc), &mut exact);
+  trainer.finalize_counts_with(
+    corpus_counts::<Standard>(doc),
+    CountMinSketch::new(1 << 16, 4),
+    &mut sketched,
+  );
+  trainer.finalize_counts_with(
+    corpus_counts::<Standard>(doc),
+    CountMinSketch::new(1, 1),
+    &mut tiny,
+  );
+
+  // A sketch with more cells than types rarely collides.
+  assert_eq!(exact, sketched);
+  assert!(exact.abbrevs().count() > 0);
+  // Every type counts as the total, so no type looks rare.
+  assert!(tiny.abbrevs().count() < exact.abbrevs().count());
+}
+
+#[test]
+fn trainer_abbrev_len_test() {
+  use prelude::*;
+
+  struct Bounded;
+
+  impl DefinesInternalPunctuation for Bounded {}
+  impl DefinesMultiChars for Bounded {}
+  impl DefinesNonPrefixCharacters for Bounded {}
+  impl DefinesNonWordCharacters for Bounded {}
+  impl DefinesSentenceEndings for Bounded {}
+  impl DefinesTypeNormalization for Bounded {}
+  impl TrainerParameters for Bounded {
+    const MIN_ABBREV_LEN: usize = 2;
+    const MAX_ABBREV_LEN: usize = 3;
+  }
+
+  let doc = "It weighs approx. ten kilos, or c. five pounds, e.g. in the shop. We left. ";
+  let doc = &doc.repeat(20)[..];
+  let standard = Trainer::<Standard>::new().dry_run(doc, &TrainingData::new());
+  let bounded = Trainer::<Bounded>::new().dry_run(doc, &TrainingData::new());
+
+  assert!(standard.added_abbrevs.contains(&"c".to_string()));
+  assert!(standard.added_abbrevs.contains(&"shop".to_string()));
+  assert_eq!(bounded.added_abbrevs, vec!["e.g"]);
+
+  // Known abbreviations outside of the bounds are still removed.
+  let mut data = TrainingData::new();
+
+  data.insert_abbrev("kilos");
+
+  let bounded = Trainer::<Bounded>::new().dry_run(doc, &data);
+
+  assert_eq!(bounded.removed_abbrevs, vec!["kilos"]);
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn trainer_owned_test() {
+  use prelude::Standard;
+
+  let docs = ["The U.S. economy grew.", "The U.S. team won. It rained."];
+  let trainer = Trainer::<Standard>::new();
+  let mut data = TrainingData::new();
+  let mut counts = CorpusCounts::new();
+
+  for doc in docs.iter() {
+    counts.merge(corpus_counts::<Standard>(doc));
+  }
+
+  trainer.finalize_counts(counts, &mut data);
+
+  assert_eq!(TrainingData::trained_on(&docs), data);
+  assert_eq!(
+    TrainingData::trained_on(docs.iter().map(|d| d.to_string())),
+    data
+  );
+  assert_eq!(
+    TrainingData::trained_on(Vec::<String>::new()),
+    TrainingData::new()
+  );
+
+  let mut data = TrainingData::english();
+  let owned = trainer.train_owned(docs[1], data.clone());
+
+  trainer.train(docs[1], &mut data);
+  assert_eq!(owned, data);
+}
+
+#[test]
+fn trainer_train_and_segment_test() {
+  use std::fs;
+
+  use prelude::{Legal, Precise, Rtl, Standard, Web};
+
+  fn check<P>(doc: &str)
+  where
+    P: TrainerParameters + TokenizerParameters,
+  {
+    let trainer = Trainer::<P>::new();
+    let mut data = TrainingData::new();
+    let mut shared = TrainingData::new();
+
+    trainer.train(doc, &mut data);
+
+    let sents: Vec<_> = SentenceByteOffsetTokenizer::<P>::new(doc, &data).collect();
+
+    assert_eq!(trainer.train_and_segment(doc, &mut shared), sents);
+    assert_eq!(shared, data);
+  }
+
+  for entry in fs::read_dir("test/raw").unwrap() {
+    let doc = fs::read_to_string(entry.unwrap().path()).unwrap();
+
+    check::<Standard>(&doc);
+    check::<Precise>(&doc);
+    check::<Legal>(&doc);
+    check::<Web>(&doc);
+    check::<Rtl>(&doc);
+  }
+
+  check::<Standard>("");
+  check::<Standard>("Hi.)x y. Wow!!! Done.\n\nMr. Smith... left");
+  check::<Precise>("\"Stop!\" she said. He left (at 5 p.m.). Done.)... Next.  ");
+}
@@ -0,0 +1,106 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
+
+use model::Model;
+use prelude::{Standard, TokenizerParameters};
+use tokenizer::SentenceByteOffsetTokenizer;
+
+/// Truncates a document with the standard parameters. See
+/// `truncate_at_sentence_with_params`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{truncate_at_sentence, TrainingData};
+/// #
+/// let data = TrainingData::english();
+/// let doc = "Mr. Smith arrived at 5 p.m. on Monday. He left early. Nobody noticed.";
+///
+/// assert_eq!(truncate_at_sentence(doc, &data, 60), "Mr. Smith arrived at 5 p.m. on Monday. He left early.");
+/// assert_eq!(truncate_at_sentence(doc, &data, 10), "");
+/// # }
+/// ```
+#[inline]
+pub fn truncate_at_sentence<'a, D>(doc: &'a str, data: &D, max_bytes: usize) -> &'a str
+where
+  D: Model,
+{
+  truncate_at_sentence_with_params::<Standard, D>(doc, data, max_bytes)
+}
+
+/// Returns the longest prefix of a document that ends at the end of a
+/// sentence, and is at most `max_bytes` long. Sentences are found lazily, so
+/// only the part of the document within the budget is segmented. Returns an
+/// empty string if the first sentence doesn't fit.
+pub fn truncate_at_sentence_with_params<'a, P, D>(
+  doc: &'a str,
+  data: &D,
+  max_bytes: usize,
+) -> &'a str
+where
+  P: TokenizerParameters,
+  D: Model,
+{
+  if doc.len() <= max_bytes {
+    return doc.trim_end();
+  }
+
+  let mut end = 0;
+
+  for (_, e) in SentenceByteOffsetTokenizer::<P, D>::new(doc, data) {
+    // The last sentence can end with trailing whitespace.
+    let e = doc.get(..e).unwrap_or("").trim_end().len();
+
+    if e > max_bytes {
+      break;
+    }
+
+    end = e;
+  }
+
+  doc.get(..end).unwrap_or("")
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn truncate_at_sentence_test() {
+  use trainer::TrainingData;
+
+  let data = TrainingData::english();
+  let doc = "The U.S. economy grew. Dr. Jones disagreed. ";
+
+  assert_eq!(truncate_at_sentence(doc, &data, 0), "");
+  assert_eq!(truncate_at_sentence(doc, &data, 21), "");
+  assert_eq!(
+    truncate_at_sentence(doc, &data, 22),
+    "The U.S. economy grew."
+  );
+  assert_eq!(
+    truncate_at_sentence(doc, &data, 42),
+    "The U.S. economy grew."
+  );
+  assert_eq!(truncate_at_sentence(doc, &data, 43), doc.trim_end());
+  assert_eq!(truncate_at_sentence(doc, &data, 1000), doc.trim_end());
+  assert_eq!(truncate_at_sentence("", &data, 10), "");
+}
@@ -0,0 +1,206 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fits the thresholds of a trainer to a small sample of hand segmented
+//! sentences.
+//!
+//! The default thresholds work well for news text, but can be too eager, or
+//! too reluctant to learn abbreviations, collocations, and sentence starters
+//! on other text. `fit_thresholds` searches for the thresholds that segment
+//! a sample best, when a trainer is run on a corpus. The result can be
+//! passed to `Trainer::with_overrides`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use punkt::{Trainer, TrainingData};
+//! # use punkt::params::Standard;
+//! # use punkt::tune::fit_thresholds;
+//! #
+//! let corpus = "The meeting is at 5 p.m. on Monday. Dr. Smith will attend. \
+//!               Prof. Jones will not. The U.S. team arrives at 9 a.m. sharp.";
+//! let sample = ["Dr. Smith left early.", "Prof. Jones stayed.", "It was 5 p.m. then."];
+//! let overrides = fit_thresholds(&sample, corpus);
+//!
+//! let mut data = TrainingData::new();
+//! let trainer: Trainer<Standard> = Trainer::with_overrides(overrides);
+//!
+//! trainer.train(corpus, &mut data);
+//! ```
+
+use std::collections::HashSet;
+
+use prelude::{Standard, TokenizerParameters, TrainerParameters};
+use tokenizer::SentenceByteOffsetTokenizer;
+use trainer::{corpus_counts, CorpusCounts, Overrides, Trainer, TrainingData};
+
+/// Candidate values for `TrainerParameters::ABBREV_LOWER_BOUND`.
+const ABBREV_LOWER_BOUNDS: &'static [f64] = &[0.1, 0.2, 0.3, 0.5, 1.0];
+
+/// Candidate values for `TrainerParameters::COLLOCATION_LOWER_BOUND`.
+const COLLOCATION_LOWER_BOUNDS: &'static [f64] = &[3.0, 5.0, 7.88, 10.0, 15.0];
+
+/// Candidate values for `TrainerParameters::SENTENCE_STARTER_LOWER_BOUND`.
+const SENTENCE_STARTER_LOWER_BOUNDS: &'static [f64] = &[10.0, 15.0, 30.0, 50.0, 100.0];
+
+/// Fits the thresholds with the standard parameters. See
+/// `fit_thresholds_with_params`.
+#[inline]
+pub fn fit_thresholds(sample: &[&str], corpus: &str) -> Overrides {
+  fit_thresholds_with_params::<Standard>(sample, corpus)
+}
+
+/// Returns the thresholds that maximize the F1 score of the sentence breaks
+/// found in a sample, when a trainer is run on a corpus. The sample is a
+/// sequence of sentences, that are joined by a space. The thresholds of the
+/// parameters are tried first, so they are kept unless other thresholds
+/// score better.
+pub fn fit_thresholds_with_params<P>(sample: &[&str], corpus: &str) -> Overrides
+where
+  P: TokenizerParameters + TrainerParameters,
+{
+  let sample = Sample::new(sample);
+  let counts = corpus_counts::<P>(corpus);
+  let mut best = (-1f64, Overrides::default());
+
+  for &abbrev in candidates(P::ABBREV_LOWER_BOUND, ABBREV_LOWER_BOUNDS).iter() {
+    for &collocation in candidates(P::COLLOCATION_LOWER_BOUND, COLLOCATION_LOWER_BOUNDS).iter() {
+      for &starter in candidates(
+        P::SENTENCE_STARTER_LOWER_BOUND,
+        SENTENCE_STARTER_LOWER_BOUNDS,
+      )
+      .iter()
+      {
+        let overrides = Overrides {
+          abbrev_lower_bound: Some(abbrev),
+          collocation_lower_bound: Some(collocation),
+          sentence_starter_lower_bound: Some(starter),
+        };
+        let score = sample.score::<P>(&counts, overrides);
+
+        if score > best.0 {
+          best = (score, overrides);
+        }
+      }
+    }
+  }
+
+  best.1
+}
+
+/// Hand segmented sentences, joined by a space, and the offsets of the
+/// breaks between them.
+struct Sample {
+  doc: String,
+  breaks: HashSet<usize>,
+}
+
+impl Sample {
+  fn new(sentences: &[&str]) -> Sample {
+    let mut doc = String::new();
+    let mut breaks = HashSet::new();
+
+    for (i, sent) in sentences.iter().enumerate() {
+      if i > 0 {
+        breaks.insert(doc.len());
+        doc.push(' ');
+      }
+
+      doc.push_str(sent.trim());
+    }
+
+    Sample {
+      doc: doc,
+      breaks: breaks,
+    }
+  }
+
+  /// Trains on the counts of a corpus with the overrides, and scores the
+  /// breaks that are found in the sample.
+  fn score<P>(&self, counts: &CorpusCounts, overrides: Overrides) -> f64
+  where
+    P: TokenizerParameters + TrainerParameters,
+  {
+    let mut data = TrainingData::new();
+
+    Trainer::<P>::with_overrides(overrides).finalize_counts(counts.clone(), &mut data);
+
+    let found: HashSet<usize> = SentenceByteOffsetTokenizer::<P>::new(&self.doc, &data)
+      .map(|(_, end)| end)
+      .filter(|&end| end < self.doc.len())
+      .collect();
+
+    f1_score(&found, &self.breaks)
+  }
+}
+
+/// The value of the parameters, followed by the other candidates.
+#[inline]
+fn candidates(default: f64, values: &[f64]) -> Vec<f64> {
+  let mut v = vec![default];
+
+  v.extend(values.iter().filter(|&&x| x != default));
+  v
+}
+
+/// The F1 score of the breaks that were found, against the expected breaks.
+fn f1_score(found: &HashSet<usize>, gold: &HashSet<usize>) -> f64 {
+  if found.is_empty() && gold.is_empty() {
+    return 1f64;
+  }
+
+  let hits = found.intersection(gold).count() as f64;
+
+  if hits == 0f64 {
+    0f64
+  } else {
+    let precision = hits / found.len() as f64;
+    let recall = hits / gold.len() as f64;
+
+    2f64 * precision * recall / (precision + recall)
+  }
+}
+
+#[test]
+fn fit_thresholds_test() {
+  let corpus = include_str!("../test/raw/ny-times-article-01.txt");
+  let sentences = [
+    "Mr. Smith said the deal was done.",
+    "It closed at 4 p.m. on Friday.",
+    "The company, based in St. Louis, declined to comment.",
+    "Shares rose 3 percent.",
+  ];
+  let overrides = fit_thresholds(&sentences, corpus);
+  let sample = Sample::new(&sentences);
+  let counts = corpus_counts::<Standard>(corpus);
+
+  assert!(overrides.abbrev_lower_bound.is_some());
+  assert!(
+    sample.score::<Standard>(&counts, overrides)
+      >= sample.score::<Standard>(&counts, Overrides::default())
+  );
+
+  let mut data = TrainingData::new();
+
+  Trainer::<Standard>::with_overrides(overrides).train(corpus, &mut data);
+
+  assert_eq!(
+    data.metadata().params().unwrap().abbrev_lower_bound(),
+    &overrides.abbrev_lower_bound.unwrap()
+  );
+}
+
+#[test]
+fn f1_score_test() {
+  let found: HashSet<usize> = vec![1, 2, 3].into_iter().collect();
+  let gold: HashSet<usize> = vec![2, 3].into_iter().collect();
+
+  assert_eq!(f1_score(&HashSet::new(), &HashSet::new()), 1f64);
+  assert_eq!(f1_score(&HashSet::new(), &gold), 0f64);
+  assert_eq!(f1_score(&found, &gold), 0.8);
+}
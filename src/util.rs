@@ -6,58 +6,173 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use token::Token;
-use trainer::TrainingData;
-use prelude::DefinesSentenceEndings;
+// The tokenizers never panic, whatever document they are given (see the
+// crate docs), so the lints for code that can panic are errors here.
+#![cfg_attr(
+  not(test),
+  deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::string_slice,
+    clippy::unreachable,
+    clippy::unwrap_used
+  )
+)]
 
-use num::Float;
+use model::Model;
+use prelude::DefinesSentenceEndings;
+use score::{Score, ScoreMath};
+use token::Token;
 
 /// Peforms a first pass annotation on a Token.
-pub fn annotate_first_pass<P: DefinesSentenceEndings>(tok: &mut Token, data: &TrainingData) {
-  let is_split_abbrev = tok
-    .tok()
-    .rsplitn(1, '-')
-    .next()
-    .map(|s| data.contains_abbrev(s))
-    .unwrap_or(false);
-
-  if tok.tok().len() == 1 && P::is_sentence_ending(&tok.tok().chars().nth(0).unwrap()) {
+pub fn annotate_first_pass<P, D>(tok: &mut Token, data: &D)
+where
+  P: DefinesSentenceEndings,
+  D: Model,
+{
+  // The characters inside of a protected token never end a sentence, but a
+  // sentence ending at its end still does.
+  if tok.is_protected() {
+    match tok.tok().chars().next_back() {
+      Some(c) if P::is_sentence_ending(&c) => tok.set_is_sentence_break(true),
+      _ => (),
+    }
+
+    return;
+  }
+
+  let is_single_ending = {
+    let mut chars = tok.tok().chars();
+
+    match (chars.next(), chars.next()) {
+      (Some(c), None) => P::is_sentence_ending(&c),
+      _ => false,
+    }
+  };
+
+  if is_single_ending {
     tok.set_is_sentence_break(true);
   } else if tok.has_final_period() && !tok.is_ellipsis() {
+    let is_split_abbrev = tok
+      .tok()
+      .rsplitn(1, '-')
+      .next()
+      .map(|s| data.contains_abbrev(s))
+      .unwrap_or(false);
+
     if is_split_abbrev || data.contains_abbrev(tok.tok_without_period()) {
       tok.set_is_abbrev(true);
     } else {
       tok.set_is_sentence_break(true);
     }
+  } else if !tok.has_final_period() {
+    // A period followed by trailing characters ends a sentence, like a final
+    // period, unless the token is an abbreviation.
+    let is_abbrev = trailed_stem::<P>(tok.tok()).map(|stem| data.contains_abbrev(stem));
+
+    match is_abbrev {
+      Some(true) => tok.set_is_abbrev(true),
+      Some(false) => tok.set_is_sentence_break(true),
+      None => (),
+    }
+  }
+}
+
+/// Returns the token without its period, if the period is followed by
+/// characters that can trail the period of an abbreviation (`nr.»`).
+/// Ellipses (`...»`) are left alone.
+fn trailed_stem<P>(tok: &str) -> Option<&str>
+where
+  P: DefinesSentenceEndings,
+{
+  if P::ABBREV_TRAILING_CHARS.len() == 0 {
+    return None;
+  }
+
+  let stem = tok.trim_end_matches(|c| P::is_abbrev_trailing_char(&c));
+
+  if stem.len() < tok.len() && stem.len() > 1 {
+    match stem.strip_suffix('.') {
+      Some(stem) if !stem.ends_with('.') => return Some(stem),
+      _ => (),
+    }
   }
+
+  None
 }
 
-pub fn dunning_log_likelihood(count_a: f64, count_b: f64, count_ab: f64, n: f64) -> f64 {
+pub fn dunning_log_likelihood(count_a: Score, count_b: Score, count_ab: Score, n: Score) -> Score {
+  let one = Score::from_count(1);
   let p1 = count_b / n;
-  let p2 = 0.99;
-  let nullh = count_ab * p1.ln() + (count_a - count_ab) * (1.0 - p1).ln();
-  let alth = count_ab * p2.ln() + (count_a - count_ab) * (1.0 - p2).ln();
+  let p2 = Score::from_f64(0.99);
+  let nullh = count_ab * p1.ln() + (count_a - count_ab) * (one - p1).ln();
+  let alth = count_ab * p2.ln() + (count_a - count_ab) * (one - p2).ln();
 
-  -2.0 * (nullh - alth)
+  -Score::from_count(2) * (nullh - alth)
 }
 
-pub fn col_log_likelihood(count_a: f64, count_b: f64, count_ab: f64, n: f64) -> f64 {
+pub fn col_log_likelihood(count_a: Score, count_b: Score, count_ab: Score, n: Score) -> Score {
+  let one = Score::from_count(1);
   let p = count_b / n;
   let p1 = count_ab / count_a;
   let p2 = (count_b - count_ab) / (n - count_a);
 
-  let s1 = count_ab * p.ln() + (count_a - count_ab) * (1.0 - p).ln();
-  let s2 = (count_b - count_ab) * p.ln() + (n - count_a - count_b + count_ab) * (1.0 - p).ln();
+  let s1 = count_ab * p.ln() + (count_a - count_ab) * (one - p).ln();
+  let s2 = (count_b - count_ab) * p.ln() + (n - count_a - count_b + count_ab) * (one - p).ln();
   let s3 = if count_a == count_ab {
-    0f64
+    Score::from_count(0)
   } else {
-    count_ab * p1.ln() + (count_a - count_ab) * (1.0 - p1).ln()
+    count_ab * p1.ln() + (count_a - count_ab) * (one - p1).ln()
   };
   let s4 = if count_b == count_ab {
-    0f64
+    Score::from_count(0)
   } else {
-    (count_b - count_ab) * p2.ln() + (n - count_a - count_b + count_ab) * (1.0 - p2).ln()
+    (count_b - count_ab) * p2.ln() + (n - count_a - count_b + count_ab) * (one - p2).ln()
   };
 
-  -2.0 * (s1 + s2 - s3 - s4)
+  -Score::from_count(2) * (s1 + s2 - s3 - s4)
+}
+
+#[test]
+fn annotate_first_pass_trailing_chars_test() {
+  use phf::phf_set;
+  use prelude::{Set, Standard};
+  use tokenizer::WordTokenizer;
+  use trainer::TrainingData;
+
+  struct Custom;
+
+  impl DefinesSentenceEndings for Custom {
+    const ABBREV_TRAILING_CHARS: &'static Set<char> = &phf_set!['\u{bb}', '\u{2014}'];
+  }
+
+  let mut data = TrainingData::new();
+
+  data.insert_abbrev("nr");
+  data.insert_abbrev("usw");
+
+  let annotate = |doc: &str, abbrev: bool| -> Vec<bool> {
+    WordTokenizer::<Standard>::new(doc)
+      .map(|mut t| {
+        if abbrev {
+          annotate_first_pass::<Custom, _>(&mut t, &data);
+        } else {
+          annotate_first_pass::<Standard, _>(&mut t, &data);
+        }
+        t.is_abbrev()
+      })
+      .collect()
+  };
+
+  let doc = "Siehe Nr.\u{bb} und usw.\u{2014} oder Berlin.\u{bb} und nr. und \u{bb}";
+
+  assert_eq!(
+    annotate(doc, true),
+    vec![false, true, false, true, false, false, false, true, false, false]
+  );
+  assert_eq!(
+    annotate(doc, false),
+    vec![false, false, false, false, false, false, false, true, false, false]
+  );
 }
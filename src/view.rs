@@ -0,0 +1,432 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use format::{write_str, write_u32, LoadError, Reader, MODEL_VERSION};
+use model::Model;
+use ortho::OrthoContext;
+use trainer::TrainingData;
+
+/// Magic bytes at the start of a model in the view format.
+const VIEW_MAGIC: &'static [u8] = b"PNKV";
+
+/// The first model version that can be written in the view format.
+const MIN_VIEW_VERSION: u32 = 4;
+
+/// Training data that is read in place from a model in the view format,
+/// written by `TrainingData::to_view_bytes`, without copying it.
+///
+/// Each part of the model is a table of sorted entries, with an index of
+/// their offsets, which is searched with a binary search. Creating a view
+/// only reads the header of each table, so it takes the same time for any
+/// size of model. The bytes are usually a memory-mapped file, so every
+/// process that segments with the same model shares one copy of it in the
+/// page cache. This crate doesn't depend on a crate for memory mapping:
+///
+/// ```ignore
+/// let file = File::open("english.pnkv")?;
+/// let map = unsafe { memmap2::Mmap::map(&file)? };
+/// let view = TrainingDataView::new(&map)?;
+///
+/// for s in SentenceTokenizer::<Standard, _>::new(doc, &view) {
+///   println!("{:?}", s);
+/// }
+/// ```
+///
+/// The entries are only checked when they are looked up. A malformed entry
+/// is never matched, rather than being an error. The metadata of the model
+/// isn't stored in the view format.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "english")]
+/// # {
+/// # use punkt::{SentenceTokenizer, TrainingData, TrainingDataView};
+/// # use punkt::params::Standard;
+/// #
+/// let bytes = TrainingData::english().to_view_bytes();
+/// let view = TrainingDataView::new(&bytes).unwrap();
+/// let doc = "Mr. Smith left. He was tired.";
+///
+/// assert!(view.contains_abbrev("mr"));
+/// assert_eq!(SentenceTokenizer::<Standard, _>::new(doc, &view).count(), 2);
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct TrainingDataView<'a> {
+  abbrevs: Table<'a>,
+  collocations: Table<'a>,
+  sentence_starters: Table<'a>,
+  orthographic_contexts: Table<'a>,
+  abbrev_contexts: Table<'a>,
+}
+
+impl<'a> TrainingDataView<'a> {
+  /// Creates a view of a model in the view format.
+  pub fn new(bytes: &'a [u8]) -> Result<TrainingDataView<'a>, LoadError> {
+    let mut r = Reader { bytes };
+
+    if r.take(VIEW_MAGIC.len())? != VIEW_MAGIC {
+      return Err(LoadError::Binary("missing magic bytes"));
+    }
+
+    match r.u32()? {
+      v if v > MODEL_VERSION => return Err(LoadError::UnsupportedVersion(v)),
+      v if v < MIN_VIEW_VERSION => return Err(LoadError::Binary("invalid version")),
+      _ => (),
+    }
+
+    let view = TrainingDataView {
+      abbrevs: Table::read(&mut r)?,
+      collocations: Table::read(&mut r)?,
+      sentence_starters: Table::read(&mut r)?,
+      orthographic_contexts: Table::read(&mut r)?,
+      abbrev_contexts: Table::read(&mut r)?,
+    };
+
+    if !r.bytes.is_empty() {
+      return Err(LoadError::Binary("trailing bytes"));
+    }
+
+    Ok(view)
+  }
+
+  /// Check if a token is considered to be an abbreviation.
+  #[inline]
+  pub fn contains_abbrev(&self, tok: &str) -> bool {
+    self.abbrevs.find(tok, None).is_some()
+  }
+
+  /// Checks if a pair of words are commonly known to appear together.
+  #[inline]
+  pub fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    self.collocations.find(left, Some(right)).is_some()
+  }
+
+  /// Check if a token is considered to be a token that commonly starts a
+  /// sentence.
+  #[inline]
+  pub fn contains_sentence_starter(&self, tok: &str) -> bool {
+    self.sentence_starters.find(tok, None).is_some()
+  }
+
+  /// Gets the orthographic context for a token. Returns an empty context if
+  /// the token was not yet encountered.
+  #[inline]
+  pub fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    let bits = self
+      .orthographic_contexts
+      .find(tok, None)
+      .and_then(|mut r| r.take(1).ok())
+      .map(|b| b[0])
+      .unwrap_or(0);
+
+    OrthoContext::from_bits(bits)
+  }
+
+  /// Checks if an abbreviation can be followed by a word without ending the
+  /// sentence. True for abbreviations without right contexts.
+  #[inline]
+  pub fn allows_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    !self.abbrev_contexts.contains_left(abbrev)
+      || self.abbrev_contexts.find(abbrev, Some(right)).is_some()
+  }
+
+  /// The number of abbreviations in the model.
+  #[inline]
+  pub fn abbrev_count(&self) -> usize {
+    self.abbrevs.len
+  }
+}
+
+/// Summarizes the sizes of the model, instead of listing its contents.
+impl<'a> fmt::Debug for TrainingDataView<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("TrainingDataView")
+      .field("abbrevs", &self.abbrevs.len)
+      .field("collocations", &self.collocations.len)
+      .field("sentence_starters", &self.sentence_starters.len)
+      .field("orthographic_contexts", &self.orthographic_contexts.len)
+      .field("abbrev_contexts", &self.abbrev_contexts.len)
+      .finish()
+  }
+}
+
+impl<'a> Model for TrainingDataView<'a> {
+  #[inline(always)]
+  fn contains_abbrev(&self, tok: &str) -> bool {
+    TrainingDataView::contains_abbrev(self, tok)
+  }
+
+  #[inline(always)]
+  fn contains_collocation(&self, left: &str, right: &str) -> bool {
+    TrainingDataView::contains_collocation(self, left, right)
+  }
+
+  #[inline(always)]
+  fn contains_sentence_starter(&self, tok: &str) -> bool {
+    TrainingDataView::contains_sentence_starter(self, tok)
+  }
+
+  #[inline(always)]
+  fn get_orthographic_context(&self, tok: &str) -> OrthoContext {
+    TrainingDataView::get_orthographic_context(self, tok)
+  }
+
+  #[inline(always)]
+  fn allows_abbrev_context(&self, abbrev: &str, right: &str) -> bool {
+    TrainingDataView::allows_abbrev_context(self, abbrev, right)
+  }
+}
+
+/// A table of sorted entries. The table starts with the number of entries,
+/// and the offset of each entry from the start of the table, followed by the
+/// entries. An entry is a string, or a pair of strings, followed by the
+/// value of the entry, if there is one.
+#[derive(Clone, Copy)]
+struct Table<'a> {
+  bytes: &'a [u8],
+  len: usize,
+}
+
+impl<'a> Table<'a> {
+  /// Reads the length of a table, and the table from the front of a reader.
+  fn read(r: &mut Reader<'a>) -> Result<Table<'a>, LoadError> {
+    let size = r.u32()? as usize;
+    let bytes = r.take(size)?;
+    let len = Reader { bytes }.u32()? as usize;
+
+    if len > (size - 4) / 4 {
+      return Err(LoadError::Binary("table index is out of bounds"));
+    }
+
+    Ok(Table { bytes, len })
+  }
+
+  /// Returns a reader at the start of an entry.
+  #[inline]
+  fn entry(&self, i: usize) -> Option<Reader<'a>> {
+    let offset = Reader {
+      bytes: self.bytes.get(4 + i * 4..)?,
+    }
+    .u32()
+    .ok()? as usize;
+
+    self.bytes.get(offset..).map(|bytes| Reader { bytes })
+  }
+
+  /// Compares the key of an entry to a string, or a pair of strings.
+  /// Returns the ordering, and a reader after the key. A malformed entry is
+  /// ordered before every key.
+  #[inline]
+  fn compare(&self, i: usize, left: &str, right: Option<&str>) -> (Ordering, Option<Reader<'a>>) {
+    let mut r = match self.entry(i) {
+      Some(r) => r,
+      None => return (Ordering::Less, None),
+    };
+
+    match (read_bytes(&mut r), right) {
+      (Some(l), None) => (l.cmp(left.as_bytes()), Some(r)),
+      (Some(l), Some(right)) => match l.cmp(left.as_bytes()) {
+        Ordering::Equal => match read_bytes(&mut r) {
+          Some(rt) => (rt.cmp(right.as_bytes()), Some(r)),
+          None => (Ordering::Less, None),
+        },
+        ord => (ord, Some(r)),
+      },
+      (None, _) => (Ordering::Less, None),
+    }
+  }
+
+  /// Finds the entry with a key, and returns a reader at its value.
+  fn find(&self, left: &str, right: Option<&str>) -> Option<Reader<'a>> {
+    let (mut lo, mut hi) = (0, self.len);
+
+    while lo < hi {
+      let mid = lo + (hi - lo) / 2;
+
+      match self.compare(mid, left, right) {
+        (Ordering::Less, _) => lo = mid + 1,
+        (Ordering::Greater, _) => hi = mid,
+        (Ordering::Equal, r) => return r,
+      }
+    }
+
+    None
+  }
+
+  /// Checks if there is a pair of strings with a left string.
+  fn contains_left(&self, left: &str) -> bool {
+    // The first pair with the left string is the first one that isn't
+    // ordered before a pair with an empty right string.
+    let (mut lo, mut hi) = (0, self.len);
+
+    while lo < hi {
+      let mid = lo + (hi - lo) / 2;
+
+      match self.compare(mid, left, Some("")) {
+        (Ordering::Less, _) => lo = mid + 1,
+        _ => hi = mid,
+      }
+    }
+
+    self
+      .entry(lo)
+      .filter(|_| lo < self.len)
+      .and_then(|mut r| read_bytes(&mut r))
+      .map(|l| l == left.as_bytes())
+      .unwrap_or(false)
+  }
+}
+
+/// Reads a string, without checking that it is valid UTF-8.
+#[inline]
+fn read_bytes<'a>(r: &mut Reader<'a>) -> Option<&'a [u8]> {
+  let len = r.u32().ok()? as usize;
+
+  r.take(len).ok()
+}
+
+/// Writes a table of sorted entries. See `Table`.
+fn write_table<T, F>(out: &mut Vec<u8>, entries: &[T], write_entry: F)
+where
+  F: Fn(&mut Vec<u8>, &T),
+{
+  let mut body = Vec::new();
+  let mut offset = 4 + entries.len() * 4;
+
+  write_u32(&mut body, entries.len());
+
+  let mut records = Vec::new();
+
+  for entry in entries {
+    let start = records.len();
+
+    write_u32(&mut body, offset);
+    write_entry(&mut records, entry);
+    offset += records.len() - start;
+  }
+
+  body.extend_from_slice(&records);
+  write_u32(out, body.len());
+  out.extend_from_slice(&body);
+}
+
+impl TrainingData {
+  /// Serializes the data into the view format, that can be read in place by
+  /// a `TrainingDataView`. Entries are written in sorted order, so the output
+  /// is deterministic. The metadata isn't written.
+  pub fn to_view_bytes(&self) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut abbrevs: Vec<&str> = self.abbrevs().collect();
+    let mut collocations: Vec<(&str, &str)> = self.collocations().collect();
+    let mut sentence_starters: Vec<&str> = self.sentence_starters().collect();
+    let mut orthographic_contexts: Vec<(&str, u8)> = self
+      .orthographic_contexts()
+      .map(|(s, c)| (s, c.bits()))
+      .collect();
+    let mut abbrev_contexts: Vec<(&str, &str)> = self.abbrev_contexts().collect();
+
+    abbrevs.sort();
+    collocations.sort();
+    sentence_starters.sort();
+    orthographic_contexts.sort();
+    abbrev_contexts.sort();
+
+    out.extend_from_slice(VIEW_MAGIC);
+    write_u32(&mut out, MODEL_VERSION as usize);
+    write_table(&mut out, &abbrevs, |out, s| write_str(out, s));
+    write_table(&mut out, &collocations, |out, &(l, r)| {
+      write_str(out, l);
+      write_str(out, r);
+    });
+    write_table(&mut out, &sentence_starters, |out, s| write_str(out, s));
+    write_table(&mut out, &orthographic_contexts, |out, &(s, c)| {
+      write_str(out, s);
+      out.push(c);
+    });
+    write_table(&mut out, &abbrev_contexts, |out, &(a, r)| {
+      write_str(out, a);
+      write_str(out, r);
+    });
+
+    out
+  }
+}
+
+#[cfg(feature = "english")]
+#[test]
+fn training_data_view_test() {
+  use prelude::Standard;
+  use tokenizer::SentenceByteOffsetTokenizer;
+
+  let mut data = TrainingData::english();
+
+  data.insert_abbrev("no");
+  data.insert_abbrev_context("no", "5");
+
+  let bytes = data.to_view_bytes();
+  let view = TrainingDataView::new(&bytes).unwrap();
+
+  assert_eq!(view.abbrev_count(), data.abbrevs().count());
+
+  for tok in data.abbrevs() {
+    assert!(view.contains_abbrev(tok));
+  }
+
+  for (l, r) in data.collocations() {
+    assert!(view.contains_collocation(l, r));
+  }
+
+  for tok in data.sentence_starters() {
+    assert!(view.contains_sentence_starter(tok));
+  }
+
+  for (tok, c) in data.orthographic_contexts() {
+    assert_eq!(view.get_orthographic_context(tok), c);
+  }
+
+  assert!(!view.contains_abbrev("zzzz"));
+  assert!(!view.contains_collocation("##number##", "zzzz"));
+  assert!(!view.contains_sentence_starter("zzzz"));
+  assert_eq!(view.get_orthographic_context("zzzz"), OrthoContext::empty());
+  assert!(view.allows_abbrev_context("no", "##number##"));
+  assert!(!view.allows_abbrev_context("no", "then"));
+  assert!(view.allows_abbrev_context("mr", "then"));
+
+  // A view segments the same as the data it was written from.
+  let doc = include_str!("../test/raw/npr-article-01.txt");
+  let expected: Vec<_> = SentenceByteOffsetTokenizer::<Standard>::new(doc, &data).collect();
+  let actual: Vec<_> = SentenceByteOffsetTokenizer::<Standard, _>::new(doc, &view).collect();
+
+  assert_eq!(actual, expected);
+
+  // Truncated, and corrupted views are rejected, or never match.
+  for i in 0..bytes.len().min(64) {
+    assert!(TrainingDataView::new(&bytes[..i]).is_err());
+  }
+
+  let mut corrupted = bytes.clone();
+
+  for b in corrupted[12..].iter_mut().step_by(7) {
+    *b = 0xff;
+  }
+
+  if let Ok(view) = TrainingDataView::new(&corrupted) {
+    let _ = view.contains_abbrev("mr");
+    let _ = view.allows_abbrev_context("no", "5");
+  }
+
+  let empty = TrainingData::new().to_view_bytes();
+
+  assert!(!TrainingDataView::new(&empty).unwrap().contains_abbrev("mr"));
+  assert!(TrainingDataView::new(&data.to_binary()).is_err());
+}
@@ -0,0 +1,281 @@
+// Copyright 2016 rust-punkt developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use notify::{self, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use format::LoadError;
+use trainer::TrainingData;
+
+/// A JSON model file that is reloaded when it changes, so a long running
+/// service can pick up a retrained model without restarting. Requires the
+/// `notify` feature.
+///
+/// Changes are found through file system events from the `notify` crate.
+/// The directory of the file is watched, so a file that is replaced by a
+/// rename is picked up too. If events aren't available, like on some network
+/// file systems, the modification time of the file is polled instead. Some
+/// file systems only store the time to the second, or coarser, so while
+/// polling, a file that is written again within the same second as the
+/// version that was loaded isn't reloaded, until it changes again.
+///
+/// Tokenizers borrow a snapshot of the model, which is never changed. A
+/// reload replaces the model for later snapshots, while earlier snapshots
+/// stay valid until they are dropped. If the file can't be loaded, like
+/// while it's only partly written, the previous model is kept. Writing the
+/// new model to a temporary file, and renaming it over the old one avoids
+/// this.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # use punkt::{SentenceTokenizer, WatchedModel};
+/// # use punkt::params::Standard;
+/// #
+/// let model = Arc::new(WatchedModel::open("models/news.json").unwrap());
+///
+/// WatchedModel::watch(&model, Duration::from_secs(5));
+///
+/// let data = model.snapshot();
+///
+/// for s in SentenceTokenizer::<Standard>::new("One. Two.", &data) {
+///   println!("{:?}", s);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WatchedModel {
+  path: PathBuf,
+  current: RwLock<Arc<TrainingData>>,
+  modified: Mutex<Option<SystemTime>>,
+}
+
+impl WatchedModel {
+  /// Loads a model from a JSON file, and remembers the file to reload it.
+  pub fn open<T: AsRef<Path>>(path: T) -> Result<WatchedModel, LoadError> {
+    let path = path.as_ref().to_path_buf();
+    let modified = modified_time(&path);
+    let data = TrainingData::from_json_file(&path)?;
+
+    Ok(WatchedModel {
+      path: path,
+      current: RwLock::new(Arc::new(data)),
+      modified: Mutex::new(modified),
+    })
+  }
+
+  /// The path of the model file.
+  #[inline(always)]
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Returns the current model.
+  #[inline]
+  pub fn snapshot(&self) -> Arc<TrainingData> {
+    match self.current.read() {
+      Ok(data) => data.clone(),
+      Err(poisoned) => poisoned.into_inner().clone(),
+    }
+  }
+
+  /// Reloads the model if the modification time of the file changed since
+  /// it was last loaded. Returns `true` if a new model was loaded. On error,
+  /// the previous model is kept, and the file is loaded again on the next
+  /// call. A change within the same tick of the modification time as the
+  /// last load is missed, see `WatchedModel`.
+  pub fn reload_if_changed(&self) -> Result<bool, LoadError> {
+    let last = match self.modified.lock() {
+      Ok(last) => *last,
+      Err(poisoned) => *poisoned.into_inner(),
+    };
+    let modified = modified_time(&self.path);
+
+    if modified.is_some() && modified == last {
+      return Ok(false);
+    }
+
+    self.reload().map(|_| true)
+  }
+
+  /// Loads the file again, whether it changed or not. On error, the previous
+  /// model is kept.
+  pub fn reload(&self) -> Result<(), LoadError> {
+    let mut last = match self.modified.lock() {
+      Ok(last) => last,
+      Err(poisoned) => poisoned.into_inner(),
+    };
+    let modified = modified_time(&self.path);
+    let data = Arc::new(TrainingData::from_json_file(&self.path)?);
+
+    match self.current.write() {
+      Ok(mut current) => *current = data,
+      Err(poisoned) => *poisoned.into_inner() = data,
+    }
+
+    *last = modified;
+
+    Ok(())
+  }
+
+  /// Spawns a thread that reloads the model when the file changes. The
+  /// thread stops once the model is dropped. Errors are ignored, so the
+  /// previous model is kept until the file can be loaded.
+  ///
+  /// The file is watched for file system events, and `interval` is how often
+  /// the thread checks whether the model was dropped. If a watcher can't be
+  /// started, or it stops, the file is polled every `interval` instead.
+  pub fn watch(model: &Arc<WatchedModel>, interval: Duration) -> JoinHandle<()> {
+    let weak: Weak<WatchedModel> = Arc::downgrade(model);
+    let events = event_watcher(&model.path);
+
+    thread::spawn(move || {
+      if let Some((_watcher, rx)) = events {
+        let name = weak
+          .upgrade()
+          .and_then(|m| m.path.file_name().map(|n| n.to_owned()));
+
+        loop {
+          let changed = match rx.recv_timeout(interval) {
+            Ok(event) => touches(&event, name.as_ref().map(|n| n.as_ref())),
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => break,
+          };
+
+          // Coalesces a burst of events, like from a write in parts, into a
+          // single reload.
+          let changed = rx.try_iter().fold(changed, |c, e| {
+            touches(&e, name.as_ref().map(|n| n.as_ref())) || c
+          });
+
+          match weak.upgrade() {
+            Some(model) => {
+              if changed {
+                let _ = model.reload();
+              }
+            }
+            None => return,
+          }
+        }
+      }
+
+      // Falls back to polling.
+      loop {
+        thread::sleep(interval);
+
+        match weak.upgrade() {
+          Some(model) => {
+            let _ = model.reload_if_changed();
+          }
+          None => return,
+        }
+      }
+    })
+  }
+}
+
+/// Starts watching the directory of a file for file system events. Returns
+/// `None` if a watcher can't be started.
+fn event_watcher(path: &Path) -> Option<(RecommendedWatcher, Receiver<Event>)> {
+  let (tx, rx) = mpsc::channel();
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir,
+    _ => Path::new("."),
+  };
+
+  let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+    if let Ok(event) = res {
+      let _ = tx.send(event);
+    }
+  })
+  .ok()?;
+
+  watcher.watch(dir, RecursiveMode::NonRecursive).ok()?;
+
+  Some((watcher, rx))
+}
+
+/// Returns `true` if an event may have changed the contents of a file with
+/// the given name.
+#[inline]
+fn touches(event: &Event, name: Option<&::std::ffi::OsStr>) -> bool {
+  match event.kind {
+    EventKind::Access(_) => false,
+    _ => event.paths.iter().any(|p| p.file_name() == name),
+  }
+}
+
+/// The modification time of a file, if it can be read.
+#[inline]
+fn modified_time(path: &Path) -> Option<SystemTime> {
+  fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[test]
+fn watched_model_test() {
+  use std::env;
+
+  let dir = env::temp_dir().join(format!("punkt-watch-{}", ::std::process::id()));
+  let path = dir.join("model.json");
+  let mut data = TrainingData::new();
+
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(&path, data.to_json()).unwrap();
+
+  let model = Arc::new(WatchedModel::open(&path).unwrap());
+  let before = model.snapshot();
+
+  assert_eq!(model.path(), path.as_path());
+  assert!(!model.reload_if_changed().unwrap());
+
+  // A partly written file keeps the previous model. The write can happen in
+  // the same second as the load, so the time of the load is forgotten.
+  fs::write(&path, "{\"abbrev_types\": [").unwrap();
+  *model.modified.lock().unwrap() = None;
+
+  assert!(model.reload_if_changed().is_err());
+  assert!(Arc::ptr_eq(&before, &model.snapshot()));
+
+  data.insert_abbrev("art");
+  fs::write(&path, data.to_json()).unwrap();
+
+  assert!(model.reload_if_changed().unwrap());
+  assert!(model.snapshot().contains_abbrev("art"));
+  assert!(!before.contains_abbrev("art"));
+
+  // Changes are picked up from events, without calling `reload_if_changed`.
+  let handle = WatchedModel::watch(&model, Duration::from_millis(10));
+
+  data.insert_abbrev("etc");
+  fs::write(&path, data.to_json()).unwrap();
+
+  for _ in 0..500 {
+    if model.snapshot().contains_abbrev("etc") {
+      break;
+    }
+
+    thread::sleep(Duration::from_millis(10));
+  }
+
+  assert!(model.snapshot().contains_abbrev("etc"));
+
+  // The watcher stops once the model is dropped.
+
+  drop(model);
+  handle.join().unwrap();
+
+  fs::remove_dir_all(&dir).unwrap();
+  assert!(WatchedModel::open(&path).is_err());
+}